@@ -0,0 +1,175 @@
+//! Base64 encoding.
+
+use core::fmt;
+
+use crate::ByteYarn;
+use crate::Yarn;
+
+/// An error returned by [`ByteYarn::from_base64()`] and
+/// [`ByteYarn::from_base64_url()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Base64Error {
+  /// The input's length is not possible for base64.
+  InvalidLength,
+  /// The input contained a character outside of the alphabet, at the given
+  /// byte offset.
+  InvalidChar(usize),
+  /// The input was padded incorrectly, or its last character had bits set
+  /// that do not correspond to any decoded byte.
+  InvalidPadding,
+}
+
+impl fmt::Display for Base64Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::InvalidLength => f.write_str("invalid base64 length"),
+      Self::InvalidChar(idx) => {
+        write!(f, "invalid base64 character at offset {idx}")
+      }
+      Self::InvalidPadding => f.write_str("invalid base64 padding"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Base64Error {}
+
+/// A base64 alphabet, which differs only in its last two characters.
+#[derive(Copy, Clone)]
+pub(crate) struct Alphabet {
+  table: &'static [u8; 64],
+  pad: bool,
+}
+
+/// The standard alphabet from RFC 4648, with padding.
+pub(crate) const STANDARD: Alphabet = Alphabet {
+  table: b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+  pad: true,
+};
+
+/// The URL- and filename-safe alphabet from RFC 4648, without padding.
+pub(crate) const URL_SAFE: Alphabet = Alphabet {
+  table: b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+  pad: false,
+};
+
+impl Alphabet {
+  fn decode_char(self, c: u8) -> Option<u32> {
+    let v = match c {
+      b'A'..=b'Z' => c - b'A',
+      b'a'..=b'z' => c - b'a' + 26,
+      b'0'..=b'9' => c - b'0' + 52,
+      _ if c == self.table[62] => 62,
+      _ if c == self.table[63] => 63,
+      _ => return None,
+    };
+    Some(v as u32)
+  }
+}
+
+/// Encodes `bytes` as base64.
+pub(crate) fn encode(bytes: &[u8], alphabet: Alphabet) -> Yarn {
+  let len = match alphabet.pad {
+    true => bytes.len().div_ceil(3) * 4,
+    false => (bytes.len() * 4).div_ceil(3),
+  };
+
+  let mut yarn = Yarn::with_capacity(len);
+  for chunk in bytes.chunks(3) {
+    let mut group = [0; 3];
+    group[..chunk.len()].copy_from_slice(chunk);
+    let n = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+
+    for i in 0..chunk.len() + 1 {
+      let sextet = (n >> (18 - 6 * i)) & 0x3f;
+      yarn.push(alphabet.table[sextet as usize] as char);
+    }
+    if alphabet.pad {
+      for _ in chunk.len()..3 {
+        yarn.push('=');
+      }
+    }
+  }
+  yarn
+}
+
+/// Decodes base64 written with `alphabet`.
+pub(crate) fn decode(
+  text: &str,
+  alphabet: Alphabet,
+) -> Result<ByteYarn, Base64Error> {
+  let mut data = text.as_bytes();
+  if alphabet.pad {
+    if data.len() % 4 != 0 {
+      return Err(Base64Error::InvalidLength);
+    }
+    for _ in 0..2 {
+      data = data.strip_suffix(b"=").unwrap_or(data);
+    }
+  }
+  if data.len() % 4 == 1 {
+    return Err(Base64Error::InvalidLength);
+  }
+
+  let mut yarn = ByteYarn::with_capacity(data.len() * 3 / 4);
+  for (i, chunk) in data.chunks(4).enumerate() {
+    let mut n = 0;
+    for (j, &c) in chunk.iter().enumerate() {
+      let v = alphabet
+        .decode_char(c)
+        .ok_or(Base64Error::InvalidChar(i * 4 + j))?;
+      n |= v << (18 - 6 * j);
+    }
+
+    let [_, bytes @ ..] = n.to_be_bytes();
+    let len = chunk.len() - 1;
+    if bytes[len..].iter().any(|&b| b != 0) {
+      return Err(Base64Error::InvalidPadding);
+    }
+    yarn.extend([&bytes[..len]]);
+  }
+  Ok(yarn)
+}
+
+#[test]
+fn base64_round_trip() {
+  // Test vectors from RFC 4648.
+  let vectors = [
+    ("", ""),
+    ("f", "Zg=="),
+    ("fo", "Zm8="),
+    ("foo", "Zm9v"),
+    ("foob", "Zm9vYg=="),
+    ("fooba", "Zm9vYmE="),
+    ("foobar", "Zm9vYmFy"),
+  ];
+  for (plain, encoded) in vectors {
+    let yarn = ByteYarn::new(plain.as_bytes());
+    assert_eq!(yarn.to_base64(), encoded);
+    assert_eq!(yarn.to_base64_url(), encoded.trim_end_matches('='));
+    assert_eq!(ByteYarn::from_base64(encoded).unwrap(), plain.as_bytes());
+    let url = encoded.trim_end_matches('=');
+    assert_eq!(ByteYarn::from_base64_url(url).unwrap(), plain.as_bytes());
+  }
+
+  let bytes = (0..=255).collect::<ByteYarn>();
+  assert_eq!(ByteYarn::from_base64(&bytes.to_base64()).unwrap(), bytes);
+  assert_eq!(ByteYarn::from_base64_url(&bytes.to_base64_url()).unwrap(), bytes);
+  assert_eq!(ByteYarn::new(b"\xfb\xff").to_base64_url(), "-_8");
+
+  let small = ByteYarn::from_base64("AAECAwQFBgcICQ==").unwrap();
+  assert!(small.is_inlined());
+
+  assert_eq!(ByteYarn::from_base64("Zg="), Err(Base64Error::InvalidLength));
+  assert_eq!(ByteYarn::from_base64("Z==="), Err(Base64Error::InvalidChar(1)));
+  assert_eq!(ByteYarn::from_base64("Zh=="), Err(Base64Error::InvalidPadding));
+  assert_eq!(ByteYarn::from_base64("Zm-v"), Err(Base64Error::InvalidChar(2)));
+  assert_eq!(
+    ByteYarn::from_base64_url("Zg=="),
+    Err(Base64Error::InvalidChar(2))
+  );
+  assert_eq!(
+    ByteYarn::from_base64_url("Zm9vY"),
+    Err(Base64Error::InvalidLength)
+  );
+}
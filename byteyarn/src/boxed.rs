@@ -1,19 +1,54 @@
-use std::alloc::Layout;
-use std::cmp::Ordering;
-use std::fmt;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::marker::PhantomData;
-use std::mem;
-use std::ops::Deref;
-use std::ptr::NonNull;
-use std::slice;
-use std::str;
-use std::str::Utf8Error;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::ffi::NulError;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::char::DecodeUtf16Error;
+use core::cmp::Ordering;
+use core::ffi::CStr;
+use core::fmt;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::iter;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::Add;
+use core::ops::AddAssign;
+use core::ops::Bound;
+use core::ops::Deref;
+use core::ops::RangeBounds;
+use core::ptr::NonNull;
+use core::slice;
+use core::str;
+use core::str::FromStr;
+use core::str::Utf8Error;
 
+#[cfg(feature = "allocator_api")]
+use crate::raw::Allocator;
+use crate::raw::Global;
 use crate::raw::RawYarn;
+#[cfg(feature = "base64")]
+use crate::Base64Error;
+use crate::CharsLossy;
+use crate::Drain;
+use crate::EncodeSet;
+use crate::Float;
+use crate::HexError;
+use crate::Integer;
+use crate::Lines;
+use crate::MatchIndices;
+use crate::Matches;
+use crate::ParseError;
+use crate::Pattern;
+use crate::PercentError;
+use crate::Split;
+use crate::StorageDebug;
 use crate::Utf8Chunks;
 use crate::YarnRef;
+use crate::YarnWriter;
 
 #[cfg(doc)]
 use crate::*;
@@ -28,6 +63,7 @@ use crate::*;
 /// this will be `'static`.
 ///
 /// See the [crate documentation](crate) for general information.
+#[cfg(not(feature = "allocator_api"))]
 #[repr(transparent)]
 pub struct YarnBox<'a, Buf = [u8]>
 where
@@ -37,6 +73,32 @@ where
   _ph: PhantomData<&'a Buf>,
 }
 
+/// An optimized, possibly heap-allocated string type.
+///
+/// This is the core data structure of `byteyarn`. It is a string that can be
+/// borrowed, boxed, or inlined. Generally, you'll want to use the [`Yarn`]
+/// or [`ByteYarn`] type aliases directly, instead.
+///
+/// The lifetime `'a` is the shortest lifetime this yarn can borrow for; often,
+/// this will be `'static`. `A` is the allocator that the yarn's heap buffer,
+/// if it has one, is allocated with; most operations are only available for
+/// the global allocator.
+///
+/// See the [crate documentation](crate) for general information.
+#[cfg(feature = "allocator_api")]
+#[repr(C)]
+pub struct YarnBox<'a, Buf = [u8], A = Global>
+where
+  Buf: crate::Buf + ?Sized,
+  A: Allocator,
+{
+  // This must be first, so that a YarnBox<Buf, Global> can be transmuted from
+  // a RawYarn.
+  raw: RawYarn,
+  alloc: A,
+  _ph: PhantomData<&'a Buf>,
+}
+
 impl<'a, Buf> YarnBox<'a, Buf>
 where
   Buf: crate::Buf + ?Sized,
@@ -55,7 +117,9 @@ where
       // SAFETY: YarnBox is a transparent wrapper over RawYarn; even though
       // YarnBox has a destructor, this is fine, because this lifetime is 'static
       // and will thus never run a destructor.
-      mem::transmute::<&'b RawYarn, &'b Self>(RawYarn::empty())
+      mem::transmute::<&'b RawYarn, &'b Self>(RawYarn::empty_aligned(
+        mem::align_of::<Buf::Element>(),
+      ))
     }
   }
 
@@ -70,6 +134,31 @@ where
     YarnRef::new(buf).to_box()
   }
 
+  /// Returns a new, empty yarn that can hold at least `cap` bytes without
+  /// reallocating.
+  ///
+  /// This only allocates if `cap` is too large for the yarn to be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::with_capacity(64);
+  /// assert!(yarn.is_empty());
+  /// assert!(yarn.capacity() >= 64);
+  ///
+  /// let ptr = yarn.as_ptr();
+  /// for _ in 0..8 {
+  ///   yarn.push_str("meow");
+  /// }
+  /// assert_eq!(yarn.as_ptr(), ptr);
+  /// ```
+  pub fn with_capacity(cap: usize) -> Self {
+    unsafe {
+      // SAFETY: Alignments are always powers of two. The new yarn is either
+      // empty or HEAP, so it is valid for any lifetime.
+      Self::from_raw(RawYarn::with_capacity(Self::align(), cap))
+    }
+  }
+
   /// Returns a new yarn containing the contents of the given slice.
   ///
   /// This function will always return an inlined string, or `None` if the
@@ -77,7 +166,10 @@ where
   /// function, since all `YarnBox`-constructing functions will automatically
   /// inline any small strings passed to them.
   ///
-  /// Note that the maximum inlined size is architecture-dependent.
+  /// Note that the maximum inlined size is architecture-dependent; it is
+  /// given by [`YarnBox::inlined_len()`]. To inline a string if it fits and
+  /// copy it onto the heap otherwise, write
+  /// `Yarn::inlined(s).unwrap_or_else(|| Yarn::from(s.to_string()))`.
   ///
   /// ```
   /// # use byteyarn::*;
@@ -94,6 +186,20 @@ where
     }
   }
 
+  /// Returns the maximum length, in bytes, of a yarn that can be inlined.
+  ///
+  /// This is architecture-dependent: it is 15 on 64-bit targets.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let max = Yarn::inlined_len();
+  /// assert!(Yarn::inlined(&"a".repeat(max)).is_some());
+  /// assert!(Yarn::inlined(&"a".repeat(max + 1)).is_none());
+  /// ```
+  pub const fn inlined_len() -> usize {
+    YarnRef::<Buf>::inlined_len()
+  }
+
   /// Returns a new yarn that aliases the contents of this yarn.
   ///
   /// In effect, this is like `Copy`ing out of `*self`, by shortening the
@@ -168,6 +274,47 @@ where
     YarnRef::<Buf>::from_char(c).to_box()
   }
 
+  /// Returns a yarn containing a single UTF-8-encoded Unicode scalar, given
+  /// as a `u32`.
+  ///
+  /// Returns `None` if `c` is a surrogate or out of range, like
+  /// [`char::from_u32()`]. This function does not allocate.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(Yarn::from_u32(0x1f408).unwrap(), "🐈");
+  /// assert!(Yarn::from_u32(0xd800).is_none());
+  /// assert!(Yarn::from_u32(0x110000).is_none());
+  /// ```
+  pub const fn from_u32(c: u32) -> Option<Self> {
+    match YarnRef::<Buf>::from_u32(c) {
+      Some(y) => Some(y.to_box()),
+      None => None,
+    }
+  }
+
+  /// Removes the contents of this yarn.
+  ///
+  /// Like [`Yarn::truncate()`], this keeps a uniquely owned heap buffer, so
+  /// that it can be reused by later appends.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::with_capacity(64);
+  /// for word in ["jellybeans", "licorice", "gumdrops"] {
+  ///   yarn.clear();
+  ///   yarn.push_str(word);
+  ///   assert_eq!(yarn, word);
+  ///   assert_eq!(yarn.capacity(), 64);
+  /// }
+  /// ```
+  pub fn clear(&mut self) {
+    unsafe {
+      // SAFETY: An empty Buf is always valid.
+      self.truncate_bytes(0)
+    }
+  }
+
   /// Checks whether this yarn is empty.
   ///
   /// ```
@@ -196,6 +343,172 @@ where
     self.as_ref().len()
   }
 
+  /// Returns the number of bytes this yarn can hold without reallocating.
+  ///
+  /// For inlined yarns, this is the maximum inlined size; for yarns that do
+  /// not own their buffer, such as string constants, this is just their
+  /// length.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert!(Yarn::new("smol").capacity() >= 4);
+  /// assert_eq!(Yarn::new("a very long string constant").capacity(), 27);
+  /// ```
+  pub fn capacity(&self) -> usize {
+    unsafe {
+      // SAFETY: This is the alignment this yarn was allocated with.
+      self.raw.capacity(Self::align())
+    }
+  }
+
+  /// Returns whether this yarn's data is stored inline, within the yarn
+  /// itself.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert!(yarn!("jellybeans").is_inlined());
+  /// assert!(!yarn!("{}", "jellybeans".repeat(2)).is_inlined());
+  /// ```
+  pub const fn is_inlined(&self) -> bool {
+    self.raw.is_small()
+  }
+
+  /// Returns whether this yarn points to a buffer that lives forever, such as
+  /// a string constant.
+  ///
+  /// Inlined yarns are not static, even though they can be
+  /// [immortalized](YarnBox::immortalize) without copying.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert!(Yarn::from_static("a very long string constant").is_static());
+  /// assert!(!Yarn::from_static("smol").is_static());
+  /// ```
+  pub const fn is_static(&self) -> bool {
+    self.raw.kind() == RawYarn::STATIC
+  }
+
+  /// Returns whether this yarn points to a heap buffer that it owns, either
+  /// uniquely or by sharing a reference count.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let long = "a string that is too long to inline";
+  /// assert!(Yarn::from(long.to_string()).is_heap());
+  /// assert!(!Yarn::new(long).is_heap());
+  /// ```
+  pub const fn is_heap(&self) -> bool {
+    self.raw.on_heap()
+  }
+
+  /// Returns whether this yarn points to a buffer that it does not own, and
+  /// which may not live forever.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let long = String::from("a string that is too long to inline");
+  /// assert!(YarnBox::<str>::new(&long).is_aliased());
+  /// assert!(!Yarn::from_static("a very long string constant").is_aliased());
+  /// ```
+  pub const fn is_aliased(&self) -> bool {
+    self.raw.kind() == RawYarn::ALIASED
+  }
+
+  /// Returns whether this yarn and `that` point to the exact same bytes of the
+  /// same buffer.
+  ///
+  /// This is a diagnostic for checking that data is actually being shared,
+  /// such as when interning. Inlined yarns never share storage, so this
+  /// always returns `false` if either yarn is inlined, even if they are
+  /// equal.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::from_static("a very long string constant");
+  /// assert!(yarn.same_allocation(&yarn.clone()));
+  /// assert!(!yarn.same_allocation(&Yarn::copy(&yarn)));
+  ///
+  /// let smol = Yarn::from_static("smol");
+  /// assert!(!smol.same_allocation(&smol));
+  /// ```
+  pub fn same_allocation(&self, that: &YarnBox<Buf>) -> bool {
+    self.as_ref().same_allocation(that.as_ref())
+  }
+
+  /// Returns a value whose `Debug` implementation shows how this yarn is
+  /// stored, along with its contents.
+  ///
+  /// This is useful for figuring out why yarns are being copied or allocated.
+  /// The plain `Debug` implementation only shows the contents.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("smol");
+  /// assert_eq!(
+  ///   format!("{:?}", yarn.debug_storage()),
+  ///   r#"Small { len: 4, capacity: 15, data: "smol" }"#,
+  /// );
+  ///
+  /// let yarn = Yarn::from_string("a string that\tdoes not fit".into());
+  /// assert_eq!(
+  ///   format!("{:?}", yarn.debug_storage()),
+  ///   r#"Heap { len: 26, capacity: 26, shared: false, data: "a string that\tdoes not fit" }"#,
+  /// );
+  /// ```
+  pub fn debug_storage(&self) -> StorageDebug<'_, Buf> {
+    unsafe {
+      // SAFETY: The returned value borrows self, so self.raw stays valid.
+      StorageDebug::new(self.raw)
+    }
+  }
+
+  /// Shrinks this yarn's heap buffer, if it uniquely owns one, to fit its
+  /// contents exactly.
+  ///
+  /// If the contents are small enough to be inlined, they are moved inline
+  /// and the heap buffer is freed altogether.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::with_capacity(64);
+  /// yarn.push_str("jellybeans");
+  /// assert_eq!(yarn.capacity(), 64);
+  ///
+  /// yarn.shrink_to_fit();
+  /// assert!(yarn.capacity() < 64);
+  /// assert_eq!(yarn, "jellybeans");
+  /// ```
+  pub fn shrink_to_fit(&mut self) {
+    unsafe {
+      // SAFETY: This is the alignment this yarn was allocated with.
+      self.raw.shrink_to_fit(Self::align())
+    }
+  }
+
+  /// Ensures that at least `additional` more bytes can be appended to this
+  /// yarn without reallocating.
+  ///
+  /// If this yarn does not uniquely own its buffer and the requested capacity
+  /// is too large to be inlined, its contents are copied onto the heap.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("jellybeans");
+  /// yarn.reserve(100);
+  /// assert!(yarn.capacity() >= 110);
+  /// assert_eq!(yarn, "jellybeans");
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the new capacity overflows.
+  pub fn reserve(&mut self, additional: usize) {
+    unsafe {
+      // SAFETY: This is the alignment this yarn was allocated with.
+      self.raw.reserve(Self::align(), additional)
+    }
+  }
+
   /// Converts this yarn into a slice.
   ///
   /// ```
@@ -300,6 +613,11 @@ where
 
   /// Returns a yarn by taking ownership of the given allocation.
   ///
+  /// The allocation is reused rather than copied, but heap yarns store their
+  /// capacity in a header at the start of their allocation, so it is grown to
+  /// make room for one. This may reallocate, and always moves the data, so it
+  /// takes time linear in the length of `bytes`.
+  ///
   /// ```
   /// # use byteyarn::*;
   /// let str = Box::new([0xf0, 0x9f, 0x90, 0x88, 0xe2, 0x80, 0x8d, 0xe2, 0xac, 0x9b]);
@@ -323,32 +641,36 @@ where
   /// assert_eq!(&*empty, "");
   /// ```
   pub fn into_box(self) -> Box<Buf> {
-    if !self.raw.on_heap() {
+    let layout = buf_trait::layout_of(self.as_slice());
+    if self.raw.on_heap() && !self.raw.is_shared() && !self.is_empty() {
+      let raw = self.into_raw();
       unsafe {
-        let layout = buf_trait::layout_of(self.as_slice());
-        let ptr = match layout.size() {
-          0 => NonNull::<Buf::Element>::dangling().as_ptr() as *mut u8,
-          _ => std::alloc::alloc(layout),
-        };
-
-        if ptr.is_null() {
-          std::alloc::handle_alloc_error(layout);
-        }
-
-        let raw = self.into_raw();
-        ptr.copy_from_nonoverlapping(raw.as_slice().as_ptr(), raw.len());
-        return Box::from_raw(buf_trait::as_buf_mut(
-          slice::from_raw_parts_mut(ptr, raw.len()),
-        ));
+        // SAFETY: raw is guaranteed to be on the heap, unshared, and
+        // non-empty, so into_heap() produces an allocation with exactly the
+        // layout of its data; because we called into_raw(), it is uniquely
+        // owned.
+        let (ptr, len) = raw.into_heap(layout.align()).into_raw_parts();
+        return Box::from_raw(Buf::from_bytes_mut(slice::from_raw_parts_mut(
+          ptr, len,
+        )));
       }
     }
 
-    let mut raw = self.into_raw();
     unsafe {
-      // SAFETY: raw is guaranteed to be on the heap, so this slice is on the
-      // heap with the correct layout; because we called into_raw(), this
-      // reference is uniquely owned.
-      Box::from_raw(Buf::from_bytes_mut(raw.as_mut_slice()))
+      let ptr = match layout.size() {
+        0 => NonNull::<Buf::Element>::dangling().as_ptr() as *mut u8,
+        _ => alloc::alloc::alloc(layout),
+      };
+
+      if ptr.is_null() {
+        alloc::alloc::handle_alloc_error(layout);
+      }
+
+      ptr.copy_from_nonoverlapping(self.as_bytes().as_ptr(), self.len());
+      Box::from_raw(buf_trait::as_buf_mut(slice::from_raw_parts_mut(
+        ptr,
+        self.len(),
+      )))
     }
   }
 
@@ -400,6 +722,39 @@ where
     }
   }
 
+  /// Returns a copy of this yarn that owns its data, with any lifetime.
+  ///
+  /// Unlike [`YarnBox::clone()`], which keeps aliasing the same data as the
+  /// original (or shares its reference-counted buffer), this always copies
+  /// the data into a fresh inlined or heap-allocated yarn. This detaches the
+  /// copy from whatever this yarn was borrowing, so it can outlive it.
+  ///
+  /// Unlike [`YarnBox::immortalize()`], this copies even if this yarn is
+  /// already immortal.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let text = String::from("a string borrowed from somewhere else");
+  /// let alias = YarnBox::<str>::new(&text);
+  /// assert!(alias.clone().is_aliased());
+  ///
+  /// let owned: Yarn = alias.to_owned_yarn();
+  /// drop(alias);
+  /// drop(text);
+  /// assert!(owned.is_heap());
+  /// assert_eq!(owned, "a string borrowed from somewhere else");
+  /// ```
+  pub fn to_owned_yarn<'b>(&self) -> YarnBox<'b, Buf> {
+    unsafe {
+      // SAFETY: RawYarn::copy_slice always returns an immortal, uniquely-owned
+      // value.
+      YarnBox::from_raw(RawYarn::copy_slice(
+        buf_trait::layout_of(self.as_slice()),
+        self.as_bytes().as_ptr(),
+      ))
+    }
+  }
+
   /// Returns a yarn consisting of the concatenation of the given slices.
   ///
   /// Does not allocate if the resulting concatenation can be inlined.
@@ -424,6 +779,135 @@ where
     }
   }
 
+  /// Returns a yarn consisting of the given yarns, separated by `sep`.
+  ///
+  /// Does not allocate if the result can be inlined, and otherwise allocates
+  /// exactly once. If there is only one yarn, it is returned as-is, so
+  /// joining a single static or aliased yarn does not copy it.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::join(["foo", "bar", "baz"], ", ");
+  /// assert_eq!(yarn, "foo, bar, baz");
+  ///
+  /// let yarn = Yarn::join([yarn!("{}", 1), yarn!("{}", 2)], "");
+  /// assert_eq!(yarn, "12");
+  ///
+  /// let long = "a string that does not fit inline";
+  /// let yarn = Yarn::join([long], "");
+  /// assert_eq!(yarn.as_slice().as_ptr(), long.as_ptr());
+  /// ```
+  pub fn join<I>(yarns: I, sep: impl AsRef<Buf>) -> Self
+  where
+    I: IntoIterator,
+    I::Item: Into<Self>,
+  {
+    let mut yarns = yarns.into_iter().map(Into::into);
+    let Some(first) = yarns.next() else {
+      return Self::default();
+    };
+    let Some(second) = yarns.next() else {
+      return first;
+    };
+
+    let yarns = [first, second].into_iter().chain(yarns).collect::<Vec<_>>();
+    let sep = sep.as_ref().as_bytes();
+    let total_len = yarns
+      .iter()
+      .try_fold(0usize, |acc, y| acc.checked_add(y.as_bytes().len()))
+      .and_then(|len| len.checked_add(sep.len().checked_mul(yarns.len() - 1)?))
+      .expect("capacity overflow");
+
+    let mut first = true;
+    let iter = yarns.iter().flat_map(|y| {
+      let sep = if mem::take(&mut first) { &[][..] } else { sep };
+      [sep, y.as_bytes()]
+    });
+
+    unsafe {
+      // SAFETY: `iter` yields exactly `total_len` bytes, and concatenating
+      // valid Bufs produces a valid Buf.
+      Self::from_raw(RawYarn::concat(
+        Layout::from_size_align_unchecked(total_len, Self::align()),
+        iter,
+      ))
+    }
+  }
+
+  /// Returns a yarn consisting of this yarn repeated `n` times.
+  ///
+  /// Does not allocate if the result can be inlined, and otherwise allocates
+  /// exactly once.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the length of the result would overflow.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("ab");
+  /// assert_eq!(yarn.repeat(3), "ababab");
+  /// assert_eq!(yarn.repeat(20), "ab".repeat(20));
+  /// assert_eq!(yarn.repeat(0), "");
+  /// ```
+  pub fn repeat(&self, n: usize) -> Self {
+    unsafe {
+      // SAFETY: The result of repeating a valid Buf is a valid Buf, and it is
+      // either inlined or uniquely owned.
+      Self::from_raw(RawYarn::repeat(Self::align(), self.as_bytes(), n))
+    }
+  }
+
+  /// Returns the byte offset of the first match of `pat` in this yarn.
+  ///
+  /// Text yarns accept the same patterns as [`Yarn::split()`]; byte yarns
+  /// also accept bytes, byte strings, and `FnMut(u8) -> bool` closures, and
+  /// are searched with a raw `memmem`.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("jellybeans & jellyfish");
+  /// assert_eq!(yarn.find("jelly"), Some(0));
+  /// assert_eq!(yarn.find('&'), Some(11));
+  /// assert_eq!(yarn.find(char::is_whitespace), Some(10));
+  /// assert_eq!(yarn.find("gumdrops"), None);
+  ///
+  /// let bytes = ByteYarn::new(b"\x7fELF\x02\x01\x01\x00");
+  /// assert_eq!(bytes.find(b"ELF"), Some(1));
+  /// assert_eq!(bytes.find(0x01), Some(5));
+  /// assert_eq!(bytes.find(|b: u8| b < 0x02), Some(5));
+  /// ```
+  pub fn find(&self, mut pat: impl Pattern<Buf>) -> Option<usize> {
+    Some(pat.find_in(self.as_slice())?.start)
+  }
+
+  /// Returns the byte offset of the last match of `pat` in this yarn.
+  ///
+  /// See [`Yarn::find()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("jellybeans & jellyfish");
+  /// assert_eq!(yarn.rfind("jelly"), Some(13));
+  /// assert_eq!(ByteYarn::new(b"\0a\0b").rfind(0), Some(2));
+  /// ```
+  pub fn rfind(&self, mut pat: impl Pattern<Buf>) -> Option<usize> {
+    Some(pat.rfind_in(self.as_slice())?.start)
+  }
+
+  /// Returns whether `pat` matches anywhere in this yarn.
+  ///
+  /// See [`Yarn::find()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert!(Yarn::new("jellybeans").contains("bean"));
+  /// assert!(!ByteYarn::new(b"jellybeans").contains(b"gum"));
+  /// ```
+  pub fn contains(&self, pat: impl Pattern<Buf>) -> bool {
+    self.find(pat).is_some()
+  }
+
   /// Tries to inline this yarn, if it's small enough.
   ///
   /// This operation has no directly visible side effects, and is only intended
@@ -435,6 +919,61 @@ where
     }
   }
 
+  /// Leaks this yarn, returning a reference to its data that lives forever.
+  ///
+  /// This never frees memory: a heap-allocated yarn's buffer is forgotten and
+  /// returned as-is (along with its reference count, if it is shared), and a
+  /// static yarn's pointer is returned directly. Any other yarn, including
+  /// inlined yarns, whose data lives inside the yarn itself, is copied into a
+  /// fresh heap allocation, which is then leaked.
+  ///
+  /// Unlike [`YarnBox::leak()`], this consumes the yarn, so the reference it
+  /// returns need not borrow from it.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let config: &'static str = yarn!("verbose={}", true).into_leaked();
+  /// assert_eq!(config, "verbose=true");
+  ///
+  /// let name = String::from("jellybeans");
+  /// let name: &'static str = YarnBox::<str>::new(&name).into_leaked();
+  /// assert_eq!(name, "jellybeans");
+  /// ```
+  pub fn into_leaked(self) -> &'static Buf
+  where
+    Buf: 'static,
+  {
+    if self.raw.on_heap() || self.raw.kind() == RawYarn::STATIC {
+      let raw = self.into_raw();
+      return unsafe {
+        // SAFETY: Static data lives forever, and a heap buffer will never be
+        // freed, because into_raw() inhibited the destructor (and, for a shared
+        // yarn, the decrement of its reference count). Neither is stored
+        // inline, so the pointer does not point into `raw` itself.
+        Buf::from_bytes(slice::from_raw_parts(raw.as_ptr(), raw.len()))
+      };
+    }
+
+    Box::leak(self.into_box())
+  }
+
+  /// Returns this yarn's contents with its full lifetime, if they are
+  /// borrowed rather than owned by this yarn (i.e., it is STATIC or ALIASED).
+  fn borrowed(&self) -> Option<&'a Buf> {
+    if self.raw.on_heap() || self.raw.is_small() {
+      return None;
+    }
+
+    unsafe {
+      // SAFETY: A STATIC or ALIASED yarn points to data that lives for at
+      // least 'a, outside of the yarn itself.
+      Some(Buf::from_bytes(slice::from_raw_parts(
+        self.raw.as_ptr(),
+        self.raw.len(),
+      )))
+    }
+  }
+
   /// Leaks any heap allocation associated with this yarn.
   ///
   /// The allocation is tagged as "static", so upcasting via
@@ -447,7 +986,8 @@ where
     unsafe {
       // SAFETY: We have unique ownership of this yarn, and we know it's HEAP,
       // so updating the tag from HEAP to STATIC will not change anything
-      // except to make it immutable and to inhibit the destructor.
+      // except to make it immutable and to inhibit the destructor. If it was
+      // shared, this leaks its reference count, which keeps the buffer alive.
       self.raw = RawYarn::from_ptr_len_tag(
         self.as_bytes().as_ptr(),
         self.len(),
@@ -480,43 +1020,222 @@ where
     Utf8Chunks::new(self.as_bytes())
   }
 
-  /// Returns a new yarn wrapping the given raw yarn.
-  ///
-  /// # Safety
+  /// Returns a copy of this yarn with its characters escaped, like
+  /// [`str::escape_default()`].
   ///
-  /// If `raw` is aliased, its lifetime must not be shorter than 'a.
+  /// Bytes that are not valid UTF-8 are escaped as `\xNN`. Does not allocate
+  /// if the result can be inlined.
   ///
-  /// If `raw` is heap-allocated, no other yarn must be holding it.
-  pub(crate) const unsafe fn from_raw(raw: RawYarn) -> Self {
-    Self { raw, _ph: PhantomData }
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(Yarn::new("tab\t'é'").escape_default(), r"tab\t\'\u{e9}\'");
+  /// assert_eq!(ByteYarn::new(b"\xffa\n").escape_default(), r"\xFFa\n");
+  /// ```
+  pub fn escape_default(&self) -> YarnBox<'static, str> {
+    self.as_ref().escape_default()
   }
 
-  /// Consumes self, inhibits the destructor, and returns the raw yarn.
-  pub(crate) const fn into_raw(self) -> RawYarn {
-    let raw = self.raw;
-    mem::forget(self);
-    raw
+  /// Returns a copy of this yarn with its characters escaped, like
+  /// [`str::escape_debug()`].
+  ///
+  /// Bytes that are not valid UTF-8 are escaped as `\xNN`. This is the same
+  /// escaping used by this yarn's `Debug` impl, minus the quotes. Does not
+  /// allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(Yarn::new("tab\t'é'").escape_debug(), r"tab\t\'é\'");
+  /// assert_eq!(ByteYarn::new(b"\xffa\n").escape_debug(), r"\xFFa\n");
+  /// ```
+  pub fn escape_debug(&self) -> YarnBox<'static, str> {
+    self.as_ref().escape_debug()
   }
-}
 
-impl<Buf> YarnBox<'static, Buf>
-where
-  Buf: crate::Buf + ?Sized,
-{
-  /// Returns a yarn pointing to the given slice, without copying. This function
-  /// has the benefit of creating a yarn that remembers that it came from a
-  /// static string, meaning that it can be dynamically upcast back to a
-  /// `'static` lifetime.
+  /// Appends the given bytes to the end of this yarn.
   ///
-  /// This function will *not* be found by `From` impls.
-  pub const fn from_static(buf: &'static Buf) -> Self {
-    YarnRef::from_static(buf).to_box()
+  /// # Safety
+  ///
+  /// Appending `bytes` must produce a valid `Buf`.
+  pub(crate) unsafe fn append_bytes(&mut self, bytes: &[u8]) {
+    self.raw.append(Self::align(), bytes)
   }
-}
 
-impl<'a> YarnBox<'a, [u8]> {
+  /// Returns a mutable reference to this yarn's bytes.
+  ///
+  /// If this yarn does not uniquely own its buffer, it is copied first. Unlike
+  /// [`YarnBox::as_mut()`], this never moves a heap yarn's data.
+  ///
+  /// # Safety
+  ///
+  /// Any changes made through the returned slice must leave a valid `Buf`.
+  unsafe fn bytes_mut(&mut self) -> &mut [u8] {
+    if !self.raw.is_unique() {
+      let raw = RawYarn::copy_slice(
+        buf_trait::layout_of(self.as_slice()),
+        self.as_bytes().as_ptr(),
+      );
+      *self = Self::from_raw(raw);
+    }
+
+    self.raw.as_mut_slice()
+  }
+
+  /// Shortens this yarn to its first `len` bytes, if it is longer than that.
+  ///
+  /// # Safety
+  ///
+  /// If `len` is less than `self.len()`, the first `len` bytes of this yarn
+  /// must be a valid `Buf`.
+  unsafe fn truncate_bytes(&mut self, len: usize) {
+    if len < self.len() {
+      self.raw.truncate(Self::align(), len)
+    }
+  }
+
+  /// Removes the bytes in `start..end` from this yarn, shifting the rest down.
+  ///
+  /// A uniquely owned heap buffer is kept; other yarns are copied first.
+  ///
+  /// # Safety
+  ///
+  /// `start..end` must be in bounds, and removing it must leave a valid `Buf`.
+  pub(crate) unsafe fn remove_bytes(&mut self, start: usize, end: usize) {
+    if start == end {
+      return;
+    }
+
+    let len = self.len();
+    self.bytes_mut().copy_within(end..len, start);
+    self.truncate_bytes(len - (end - start));
+  }
+
+  /// Removes some of the units that make up this yarn, compacting the rest in
+  /// place, and then inlines the result if it fits.
+  ///
+  /// `unit` is called on the remaining bytes of the yarn, and returns the
+  /// length of the next unit and whether to keep it. The yarn is only copied
+  /// if a unit is actually removed.
+  ///
+  /// # Safety
+  ///
+  /// Any concatenation of the units `unit` splits this yarn into must be a
+  /// valid `Buf`.
+  unsafe fn retain_units(
+    &mut self,
+    mut unit: impl FnMut(&[u8]) -> (usize, bool),
+  ) {
+    // Find the first unit to remove, so that a no-op is not a copy.
+    let mut read = 0;
+    let write = loop {
+      let rest = &self.as_bytes()[read..];
+      if rest.is_empty() {
+        return;
+      }
+      let (len, keep) = unit(rest);
+      read += len;
+      if !keep {
+        break read - len;
+      }
+    };
+
+    // If `unit` panics, only keep what has been compacted so far, since the
+    // rest of the buffer may contain pieces of partially overwritten units.
+    struct Guard<'y, 'a, Buf: crate::Buf + ?Sized> {
+      yarn: &'y mut YarnBox<'a, Buf>,
+      len: usize,
+    }
+    impl<Buf: crate::Buf + ?Sized> Drop for Guard<'_, '_, Buf> {
+      fn drop(&mut self) {
+        unsafe { self.yarn.truncate_bytes(self.len) }
+      }
+    }
+
+    self.bytes_mut();
+    let mut guard = Guard { yarn: self, len: write };
+    while read < guard.yarn.len() {
+      let bytes = guard.yarn.raw.as_mut_slice();
+      let (len, keep) = unit(&bytes[read..]);
+      if keep {
+        bytes.copy_within(read..read + len, guard.len);
+        guard.len += len;
+      }
+      read += len;
+    }
+    drop(guard);
+    self.inline_in_place();
+  }
+
+  /// Returns the alignment of this yarn's data.
+  const fn align() -> usize {
+    mem::align_of::<Buf::Element>()
+  }
+
+  /// Returns a new yarn wrapping the given raw yarn.
+  ///
+  /// # Safety
+  ///
+  /// If `raw` is aliased, its lifetime must not be shorter than 'a.
+  ///
+  /// If `raw` is heap-allocated, no other yarn must be holding it, unless it is
+  /// shared, in which case it must hold its own reference count.
+  pub(crate) const unsafe fn from_raw(raw: RawYarn) -> Self {
+    Self {
+      raw,
+      #[cfg(feature = "allocator_api")]
+      alloc: Global,
+      _ph: PhantomData,
+    }
+  }
+
+  /// Consumes self, inhibits the destructor, and returns the raw yarn.
+  pub(crate) const fn into_raw(self) -> RawYarn {
+    let raw = self.raw;
+    mem::forget(self);
+    raw
+  }
+}
+
+impl<Buf> YarnBox<'static, Buf>
+where
+  Buf: crate::Buf + ?Sized,
+{
+  /// Returns a yarn pointing to the given slice, without copying. This function
+  /// has the benefit of creating a yarn that remembers that it came from a
+  /// static string, meaning that it can be dynamically upcast back to a
+  /// `'static` lifetime.
+  ///
+  /// Slices no longer than [`YarnBox::inlined_len()`] are inlined instead,
+  /// since copying them is as cheap as pointing to them, so only longer
+  /// slices keep their address. The same goes for yarns derived from this
+  /// one, such as by [`YarnBox::as_ref()`] or [`Yarn::trim()`]. Either way,
+  /// the result never allocates, and never needs to be copied to become
+  /// `'static`.
+  ///
+  /// This works for any kind of yarn, so byte strings use this function too.
+  ///
+  /// This function will *not* be found by `From` impls.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// static RESOURCE: &[u8] = b"an embedded resource that is not inlined";
+  /// let yarn = ByteYarn::from_static(RESOURCE);
+  /// assert!(yarn.is_static());
+  /// assert_eq!(yarn.as_ptr(), RESOURCE.as_ptr());
+  ///
+  /// let short = Yarn::from_static("short");
+  /// assert!(short.is_inlined());
+  /// ```
+  pub const fn from_static(buf: &'static Buf) -> Self {
+    YarnRef::from_static(buf).to_box()
+  }
+}
+
+impl<'a> YarnBox<'a, [u8]> {
   /// Returns a yarn by taking ownership of an allocation.
   ///
+  /// Like [`YarnBox::from_box()`], this reuses the allocation, but must grow it
+  /// to make room for a header, which moves the data.
+  ///
   /// ```
   /// # use byteyarn::*;
   /// let str = String::from("big string box").into_boxed_str();
@@ -534,6 +1253,10 @@ impl<'a> YarnBox<'a, [u8]> {
 
   /// Returns a yarn by taking ownership of an allocation.
   ///
+  /// Like [`YarnBox::from_box()`], this reuses the allocation, but must grow it
+  /// to make room for a header, which moves the data. Any spare capacity is
+  /// discarded first.
+  ///
   /// ```
   /// # use byteyarn::*;
   /// let str = String::from("big string box");
@@ -544,6 +1267,27 @@ impl<'a> YarnBox<'a, [u8]> {
     Self::from_boxed_str(string.into())
   }
 
+  /// Returns a yarn that shares the given reference-counted buffer.
+  ///
+  /// See [`Yarn::from_arc()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// use std::sync::Arc;
+  ///
+  /// let arc: Arc<[u8]> = Arc::from(&[0xff; 64][..]);
+  /// let yarn = ByteYarn::from_arc(arc.clone());
+  /// assert_eq!(yarn.clone(), [0xff; 64]);
+  /// assert_eq!(Arc::strong_count(&arc), 2);
+  /// ```
+  pub fn from_arc(arc: Arc<[u8]>) -> Self {
+    let raw = RawYarn::from_arc(arc);
+    unsafe {
+      // SAFETY: Any sequence of bytes is a valid [u8].
+      Self::from_raw(raw)
+    }
+  }
+
   /// Returns a yarn containing a single byte, without allocating.
   ///
   /// ```
@@ -555,6 +1299,161 @@ impl<'a> YarnBox<'a, [u8]> {
     YarnRef::from_byte(c).to_box()
   }
 
+  /// Returns a yarn pointing to the bytes of a C string, without copying.
+  ///
+  /// The yarn does not include the trailing NUL.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// # use std::ffi::CString;
+  /// let c = CString::new("a string from C, somewhere").unwrap();
+  /// let yarn = YarnBox::<[u8]>::from_cstr(&c);
+  /// assert_eq!(yarn, b"a string from C, somewhere");
+  /// assert_eq!(yarn.as_ptr(), c.as_ptr().cast());
+  /// ```
+  pub const fn from_cstr(c: &'a CStr) -> Self {
+    Self::new(c.to_bytes())
+  }
+
+  /// Returns a yarn pointing to the bytes of a static C string, without
+  /// copying.
+  ///
+  /// Like [`YarnBox::from_static()`], the yarn remembers that it came from a
+  /// static string. It does not include the trailing NUL.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::from_static_cstr(c"a static C string");
+  /// assert_eq!(yarn, b"a static C string");
+  /// assert!(yarn.is_static());
+  /// ```
+  pub const fn from_static_cstr(c: &'static CStr) -> Self {
+    YarnBox::from_static(c.to_bytes())
+  }
+
+  /// Copies this yarn into a new C string.
+  ///
+  /// Returns an error if this yarn contains a NUL byte.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"\xffC");
+  /// assert_eq!(yarn.to_cstring().unwrap().as_bytes(), b"\xffC");
+  ///
+  /// assert!(ByteYarn::new(b"a\0b").to_cstring().is_err());
+  /// ```
+  pub fn to_cstring(&self) -> Result<CString, NulError> {
+    self.as_ref().to_cstring()
+  }
+
+  /// Returns a hash of this yarn's contents that is the same on every
+  /// platform.
+  ///
+  /// Unlike the `Hash` impl, which may vary between platforms and compiler
+  /// versions, this is the 64-bit FNV-1a hash of the yarn's bytes, so it is
+  /// suitable for persisting.
+  ///
+  /// Requires the `fnv` feature.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(ByteYarn::new(b"foobar").stable_hash(), 0x85944171f73967e8);
+  /// ```
+  #[cfg(feature = "fnv")]
+  pub fn stable_hash(&self) -> u64 {
+    crate::fnv::hash(self.as_bytes())
+  }
+
+  /// Returns an iterator over the characters of this yarn, treating it as
+  /// UTF-8.
+  ///
+  /// Each run of invalid UTF-8 is replaced with a single
+  /// [`char::REPLACEMENT_CHARACTER`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"abc\xFF\xFE\xF0\x9F\x90\x88!");
+  /// let chars = yarn.chars_lossy().collect::<String>();
+  /// assert_eq!(chars, "abc��🐈!");
+  /// ```
+  pub fn chars_lossy(&self) -> CharsLossy<'_> {
+    CharsLossy::new(self.as_bytes())
+  }
+
+  /// Returns an iterator over the bytes of this yarn.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"\x01\x02\x03");
+  /// assert_eq!(yarn.bytes().sum::<u8>(), 6);
+  /// ```
+  pub fn bytes(&self) -> iter::Copied<slice::Iter<'_, u8>> {
+    self.as_slice().iter().copied()
+  }
+
+  /// Returns an iterator over the bytes of this yarn.
+  ///
+  /// This is the same as [`ByteYarn::bytes()`], and is also what iterating
+  /// over a `&ByteYarn` produces.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"a longer byte string on the heap");
+  /// assert_eq!(yarn.iter().filter(|&b| b == b' ').count(), 6);
+  ///
+  /// let mut upper = Vec::new();
+  /// for b in &yarn {
+  ///   upper.push(b.to_ascii_uppercase());
+  /// }
+  /// assert_eq!(upper, b"A LONGER BYTE STRING ON THE HEAP");
+  /// ```
+  pub fn iter(&self) -> iter::Copied<slice::Iter<'_, u8>> {
+    self.bytes()
+  }
+
+  /// Returns an iterator over the bytes of this yarn, back to front.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"abc");
+  /// assert_eq!(yarn.rbytes().collect::<Vec<_>>(), b"cba");
+  /// assert_eq!(yarn.rbytes().next_back(), Some(b'a'));
+  /// ```
+  pub fn rbytes(&self) -> iter::Rev<iter::Copied<slice::Iter<'_, u8>>> {
+    self.bytes().rev()
+  }
+
+  /// Returns a copy of this yarn with its bytes in reverse order.
+  ///
+  /// Does not allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"ab\xff");
+  /// assert_eq!(yarn.reverse_bytes(), b"\xffba");
+  /// ```
+  pub fn reverse_bytes(&self) -> YarnBox<'static, [u8]> {
+    self.as_ref().reverse_bytes()
+  }
+
+  /// Splits this yarn into two at byte offset `mid`.
+  ///
+  /// Both halves alias this yarn, unless they are small enough to inline.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `mid` is greater than this yarn's length.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"jelly\xffbeans");
+  /// let (jelly, beans) = yarn.split_at(5);
+  /// assert_eq!([jelly, beans], [&b"jelly"[..], b"\xffbeans"]);
+  /// ```
+  pub fn split_at(&self, mid: usize) -> (YarnRef<'_, [u8]>, YarnRef<'_, [u8]>) {
+    self.as_ref().split_at(mid)
+  }
+
   /// Tries to convert this yarn into a UTF-8 yarn via [`str::from_utf8()`].
   ///
   /// ```
@@ -585,126 +1484,1604 @@ impl<'a> YarnBox<'a, [u8]> {
     }
     unsafe { Ok(YarnBox::from_raw(self.into_raw())) }
   }
-}
 
-impl<T> YarnBox<'_, [T]>
-where
-  [T]: crate::Buf,
-{
-  /// Returns a yarn by taking ownership of the given allocation.
+  /// Parses this yarn into another type via [`FromStr`], if it is valid UTF-8.
   ///
   /// ```
   /// # use byteyarn::*;
-  /// let str = vec![0xf0, 0x9f, 0x90, 0x88, 0xe2, 0x80, 0x8d, 0xe2, 0xac, 0x9b];
-  /// let yarn = ByteYarn::from_vec(str);
-  /// assert_eq!(yarn, "🐈‍⬛".as_bytes());
+  /// assert_eq!(ByteYarn::new(b"42").parse::<u32>(), Ok(42));
+  ///
+  /// assert!(matches!(ByteYarn::new(b"x").parse::<u32>(), Err(ParseError::Parse(_))));
+  /// assert!(matches!(ByteYarn::new(b"\xff").parse::<u32>(), Err(ParseError::Utf8(_))));
   /// ```
-  pub fn from_vec(bytes: Vec<T>) -> Self {
-    Self::from_box(bytes.into_boxed_slice())
+  pub fn parse<T: FromStr>(&self) -> Result<T, ParseError<T::Err>> {
+    self.as_ref().parse()
   }
 
-  /// Converts this yarn into a vector, potentially by copying it.
+  /// Decodes a string of hex digits into bytes.
+  ///
+  /// The digits may be either upper or lowercase. Returns an error if `hex`
+  /// has an odd length or contains anything other than hex digits. Does not
+  /// allocate if the result can be inlined.
   ///
   /// ```
   /// # use byteyarn::*;
-  /// let mut vec = ByteYarn::new(b"jellybeans").into_vec();
-  /// vec.extend_from_slice(b" & KNUCKLES");
-  /// let yarn = ByteYarn::from_vec(vec);
+  /// assert_eq!(ByteYarn::from_hex("c0ffEE").unwrap(), b"\xc0\xff\xee");
   ///
-  /// assert_eq!(yarn, b"jellybeans & KNUCKLES");
+  /// assert_eq!(ByteYarn::from_hex("c0f"), Err(HexError::OddLength));
+  /// assert_eq!(ByteYarn::from_hex("c0fx"), Err(HexError::InvalidDigit(3)));
   /// ```
-  pub fn into_vec(self) -> Vec<T> {
-    self.into_box().into()
+  pub fn from_hex(hex: &str) -> Result<Self, HexError> {
+    crate::hex::decode(hex)
   }
 
-  /// Returns a mutable reference into this yarn's internal buffer.
+  /// Decodes a string of base64 in the standard alphabet, with padding.
   ///
-  /// If the buffer is not uniquely owned (e.g., it is an alias of some other
-  /// buffer or a string constant) this function will first perform a copy and
-  /// possibly a heap allocation.
+  /// Returns an error if `text` contains characters outside of the alphabet
+  /// (including whitespace), or is not padded correctly. Does not allocate if
+  /// the result can be inlined.
+  ///
+  /// Requires the `base64` feature.
   ///
   /// ```
   /// # use byteyarn::*;
-  /// let mut yarn = ByteYarn::new(b"const but very long");
-  /// assert!(yarn.try_mut().is_none());
+  /// assert_eq!(ByteYarn::from_base64("+/8=").unwrap(), b"\xfb\xff");
   ///
-  /// let mut smol = ByteYarn::new(b"smol const");
-  /// smol.try_mut().unwrap()[3] = b'g';
-  /// assert_eq!(smol, b"smog const");
+  /// assert_eq!(ByteYarn::from_base64("+/8"), Err(Base64Error::InvalidLength));
+  /// assert_eq!(ByteYarn::from_base64("-_8="), Err(Base64Error::InvalidChar(0)));
   /// ```
-  pub fn try_mut(&mut self) -> Option<&mut [T]> {
-    self.inline_in_place();
-    if !self.raw.on_heap() && !self.raw.is_small() {
-      return None;
-    }
-
-    Some(self.as_mut())
+  #[cfg(feature = "base64")]
+  pub fn from_base64(text: &str) -> Result<Self, Base64Error> {
+    crate::base64::decode(text, crate::base64::STANDARD)
   }
 
-  /// Returns a mutable reference into this yarn's internal buffer.
+  /// Decodes a string of base64 in the URL-safe alphabet, without padding.
   ///
-  /// If the buffer is not uniquely owned (e.g., it is an alias of some other
-  /// buffer or a string constant) this function will first perform a copy and
-  /// possibly a heap allocation.
+  /// Requires the `base64` feature. See [`ByteYarn::from_base64()`].
   ///
   /// ```
   /// # use byteyarn::*;
-  /// let mut yarn = ByteYarn::new(b"const but very long");
-  /// yarn.as_mut()[17] = b'_';
-  /// assert_eq!(yarn, b"const but very lo_g");
+  /// assert_eq!(ByteYarn::from_base64_url("-_8").unwrap(), b"\xfb\xff");
   /// ```
-  #[allow(clippy::should_implement_trait)]
-  pub fn as_mut(&mut self) -> &mut [T] {
-    self.inline_in_place();
-    if !self.raw.on_heap() && !self.raw.is_small() {
-      *self = Self::from_box(mem::take(self).into_box());
-    }
-
-    unsafe { buf_trait::as_buf_mut(self.raw.as_mut_slice()) }
+  #[cfg(feature = "base64")]
+  pub fn from_base64_url(text: &str) -> Result<Self, Base64Error> {
+    crate::base64::decode(text, crate::base64::URL_SAFE)
   }
-}
 
-impl YarnBox<'_, str> {
-  /// Returns a yarn by taking ownership of an allocation.
+  /// Encodes this yarn as base64 in the standard alphabet, with padding.
+  ///
+  /// Does not allocate if the result can be inlined.
+  ///
+  /// Requires the `base64` feature.
   ///
   /// ```
   /// # use byteyarn::*;
-  /// let str = String::from("big string box").into_boxed_str();
-  /// let yarn = Yarn::from_boxed_str(str);
-  /// assert_eq!(yarn, "big string box");
+  /// assert_eq!(ByteYarn::new(b"\xfb\xff").to_base64(), "+/8=");
   /// ```
-  pub fn from_boxed_str(string: Box<str>) -> Self {
-    let raw = RawYarn::from_heap(string.into());
-    unsafe {
-      // SAFETY: both [u8] and str can be safely constructed from a str. We have
-      // unique ownership of raw's allocation because from_heap guarantees it.
-      Self::from_raw(raw)
-    }
+  #[cfg(feature = "base64")]
+  pub fn to_base64(&self) -> YarnBox<'static, str> {
+    self.as_ref().to_base64()
   }
 
-  /// Returns a yarn by taking ownership of an allocation.
+  /// Encodes this yarn as base64 in the URL-safe alphabet, without padding.
+  ///
+  /// Does not allocate if the result can be inlined.
+  ///
+  /// Requires the `base64` feature.
   ///
   /// ```
   /// # use byteyarn::*;
-  /// let str = String::from("big string box");
-  /// let yarn = Yarn::from_string(str);
-  /// assert_eq!(yarn, "big string box");
+  /// assert_eq!(ByteYarn::new(b"\xfb\xff").to_base64_url(), "-_8");
   /// ```
-  pub fn from_string(string: String) -> Self {
-    Self::from_boxed_str(string.into())
+  #[cfg(feature = "base64")]
+  pub fn to_base64_url(&self) -> YarnBox<'static, str> {
+    self.as_ref().to_base64_url()
   }
 
-  /// Builds a new yarn from the given formatting arguments
-  /// (see [`format_args!()`]), allocating only when absolutely necessary.
+  /// Percent-encodes every byte of this yarn that is in `set`, as `%XY`.
   ///
-  /// In general, you'll want to use the [`yarn!()`] macro, instead.
-  pub fn from_fmt(args: fmt::Arguments) -> Self {
-    unsafe { YarnBox::from_raw(RawYarn::from_fmt_args(args)) }
+  /// Does not allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"\xff?");
+  /// assert_eq!(yarn.percent_encode(EncodeSet::COMPONENT), "%FF%3F");
+  /// ```
+  pub fn percent_encode(&self, set: EncodeSet) -> YarnBox<'static, str> {
+    self.as_ref().percent_encode(set)
   }
 
-  /// Converts this yarn into a string slice.
-  pub fn as_str(&self) -> &str {
-    self.as_slice()
+  /// Decodes every `%XY` percent-escape in this yarn.
+  ///
+  /// Returns an error if a `%` is not followed by two hex digits. Does not
+  /// allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("caf%C3%A9%21");
+  /// assert_eq!(yarn.percent_decode().unwrap(), "café!".as_bytes());
+  ///
+  /// let err = Yarn::new("50%").percent_decode();
+  /// assert_eq!(err, Err(PercentError::InvalidEscape(2)));
+  /// ```
+  pub fn percent_decode(&self) -> Result<YarnBox<'static, [u8]>, PercentError> {
+    self.as_ref().percent_decode()
+  }
+
+  /// Encodes this yarn as lowercase hex digits, two per byte.
+  ///
+  /// Does not allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"\xc0\xff\xee");
+  /// assert_eq!(yarn.to_hex(), "c0ffee");
+  /// ```
+  pub fn to_hex(&self) -> YarnBox<'static, str> {
+    self.as_ref().to_hex()
+  }
+
+  /// Encodes this yarn as uppercase hex digits, two per byte.
+  ///
+  /// Does not allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"\xc0\xff\xee");
+  /// assert_eq!(yarn.to_hex_upper(), "C0FFEE");
+  /// ```
+  pub fn to_hex_upper(&self) -> YarnBox<'static, str> {
+    self.as_ref().to_hex_upper()
+  }
+
+  /// Checks whether this yarn is equal to `that`, ignoring ASCII case.
+  ///
+  /// This compares the bytes directly, without making lowercase copies.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"Content-Type");
+  /// assert!(yarn.eq_ignore_ascii_case(b"content-TYPE"));
+  /// ```
+  pub fn eq_ignore_ascii_case(&self, that: impl AsRef<[u8]>) -> bool {
+    self.as_bytes().eq_ignore_ascii_case(that.as_ref())
+  }
+
+  /// Compares this yarn with `that` lexicographically, ignoring ASCII case.
+  ///
+  /// This is the ordering of the two yarns after converting both to ASCII
+  /// lowercase, but it does not make lowercase copies.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// # use std::cmp::Ordering;
+  /// let yarn = ByteYarn::new(b"Content-Type");
+  /// assert_eq!(yarn.cmp_ignore_ascii_case(b"content-typf"), Ordering::Less);
+  /// ```
+  pub fn cmp_ignore_ascii_case(&self, that: impl AsRef<[u8]>) -> Ordering {
+    crate::caseless::cmp(self.as_bytes(), that.as_ref())
+  }
+
+  /// Returns a copy of this yarn with every ASCII letter mapped to uppercase.
+  ///
+  /// Non-ASCII bytes are unchanged. This
+  /// does not allocate if the yarn is small enough to be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"abc\xFF");
+  /// assert_eq!(yarn.to_ascii_uppercase(), b"ABC\xFF");
+  /// ```
+  pub fn to_ascii_uppercase(&self) -> Self {
+    let mut copy = self.to_owned_yarn();
+    copy.make_ascii_uppercase();
+    copy
+  }
+
+  /// Returns a copy of this yarn with every ASCII letter mapped to lowercase.
+  ///
+  /// Non-ASCII bytes are unchanged. This
+  /// does not allocate if the yarn is small enough to be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"A VERY LONG STRING, NOT INLINED");
+  /// assert_eq!(yarn.to_ascii_lowercase(), b"a very long string, not inlined");
+  /// ```
+  pub fn to_ascii_lowercase(&self) -> Self {
+    let mut copy = self.to_owned_yarn();
+    copy.make_ascii_lowercase();
+    copy
+  }
+
+  /// Maps every ASCII letter in this yarn to uppercase, in place.
+  ///
+  /// ASCII case mapping never changes the length of a yarn, so this never
+  /// reallocates an inlined or heap-allocated yarn. Other yarns are copied
+  /// first, like in [`YarnBox::push_str()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = ByteYarn::new(b"Jellybeans!");
+  /// yarn.make_ascii_uppercase();
+  /// assert_eq!(yarn, b"JELLYBEANS!");
+  /// ```
+  pub fn make_ascii_uppercase(&mut self) {
+    unsafe {
+      // SAFETY: Any sequence of bytes is a valid [u8].
+      self.bytes_mut().make_ascii_uppercase()
+    }
+  }
+
+  /// Maps every ASCII letter in this yarn to lowercase, in place.
+  ///
+  /// See [`YarnBox::make_ascii_uppercase()`].
+  pub fn make_ascii_lowercase(&mut self) {
+    unsafe {
+      // SAFETY: Any sequence of bytes is a valid [u8].
+      self.bytes_mut().make_ascii_lowercase()
+    }
+  }
+
+  /// Returns a copy of this yarn with `f` applied to every byte.
+  ///
+  /// This does not allocate if the yarn is small enough to be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = ByteYarn::new(b"jellybeans");
+  /// let xored = yarn.map_bytes(|b| b ^ 0x20);
+  /// assert_eq!(xored, b"JELLYBEANS");
+  /// assert_eq!(xored.map_bytes(|b| b ^ 0x20), yarn);
+  /// ```
+  pub fn map_bytes(&self, f: impl FnMut(u8) -> u8) -> Self {
+    let mut copy = self.to_owned_yarn();
+    copy.map_bytes_mut(f);
+    copy
+  }
+
+  /// Applies `f` to every byte in this yarn, in place.
+  ///
+  /// This never changes the length of a yarn, so it never reallocates an
+  /// inlined or heap-allocated yarn. Other yarns are copied first, like in
+  /// [`YarnBox::push_str()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = ByteYarn::new(b"uryyb");
+  /// yarn.map_bytes_mut(|b| match b {
+  ///   b'a'..=b'm' => b + 13,
+  ///   b'n'..=b'z' => b - 13,
+  ///   _ => b,
+  /// });
+  /// assert_eq!(yarn, b"hello");
+  /// ```
+  pub fn map_bytes_mut(&mut self, mut f: impl FnMut(u8) -> u8) {
+    unsafe {
+      // SAFETY: Any sequence of bytes is a valid [u8].
+      for b in self.bytes_mut() {
+        *b = f(*b);
+      }
+    }
+  }
+
+  /// Removes every byte for which `f` returns `false`, in place.
+  ///
+  /// Bytes are visited in order, and the rest are kept in their original
+  /// order. If nothing is removed, this does not copy a yarn that does not
+  /// own its buffer; otherwise it is copied first, like in
+  /// [`YarnBox::push_str()`]. The result is inlined if it fits.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = ByteYarn::new(b"j\0elly\0beans");
+  /// yarn.retain(|b| b != 0);
+  /// assert_eq!(yarn, b"jellybeans");
+  /// ```
+  pub fn retain(&mut self, mut f: impl FnMut(u8) -> bool) {
+    unsafe {
+      // SAFETY: Any sequence of bytes is a valid [u8].
+      self.retain_units(|rest| (1, f(rest[0])))
+    }
+  }
+}
+
+impl<'a, T> YarnBox<'a, [T]>
+where
+  [T]: crate::Buf,
+{
+  /// Returns a yarn by taking ownership of the given allocation.
+  ///
+  /// Like [`YarnBox::from_box()`], this reuses the allocation, but must grow it
+  /// to make room for a header, which moves the data. Any spare capacity is
+  /// discarded first.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let str = vec![0xf0, 0x9f, 0x90, 0x88, 0xe2, 0x80, 0x8d, 0xe2, 0xac, 0x9b];
+  /// let yarn = ByteYarn::from_vec(str);
+  /// assert_eq!(yarn, "🐈‍⬛".as_bytes());
+  /// ```
+  pub fn from_vec(bytes: Vec<T>) -> Self {
+    Self::from_box(bytes.into_boxed_slice())
+  }
+
+  /// Converts this yarn into a [`Cow`], without copying borrowed data.
+  ///
+  /// Static and aliased yarns become [`Cow::Borrowed`]. Inlined and heap
+  /// yarns become [`Cow::Owned`], via [`YarnBox::into_vec()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// # use std::borrow::Cow;
+  /// let data = b"some borrowed bytes";
+  /// let cow = YarnBox::<[u8]>::new(data).into_cow();
+  /// assert!(matches!(cow, Cow::Borrowed(b) if b.as_ptr() == data.as_ptr()));
+  ///
+  /// let cow = byarn!(b"{}", "owned").into_cow();
+  /// assert!(matches!(cow, Cow::Owned(_)));
+  /// ```
+  pub fn into_cow(self) -> Cow<'a, [T]>
+  where
+    T: Clone,
+  {
+    match self.borrowed() {
+      Some(slice) => Cow::Borrowed(slice),
+      None => Cow::Owned(self.into_vec()),
+    }
+  }
+
+  /// Converts this yarn into a vector, potentially by copying it.
+  ///
+  /// If this yarn uniquely owns a heap allocation, the vector reuses it
+  /// without reallocating, and keeps any spare capacity. Otherwise, the data
+  /// is copied into a new allocation.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut vec = ByteYarn::new(b"jellybeans").into_vec();
+  /// vec.extend_from_slice(b" & KNUCKLES");
+  /// let yarn = ByteYarn::from_vec(vec);
+  ///
+  /// assert_eq!(yarn, b"jellybeans & KNUCKLES");
+  /// ```
+  pub fn into_vec(self) -> Vec<T> {
+    let elem = mem::size_of::<T>();
+    let align = Self::align();
+    if elem == 0 {
+      // Zero-sized elements take up no bytes, so the yarn cannot hold any, but
+      // it may still have spare capacity on the heap to free.
+      let raw = self.into_raw();
+      unsafe {
+        // SAFETY: raw is uniquely owned, since we called into_raw(), and its
+        // data has the alignment of T.
+        raw.destroy_in(&Global, Layout::from_size_align_unchecked(0, align));
+      }
+      return Vec::new();
+    }
+
+    // The allocation's size must be a whole number of elements for it to be
+    // usable as the buffer of a Vec<T>.
+    if self.raw.on_heap()
+      && !self.raw.is_shared()
+      && unsafe { self.raw.heap_size(align) } % elem == 0
+    {
+      let len = self.len() / elem;
+      let raw = self.into_raw();
+      unsafe {
+        // SAFETY: raw is on the heap and unshared, and because we called
+        // into_raw(), it is uniquely owned. Its allocation has alignment
+        // align_of::<T>() and a size that is a multiple of size_of::<T>(), so
+        // it is a valid Vec<T> buffer, and the first len elements are
+        // initialized.
+        let cap = raw.heap_size(align) / elem;
+        let ptr = raw.into_heap_front(align);
+        return Vec::from_raw_parts(ptr.cast::<T>(), len, cap);
+      }
+    }
+
+    self.into_box().into()
+  }
+
+  /// Shortens this yarn to its first `len` elements.
+  ///
+  /// If `len` is not less than this yarn's current length, this has no
+  /// effect. A uniquely owned heap buffer is kept, so that it can be reused by
+  /// later appends.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = ByteYarn::new(b"jellybeans");
+  /// yarn.truncate(5);
+  /// assert_eq!(yarn, b"jelly");
+  /// ```
+  pub fn truncate(&mut self, len: usize) {
+    unsafe {
+      // SAFETY: Any prefix of a slice that ends on an element boundary is a
+      // valid slice.
+      self.truncate_bytes(len.saturating_mul(mem::size_of::<T>()))
+    }
+  }
+
+  /// Returns a mutable reference into this yarn's internal buffer.
+  ///
+  /// If the buffer is not uniquely owned (e.g., it is an alias of some other
+  /// buffer or a string constant) this function will first perform a copy and
+  /// possibly a heap allocation.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = ByteYarn::new(b"const but very long");
+  /// assert!(yarn.try_mut().is_none());
+  ///
+  /// let mut smol = ByteYarn::new(b"smol const");
+  /// smol.try_mut().unwrap()[3] = b'g';
+  /// assert_eq!(smol, b"smog const");
+  /// ```
+  pub fn try_mut(&mut self) -> Option<&mut [T]> {
+    self.inline_in_place();
+    if !self.raw.is_unique() {
+      return None;
+    }
+
+    Some(self.as_mut())
+  }
+
+  /// Returns a mutable reference into this yarn's internal buffer.
+  ///
+  /// If the buffer is not uniquely owned (e.g., it is an alias of some other
+  /// buffer or a string constant) this function will first perform a copy and
+  /// possibly a heap allocation.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = ByteYarn::new(b"const but very long");
+  /// yarn.as_mut()[17] = b'_';
+  /// assert_eq!(yarn, b"const but very lo_g");
+  /// ```
+  #[allow(clippy::should_implement_trait)]
+  pub fn as_mut(&mut self) -> &mut [T] {
+    self.inline_in_place();
+    if !self.raw.is_unique() {
+      *self = Self::from_box(mem::take(self).into_box());
+    }
+
+    unsafe { buf_trait::as_buf_mut(self.raw.as_mut_slice()) }
+  }
+}
+
+impl<'a> YarnBox<'a, str> {
+  /// Returns a yarn by taking ownership of an allocation.
+  ///
+  /// Like [`YarnBox::from_box()`], this reuses the allocation, but must grow it
+  /// to make room for a header, which moves the data.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let str = String::from("big string box").into_boxed_str();
+  /// let yarn = Yarn::from_boxed_str(str);
+  /// assert_eq!(yarn, "big string box");
+  /// ```
+  pub fn from_boxed_str(string: Box<str>) -> Self {
+    let raw = RawYarn::from_heap(string.into());
+    unsafe {
+      // SAFETY: both [u8] and str can be safely constructed from a str. We have
+      // unique ownership of raw's allocation because from_heap guarantees it.
+      Self::from_raw(raw)
+    }
+  }
+
+  /// Returns a yarn by taking ownership of an allocation.
+  ///
+  /// Like [`YarnBox::from_box()`], this reuses the allocation, but must grow it
+  /// to make room for a header, which moves the data. Any spare capacity is
+  /// discarded first.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let str = String::from("big string box");
+  /// let yarn = Yarn::from_string(str);
+  /// assert_eq!(yarn, "big string box");
+  /// ```
+  pub fn from_string(string: String) -> Self {
+    Self::from_boxed_str(string.into())
+  }
+
+  /// Returns a yarn that shares the given reference-counted buffer.
+  ///
+  /// Cloning the resulting yarn only increments the reference count, which
+  /// makes it cheap to hand copies of a large yarn out to many owners.
+  /// Small buffers are inlined instead.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// use std::sync::Arc;
+  ///
+  /// let arc: Arc<str> = Arc::from("a string that does not fit inline");
+  /// let yarn = Yarn::from_arc(arc.clone());
+  /// let copy = yarn.clone();
+  ///
+  /// assert_eq!(copy, "a string that does not fit inline");
+  /// assert_eq!(copy.as_slice().as_ptr(), arc.as_ptr());
+  /// assert_eq!(Arc::strong_count(&arc), 3);
+  /// ```
+  pub fn from_arc(arc: Arc<str>) -> Self {
+    let raw = RawYarn::from_arc(arc.into());
+    unsafe {
+      // SAFETY: the Arc<[u8]> was converted from an Arc<str>, so it holds
+      // valid UTF-8.
+      Self::from_raw(raw)
+    }
+  }
+
+  /// Formats an integer in decimal.
+  ///
+  /// The digits are written into a buffer on the stack, so this does not
+  /// allocate if the result can be inlined, which is the case for every
+  /// integer of up to 15 characters on 64-bit targets (every `i32`, for
+  /// example).
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::from_int(-1234);
+  /// assert_eq!(yarn, "-1234");
+  /// assert!(yarn.is_inlined());
+  ///
+  /// assert_eq!(Yarn::from_int(u128::MAX), u128::MAX.to_string());
+  /// ```
+  pub fn from_int<T: Integer>(n: T) -> Self {
+    crate::num::from_int(n)
+  }
+
+  /// Formats a floating-point number as the shortest decimal that rounds
+  /// trips back to it.
+  ///
+  /// This matches the number's `Debug` output: integral values print as
+  /// `1.0`, and very large or small values use exponential notation, so that
+  /// `f32`s and most `f64`s are inlined. Does not allocate if the result can
+  /// be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(Yarn::from_float(0.1 + 0.2), "0.30000000000000004");
+  /// assert_eq!(Yarn::from_float(1e100), "1e100");
+  /// assert_eq!(Yarn::from_float(2.0f32), "2.0");
+  /// ```
+  pub fn from_float<T: Float>(f: T) -> Self {
+    crate::num::from_float(f)
+  }
+
+  /// Builds a new yarn from the given formatting arguments
+  /// (see [`format_args!()`]), allocating only when absolutely necessary.
+  ///
+  /// In general, you'll want to use the [`yarn!()`] macro, instead.
+  pub fn from_fmt(args: fmt::Arguments) -> Self {
+    if let Some(constant) = args.as_str() {
+      return YarnBox::from_static(constant);
+    }
+
+    let mut w = YarnWriter::<str>::new();
+    let _ = fmt::Write::write_fmt(&mut w, args);
+    w.into_yarn()
+  }
+
+  /// Converts a byte yarn into a string yarn, if it is valid UTF-8.
+  ///
+  /// This does not copy: the returned yarn uses the same storage as `bytes`.
+  /// This is equivalent to [`ByteYarn::to_utf8()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let bytes = ByteYarn::from_vec(b"a very long string that is not inlined".to_vec());
+  /// let ptr = bytes.as_ptr();
+  ///
+  /// let yarn = Yarn::from_utf8(bytes).unwrap();
+  /// assert_eq!(yarn, "a very long string that is not inlined");
+  /// assert_eq!(yarn.as_ptr(), ptr);
+  ///
+  /// assert!(Yarn::from_utf8(ByteYarn::from_byte(0xff)).is_err());
+  /// ```
+  pub fn from_utf8(bytes: YarnBox<'a, [u8]>) -> Result<Self, Utf8Error> {
+    bytes.to_utf8()
+  }
+
+  /// Converts a byte yarn into a string yarn, replacing invalid UTF-8 with
+  /// [`char::REPLACEMENT_CHARACTER`].
+  ///
+  /// This has the same semantics as [`String::from_utf8_lossy()`]. If `bytes`
+  /// is already valid UTF-8, its storage is reused, like in
+  /// [`Yarn::from_utf8()`]; otherwise, a new yarn is built, which is inlined if
+  /// it fits.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::from_utf8_lossy(ByteYarn::new(b"abc\xFF\xFE\xF0\x9F\x90\x88!"));
+  /// assert_eq!(yarn, "abc\u{FFFD}\u{FFFD}🐈!");
+  ///
+  /// let bytes = ByteYarn::new(b"a very long string that is not inlined");
+  /// let yarn = YarnBox::<str>::from_utf8_lossy(bytes.aliased());
+  /// assert_eq!(yarn.as_ptr(), bytes.as_ptr());
+  /// ```
+  pub fn from_utf8_lossy(bytes: YarnBox<'a, [u8]>) -> Self {
+    let bytes = match bytes.to_utf8_or_bytes() {
+      Ok(yarn) => return yarn,
+      Err((bytes, _)) => bytes,
+    };
+
+    let mut yarn = Self::default();
+    for chunk in bytes.utf8_chunks() {
+      match chunk {
+        Ok(s) => yarn.push_str(s),
+        Err(_) => yarn.push(char::REPLACEMENT_CHARACTER),
+      }
+    }
+    yarn
+  }
+
+  /// Decodes a UTF-16-encoded slice into a yarn.
+  ///
+  /// Returns an error if `data` contains an unpaired surrogate. The result is
+  /// inlined if it fits.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::from_utf16(&[0x6d, 0x75, 0xd834, 0xdd1e, 0x69, 0x63]).unwrap();
+  /// assert_eq!(yarn, "mu𝄞ic");
+  ///
+  /// let err = Yarn::from_utf16(&[0x6d, 0xd800, 0x69]).unwrap_err();
+  /// assert_eq!(err.unpaired_surrogate(), 0xd800);
+  /// ```
+  pub fn from_utf16(data: &[u16]) -> Result<Self, DecodeUtf16Error> {
+    char::decode_utf16(data.iter().copied()).collect()
+  }
+
+  /// Decodes a UTF-16-encoded slice into a yarn, replacing unpaired
+  /// surrogates with [`char::REPLACEMENT_CHARACTER`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::from_utf16_lossy(&[0x6d, 0xd800, 0x69]);
+  /// assert_eq!(yarn, "m\u{FFFD}i");
+  /// ```
+  pub fn from_utf16_lossy(data: &[u16]) -> Self {
+    char::decode_utf16(data.iter().copied())
+      .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
+      .collect()
+  }
+
+  /// Converts this yarn into a string slice.
+  pub fn as_str(&self) -> &str {
+    self.as_slice()
+  }
+
+  /// Copies this yarn into a new C string.
+  ///
+  /// Returns an error if this yarn contains a NUL character.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(Yarn::new("C").to_cstring().unwrap().as_bytes(), b"C");
+  /// assert!(Yarn::new("a\0b").to_cstring().is_err());
+  /// ```
+  pub fn to_cstring(&self) -> Result<CString, NulError> {
+    CString::new(self.as_bytes())
+  }
+
+  /// Returns a hash of this yarn's contents that is the same on every
+  /// platform.
+  ///
+  /// Unlike the `Hash` impl, which may vary between platforms and compiler
+  /// versions, this is the 64-bit FNV-1a hash of the yarn's bytes, so it is
+  /// suitable for persisting.
+  ///
+  /// Requires the `fnv` feature.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(Yarn::new("foobar").stable_hash(), 0x85944171f73967e8);
+  /// ```
+  #[cfg(feature = "fnv")]
+  pub fn stable_hash(&self) -> u64 {
+    crate::fnv::hash(self.as_bytes())
+  }
+
+  /// Returns an iterator over the extended grapheme clusters of this yarn,
+  /// as defined by [UAX #29](https://www.unicode.org/reports/tr29/).
+  ///
+  /// The clusters point into this yarn's buffer without copying, unless they
+  /// are small enough to be inlined.
+  ///
+  /// Requires the `unicode` feature.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("e\u{301}🐈\u{200d}⬛!");
+  /// let graphemes = yarn.graphemes().collect::<Vec<_>>();
+  /// assert_eq!(graphemes, ["e\u{301}", "🐈\u{200d}⬛", "!"]);
+  /// ```
+  #[cfg(feature = "unicode")]
+  pub fn graphemes(&self) -> crate::Graphemes<'_> {
+    self.as_ref().graphemes()
+  }
+
+  /// Returns an iterator over the extended grapheme clusters of this yarn,
+  /// along with their byte offsets.
+  ///
+  /// Requires the `unicode` feature.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("e\u{301}🐈\u{200d}⬛!");
+  /// let starts = yarn.grapheme_indices().map(|(i, _)| i).collect::<Vec<_>>();
+  /// assert_eq!(starts, [0, 3, 13]);
+  /// ```
+  #[cfg(feature = "unicode")]
+  pub fn grapheme_indices(&self) -> crate::GraphemeIndices<'_> {
+    self.as_ref().grapheme_indices()
+  }
+
+  /// Percent-encodes every byte of this yarn that is in `set`, as `%XY`.
+  ///
+  /// Does not allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("a b&c");
+  /// assert_eq!(yarn.percent_encode(EncodeSet::COMPONENT), "a%20b%26c");
+  /// ```
+  pub fn percent_encode(&self, set: EncodeSet) -> YarnBox<'static, str> {
+    self.as_ref().percent_encode(set)
+  }
+
+  /// Decodes every `%XY` percent-escape in this yarn.
+  ///
+  /// Returns an error if a `%` is not followed by two hex digits. Does not
+  /// allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("caf%C3%A9%21");
+  /// assert_eq!(yarn.percent_decode().unwrap(), "café!".as_bytes());
+  ///
+  /// let err = Yarn::new("50%").percent_decode();
+  /// assert_eq!(err, Err(PercentError::InvalidEscape(2)));
+  /// ```
+  pub fn percent_decode(&self) -> Result<YarnBox<'static, [u8]>, PercentError> {
+    self.as_ref().percent_decode()
+  }
+
+  /// Escapes this yarn as the contents of a JSON string literal, without the
+  /// surrounding quotes.
+  ///
+  /// Quotes, backslashes, and control characters are escaped; everything else
+  /// is copied as-is. Does not allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("\"é\"\n");
+  /// assert_eq!(yarn.json_escape(), r#"\"é\"\n"#);
+  /// ```
+  pub fn json_escape(&self) -> YarnBox<'static, str> {
+    self.as_ref().json_escape()
+  }
+
+  /// Like [`Yarn::json_escape()`], but also escapes every non-ASCII character
+  /// as `\uXXXX`, so the result is pure ASCII.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("\"é\"\n");
+  /// assert_eq!(yarn.json_escape_ascii(), r#"\"\u00e9\"\n"#);
+  /// ```
+  pub fn json_escape_ascii(&self) -> YarnBox<'static, str> {
+    self.as_ref().json_escape_ascii()
+  }
+
+  /// Returns this yarn padded at the start with `fill` to `width` characters,
+  /// like `format!("{:>width$}")`.
+  ///
+  /// Width is measured in `char`s. Does not allocate if the result can be
+  /// inlined. If this yarn is already at least `width` characters wide, it is
+  /// returned unchanged, as if by [`Clone`], rather than truncated.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("42");
+  /// assert_eq!(yarn.pad_start(5, '0'), "00042");
+  /// assert_eq!(yarn.pad_start(1, '0'), "42");
+  /// ```
+  pub fn pad_start(&self, width: usize, fill: char) -> Self {
+    let padded = self.as_ref().pad(width, fill, |pad| pad);
+    padded.unwrap_or_else(|| self.clone())
+  }
+
+  /// Returns this yarn padded at the end with `fill` to `width` characters,
+  /// like `format!("{:<width$}")`.
+  ///
+  /// See [`Yarn::pad_start()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("né");
+  /// assert_eq!(yarn.pad_end(4, '.'), "né..");
+  /// ```
+  pub fn pad_end(&self, width: usize, fill: char) -> Self {
+    let padded = self.as_ref().pad(width, fill, |_| 0);
+    padded.unwrap_or_else(|| self.clone())
+  }
+
+  /// Returns this yarn padded on both sides with `fill` to `width` characters,
+  /// like `format!("{:^width$}")`.
+  ///
+  /// If the padding cannot be split evenly, the extra character goes at the
+  /// end. See [`Yarn::pad_start()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("ab");
+  /// assert_eq!(yarn.center(5, '*'), "*ab**");
+  /// ```
+  pub fn center(&self, width: usize, fill: char) -> Self {
+    let padded = self.as_ref().pad(width, fill, |pad| pad / 2);
+    padded.unwrap_or_else(|| self.clone())
+  }
+
+  /// Returns an iterator over the characters of this yarn, back to front.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("añb");
+  /// assert_eq!(yarn.rchars().collect::<String>(), "bña");
+  /// assert_eq!(yarn.rchars().next_back(), Some('a'));
+  /// ```
+  pub fn rchars(&self) -> iter::Rev<str::Chars<'_>> {
+    self.as_str().chars().rev()
+  }
+
+  /// Returns a copy of this yarn with its characters in reverse order.
+  ///
+  /// This reverses Unicode scalar values, so multibyte characters are kept
+  /// intact, but grapheme clusters made of several scalars are not. Does not
+  /// allocate if the result can be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("añb");
+  /// assert_eq!(yarn.reverse_chars(), "bña");
+  /// ```
+  pub fn reverse_chars(&self) -> YarnBox<'static, str> {
+    self.as_ref().reverse_chars()
+  }
+
+  /// Parses this yarn into another type via [`str::parse()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = yarn!("{}", 6 * 7);
+  /// assert_eq!(yarn.parse::<u32>(), Ok(42));
+  /// assert!(yarn.parse::<bool>().is_err());
+  /// ```
+  pub fn parse<T: FromStr>(&self) -> Result<T, T::Err> {
+    self.as_str().parse()
+  }
+
+  /// Returns this yarn with leading and trailing whitespace removed.
+  ///
+  /// The result points into this yarn's buffer without copying, unless it is
+  /// small enough to be inlined. Trimming a static string produces a yarn that
+  /// remembers it is static.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::from_static("  a very long string constant\n");
+  /// let trimmed = yarn.trim();
+  /// assert_eq!(trimmed, "a very long string constant");
+  ///
+  /// // No copy was made.
+  /// assert_eq!(trimmed.as_ptr(), yarn[2..].as_ptr());
+  /// assert!(trimmed.immortalize().is_some());
+  /// ```
+  pub fn trim(&self) -> YarnRef<'_, str> {
+    self.as_ref().trim()
+  }
+
+  /// Returns this yarn with leading whitespace removed.
+  ///
+  /// See [`Yarn::trim()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(yarn!("  jellybeans  ").trim_start(), "jellybeans  ");
+  /// ```
+  pub fn trim_start(&self) -> YarnRef<'_, str> {
+    self.as_ref().trim_start()
+  }
+
+  /// Returns this yarn with trailing whitespace removed.
+  ///
+  /// See [`Yarn::trim()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(yarn!("  jellybeans  ").trim_end(), "  jellybeans");
+  /// ```
+  pub fn trim_end(&self) -> YarnRef<'_, str> {
+    self.as_ref().trim_end()
+  }
+
+  /// Returns this yarn with all leading and trailing copies of `c` removed.
+  ///
+  /// See [`Yarn::trim()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// assert_eq!(yarn!("__jellybeans__").trim_matches('_'), "jellybeans");
+  /// ```
+  pub fn trim_matches(&self, c: char) -> YarnRef<'_, str> {
+    self.as_ref().trim_matches(c)
+  }
+
+  /// Returns this yarn with `prefix` removed, if it starts with it.
+  ///
+  /// Like [`Yarn::trim()`], the result points into this yarn's buffer unless
+  /// it is small enough to be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("https://example.com/jellybeans");
+  /// let rest = yarn.strip_prefix("https://").unwrap();
+  /// assert_eq!(rest, "example.com/jellybeans");
+  /// assert_eq!(rest.as_ptr(), yarn[8..].as_ptr());
+  ///
+  /// assert!(yarn.strip_prefix("http://").is_none());
+  /// ```
+  pub fn strip_prefix(&self, prefix: impl Pattern) -> Option<YarnRef<'_, str>> {
+    self.as_ref().strip_prefix(prefix)
+  }
+
+  /// Returns this yarn with `suffix` removed, if it ends with it.
+  ///
+  /// See [`Yarn::strip_prefix()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("jellybeans.txt");
+  /// assert_eq!(yarn.strip_suffix(".txt").unwrap(), "jellybeans");
+  /// assert!(yarn.strip_suffix('.').is_none());
+  /// ```
+  pub fn strip_suffix(&self, suffix: impl Pattern) -> Option<YarnRef<'_, str>> {
+    self.as_ref().strip_suffix(suffix)
+  }
+
+  /// Returns an iterator over the pieces of this yarn separated by `pat`.
+  ///
+  /// This has the same semantics as [`str::split()`], including yielding
+  /// empty pieces at the start and end. Like [`Yarn::trim()`], each piece
+  /// points into this yarn's buffer unless it is small enough to be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("jellybeans,licorice,,gumdrops,");
+  /// let pieces = yarn.split(',').collect::<Vec<_>>();
+  /// assert_eq!(pieces, ["jellybeans", "licorice", "", "gumdrops", ""]);
+  ///
+  /// let pieces = yarn.split("ice").collect::<Vec<_>>();
+  /// assert_eq!(pieces, ["jellybeans,licor", ",,gumdrops,"]);
+  ///
+  /// let yarn = Yarn::new("a1b22c");
+  /// let pieces = yarn.split(|c: char| c.is_numeric()).collect::<Vec<_>>();
+  /// assert_eq!(pieces, ["a", "b", "", "c"]);
+  ///
+  /// let yarn = Yarn::new("abc");
+  /// let pieces = yarn.split("").collect::<Vec<_>>();
+  /// assert_eq!(pieces, ["", "a", "b", "c", ""]);
+  /// ```
+  pub fn split<P: Pattern>(&self, pat: P) -> Split<'_, P> {
+    self.as_ref().split(pat)
+  }
+
+  /// Returns an iterator over the non-overlapping matches of `pat` in this
+  /// yarn.
+  ///
+  /// As with [`str::matches()`], matches are found from front to back, and a
+  /// match never overlaps the previous one. The matches point into this
+  /// yarn's buffer without copying, unless they are small enough to be
+  /// inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("a1bb22ccc333");
+  /// let digits = yarn.matches(|c: char| c.is_numeric()).count();
+  /// assert_eq!(digits, 6);
+  ///
+  /// let yarn = Yarn::new("aaaa");
+  /// assert_eq!(yarn.matches("aa").collect::<Vec<_>>(), ["aa", "aa"]);
+  /// ```
+  pub fn matches<P: Pattern>(&self, pat: P) -> Matches<'_, P> {
+    self.as_ref().matches(pat)
+  }
+
+  /// Returns an iterator over the non-overlapping matches of `pat` in this
+  /// yarn, along with their byte offsets.
+  ///
+  /// This is like [`Yarn::matches()`], but also yields the offset of each
+  /// match, like [`str::match_indices()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("jelly, beans, and jelly");
+  /// let jellies = yarn.match_indices("jelly").collect::<Vec<_>>();
+  /// assert_eq!(jellies, [(0, "jelly".into()), (18, "jelly".into())]);
+  ///
+  /// let yarn = Yarn::new("héllo");
+  /// let empty = yarn.match_indices("").map(|(i, _)| i).collect::<Vec<_>>();
+  /// assert_eq!(empty, [0, 1, 3, 4, 5, 6]);
+  /// ```
+  pub fn match_indices<P: Pattern>(&self, pat: P) -> MatchIndices<'_, P> {
+    self.as_ref().match_indices(pat)
+  }
+
+  /// Returns an iterator over at most `n` pieces of this yarn separated by
+  /// `pat`.
+  ///
+  /// The last piece contains the rest of the yarn, like in [`str::splitn()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("key=value=more");
+  /// let pieces = yarn.splitn(2, '=').collect::<Vec<_>>();
+  /// assert_eq!(pieces, ["key", "value=more"]);
+  ///
+  /// assert_eq!(yarn.splitn(0, '=').count(), 0);
+  /// ```
+  pub fn splitn<P: Pattern>(&self, n: usize, pat: P) -> Split<'_, P> {
+    self.as_ref().splitn(n, pat)
+  }
+
+  /// Returns an iterator over the lines of this yarn.
+  ///
+  /// This has the same semantics as [`str::lines()`]: lines end in either
+  /// `\n` or `\r\n`, which are not included, and a trailing empty line is
+  /// not yielded. Each line aliases this yarn, unless it is small enough to
+  /// inline.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("jellybeans\r\nlicorice\n\ngumdrops\n");
+  /// let lines = yarn.lines().collect::<Vec<_>>();
+  /// assert_eq!(lines, ["jellybeans", "licorice", "", "gumdrops"]);
+  /// ```
+  pub fn lines(&self) -> Lines<'_> {
+    self.as_ref().lines()
+  }
+
+  /// Splits this yarn into two at byte offset `mid`.
+  ///
+  /// Both halves alias this yarn, unless they are small enough to inline.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `mid` is greater than this yarn's length, or if it does not lie
+  /// on a `char` boundary.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("jelly🐈beans");
+  /// let (jelly, beans) = yarn.split_at(9);
+  /// assert_eq!([jelly, beans], ["jelly🐈", "beans"]);
+  /// ```
+  pub fn split_at(&self, mid: usize) -> (YarnRef<'_, str>, YarnRef<'_, str>) {
+    self.as_ref().split_at(mid)
+  }
+
+  /// Splits this yarn around the first match of `pat`.
+  ///
+  /// This has the same semantics as [`str::split_once()`]. Both halves alias
+  /// this yarn, unless they are small enough to inline.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("key=value=more");
+  /// let (key, value) = yarn.split_once('=').unwrap();
+  /// assert_eq!([key, value], ["key", "value=more"]);
+  ///
+  /// let (empty, all) = yarn.split_once("").unwrap();
+  /// assert_eq!([empty, all], ["", "key=value=more"]);
+  /// assert!(yarn.split_once(':').is_none());
+  /// ```
+  pub fn split_once(
+    &self,
+    pat: impl Pattern,
+  ) -> Option<(YarnRef<'_, str>, YarnRef<'_, str>)> {
+    self.as_ref().split_once(pat)
+  }
+
+  /// Splits this yarn around the last match of `pat`.
+  ///
+  /// This has the same semantics as [`str::rsplit_once()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("key=value=more");
+  /// let (key, value) = yarn.rsplit_once('=').unwrap();
+  /// assert_eq!([key, value], ["key=value", "more"]);
+  ///
+  /// let (all, empty) = yarn.rsplit_once("").unwrap();
+  /// assert_eq!([all, empty], ["key=value=more", ""]);
+  /// ```
+  pub fn rsplit_once(
+    &self,
+    pat: impl Pattern,
+  ) -> Option<(YarnRef<'_, str>, YarnRef<'_, str>)> {
+    self.as_ref().rsplit_once(pat)
+  }
+
+  /// Returns a new yarn with all matches of `from` replaced with `to`.
+  ///
+  /// This has the same semantics as [`str::replace()`]. If the result fits in
+  /// an inlined yarn, it will not allocate. If nothing would change, because
+  /// there are no matches or every match is already equal to `to`, this
+  /// returns an alias of this yarn instead of copying it.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("jellybeans and gumdrops and licorice");
+  /// assert_eq!(yarn.replace(" and ", ", "), "jellybeans, gumdrops, licorice");
+  /// assert_eq!(yarn.replace('e', "E"), "jEllybEans and gumdrops and licoricE");
+  /// assert_eq!(Yarn::new("abc").replace("", "-"), "-a-b-c-");
+  ///
+  /// // No matches, so no copies.
+  /// let same = yarn.replace("taffy", "nougat");
+  /// assert_eq!(same.as_slice().as_ptr(), yarn.as_slice().as_ptr());
+  ///
+  /// let same = yarn.replace("and", Yarn::from_static("and"));
+  /// assert_eq!(same.as_slice().as_ptr(), yarn.as_slice().as_ptr());
+  /// ```
+  pub fn replace<'b>(
+    &self,
+    from: impl Pattern,
+    to: impl Into<YarnBox<'b, str>>,
+  ) -> YarnBox<'_, str> {
+    self.as_ref().replace(from, to)
+  }
+
+  /// Returns a new yarn with the first `n` matches of `from` replaced with
+  /// `to`.
+  ///
+  /// This has the same semantics as [`str::replacen()`], and the same
+  /// allocation behavior as [`Yarn::replace()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("a.b.c.d");
+  /// assert_eq!(yarn.replacen('.', "::", 2), "a::b::c.d");
+  /// assert_eq!(yarn.replacen('.', "::", 0), "a.b.c.d");
+  /// ```
+  pub fn replacen<'b>(
+    &self,
+    from: impl Pattern,
+    to: impl Into<YarnBox<'b, str>>,
+    n: usize,
+  ) -> YarnBox<'_, str> {
+    self.as_ref().replacen(from, to, n)
+  }
+
+  /// Returns an iterator over the characters of this yarn.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("猫🐈");
+  /// assert_eq!(yarn.chars().collect::<Vec<_>>(), ['猫', '🐈']);
+  /// ```
+  pub fn chars(&self) -> str::Chars<'_> {
+    self.as_str().chars()
+  }
+
+  /// Returns an iterator over the characters of this yarn, and their byte
+  /// offsets.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("猫🐈");
+  /// assert_eq!(yarn.char_indices().collect::<Vec<_>>(), [(0, '猫'), (3, '🐈')]);
+  /// ```
+  pub fn char_indices(&self) -> str::CharIndices<'_> {
+    self.as_str().char_indices()
+  }
+
+  /// Returns an iterator over the UTF-16 encoding of this yarn.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("mu𝄞ic");
+  /// let utf16 = yarn.encode_utf16().collect::<Vec<_>>();
+  /// assert_eq!(utf16, [0x6d, 0x75, 0xd834, 0xdd1e, 0x69, 0x63]);
+  /// assert_eq!(Yarn::from_utf16(&utf16).unwrap(), yarn);
+  /// ```
+  pub fn encode_utf16(&self) -> str::EncodeUtf16<'_> {
+    self.as_str().encode_utf16()
+  }
+
+  /// Appends a string to the end of this yarn.
+  ///
+  /// If this yarn does not uniquely own its buffer (e.g., it is an alias of
+  /// some other buffer or a string constant), its contents are first copied
+  /// into a buffer that it does own. The yarn stays inlined for as long as it
+  /// fits; once it spills onto the heap, the allocation is grown in place.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("Byzantium");
+  /// yarn.push_str(", Constantinople");
+  /// yarn.push_str(", Istanbul");
+  /// assert_eq!(yarn, "Byzantium, Constantinople, Istanbul");
+  ///
+  /// // Pushing onto an alias does not disturb the aliased buffer.
+  /// let string = String::from("a very long string that is not inlined");
+  /// let mut alias = YarnBox::new(string.as_str());
+  /// alias.push_str("!");
+  /// assert_eq!(alias, "a very long string that is not inlined!");
+  /// assert_eq!(string, "a very long string that is not inlined");
+  /// ```
+  pub fn push_str(&mut self, s: &str) {
+    unsafe {
+      // SAFETY: Concatenating two strings produces a valid string.
+      self.append_bytes(s.as_bytes())
+    }
+  }
+
+  /// Appends a single character to the end of this yarn.
+  ///
+  /// See [`YarnBox::push_str()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("jellybean");
+  /// yarn.push('s');
+  /// yarn.push('🐈');
+  /// assert_eq!(yarn, "jellybeans🐈");
+  /// ```
+  pub fn push(&mut self, c: char) {
+    self.push_str(c.encode_utf8(&mut [0; 4]))
+  }
+
+  /// Inserts a string at byte offset `idx`.
+  ///
+  /// If the yarn does not own its buffer, it is copied first; the result is
+  /// inlined if it fits.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `idx` is out of bounds or does not lie on a `char` boundary.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("jellys");
+  /// yarn.insert_str(5, "bean");
+  /// assert_eq!(yarn, "jellybeans");
+  /// ```
+  pub fn insert_str(&mut self, idx: usize, s: &str) {
+    assert!(self.as_str().is_char_boundary(idx));
+    if s.is_empty() {
+      return;
+    }
+
+    unsafe {
+      // SAFETY: Appending a string and then rotating it into place at a char
+      // boundary produces a valid string. Appending a non-empty string always
+      // leaves the yarn uniquely owned.
+      self.append_bytes(s.as_bytes());
+      self.raw.as_mut_slice()[idx..].rotate_right(s.len());
+    }
+  }
+
+  /// Inserts a character at byte offset `idx`.
+  ///
+  /// See [`Yarn::insert_str()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("jellybeans");
+  /// yarn.insert(0, '🐈');
+  /// assert_eq!(yarn, "🐈jellybeans");
+  /// ```
+  pub fn insert(&mut self, idx: usize, c: char) {
+    self.insert_str(idx, c.encode_utf8(&mut [0; 4]))
+  }
+
+  /// Removes the character at byte offset `idx`, and returns it.
+  ///
+  /// If the yarn does not own its buffer, it is copied first; the result is
+  /// inlined if it fits.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `idx` is not less than this yarn's length, or if it does not
+  /// lie on a `char` boundary.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("jelly🐈beans");
+  /// assert_eq!(yarn.remove(5), '🐈');
+  /// assert_eq!(yarn, "jellybeans");
+  /// ```
+  pub fn remove(&mut self, idx: usize) -> char {
+    let c = self.as_str()[idx..]
+      .chars()
+      .next()
+      .expect("cannot remove a char from the end of a yarn");
+    let len = c.len_utf8();
+
+    unsafe {
+      // SAFETY: Rotating a whole char to the end of the string and then
+      // truncating it away produces a valid string.
+      self.bytes_mut()[idx..].rotate_left(len);
+      self.truncate_bytes(self.len() - len);
+    }
+    c
+  }
+
+  /// Shortens this yarn to its first `len` bytes.
+  ///
+  /// If `len` is not less than this yarn's current length, this has no
+  /// effect. A uniquely owned heap buffer is kept, so that it can be reused by
+  /// later appends; a yarn that aliases some other buffer is only copied when
+  /// it is next appended to.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `len` does not lie on a `char` boundary.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("jellybeans🐈");
+  /// yarn.truncate(10);
+  /// assert_eq!(yarn, "jellybeans");
+  /// ```
+  pub fn truncate(&mut self, len: usize) {
+    if len < self.len() {
+      assert!(self.as_str().is_char_boundary(len));
+      unsafe {
+        // SAFETY: A prefix of a string that ends on a char boundary is a valid
+        // string.
+        self.truncate_bytes(len)
+      }
+    }
+  }
+
+  /// Removes the given byte range from this yarn, returning an iterator over
+  /// the removed characters.
+  ///
+  /// The range is removed when the iterator is dropped, whether or not it was
+  /// fully consumed. A uniquely owned heap buffer is kept; other yarns are
+  /// copied first, like in [`Yarn::push_str()`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if the range is out of bounds, or if either end does not lie on
+  /// a `char` boundary.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("jelly🐈beans");
+  /// let removed = yarn.drain(3..9).collect::<String>();
+  /// assert_eq!(removed, "ly🐈");
+  /// assert_eq!(yarn, "jelbeans");
+  ///
+  /// yarn.drain(..3).next();
+  /// assert_eq!(yarn, "beans");
+  /// ```
+  pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, 'a> {
+    let start = match range.start_bound() {
+      Bound::Included(&n) => n,
+      Bound::Excluded(&n) => n.checked_add(1).expect("range start overflow"),
+      Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+      Bound::Included(&n) => n.checked_add(1).expect("range end overflow"),
+      Bound::Excluded(&n) => n,
+      Bound::Unbounded => self.len(),
+    };
+
+    // This panics on out-of-bounds ranges and non-char boundaries.
+    let _ = &self.as_str()[start..end];
+    Drain::new(self, start, end)
+  }
+
+  /// Removes every character for which `f` returns `false`, in place.
+  ///
+  /// Characters are visited in order, and the rest are kept in their original
+  /// order. If nothing is removed, this does not copy a yarn that does not
+  /// own its buffer; otherwise it is copied first, like in
+  /// [`Yarn::push_str()`]. The result is inlined if it fits.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("1_000_000");
+  /// yarn.retain(|c| c != '_');
+  /// assert_eq!(yarn, "1000000");
+  /// ```
+  pub fn retain(&mut self, mut f: impl FnMut(char) -> bool) {
+    unsafe {
+      // SAFETY: Splitting a string into chars and concatenating some of them
+      // produces a valid string.
+      self.retain_units(|rest| {
+        let c = str::from_utf8_unchecked(rest).chars().next().unwrap();
+        (c.len_utf8(), f(c))
+      })
+    }
+  }
+
+  /// Removes every non-overlapping match of `pat` from this yarn, in place.
+  ///
+  /// Matches are found from left to right, like in [`Yarn::replace()`], and
+  /// empty matches are ignored. If there are no matches, this does not copy a
+  /// yarn that does not own its buffer; otherwise it is copied first, like in
+  /// [`Yarn::push_str()`]. The result is inlined if it fits.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("jelly, beans, and, gumdrops");
+  /// yarn.remove_matches(", ");
+  /// assert_eq!(yarn, "jellybeansandgumdrops");
+  ///
+  /// yarn.remove_matches(char::is_uppercase);
+  /// assert_eq!(yarn, "jellybeansandgumdrops");
+  /// ```
+  pub fn remove_matches(&mut self, mut pat: impl Pattern) {
+    unsafe {
+      // SAFETY: Matches always begin and end on char boundaries, so this only
+      // splits the string at char boundaries.
+      self.retain_units(|rest| {
+        let rest = str::from_utf8_unchecked(rest);
+        match pat.find_in(rest) {
+          // Skip the character after an empty match, so that it is not found
+          // again.
+          Some(found) if found.is_empty() => {
+            let next = rest[found.end..].chars().next();
+            (found.end + next.map_or(0, char::len_utf8), true)
+          }
+          Some(found) if found.start > 0 => (found.start, true),
+          Some(found) => (found.end, false),
+          None => (rest.len(), true),
+        }
+      })
+    }
+  }
+
+  /// Checks whether this yarn is equal to `that`, ignoring ASCII case.
+  ///
+  /// This compares the bytes directly, without making lowercase copies.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("Content-Type");
+  /// assert!(yarn.eq_ignore_ascii_case("content-TYPE"));
+  /// ```
+  pub fn eq_ignore_ascii_case(&self, that: impl AsRef<str>) -> bool {
+    self
+      .as_bytes()
+      .eq_ignore_ascii_case(that.as_ref().as_bytes())
+  }
+
+  /// Compares this yarn with `that` lexicographically, ignoring ASCII case.
+  ///
+  /// This is the ordering of the two yarns after converting both to ASCII
+  /// lowercase, but it does not make lowercase copies.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// # use std::cmp::Ordering;
+  /// let yarn = Yarn::new("Content-Type");
+  /// assert_eq!(yarn.cmp_ignore_ascii_case("content-typf"), Ordering::Less);
+  /// ```
+  pub fn cmp_ignore_ascii_case(&self, that: impl AsRef<str>) -> Ordering {
+    crate::caseless::cmp(self.as_bytes(), that.as_ref().as_bytes())
+  }
+
+  /// Returns a copy of this yarn with every ASCII letter mapped to uppercase.
+  ///
+  /// Non-ASCII characters are unchanged. This
+  /// does not allocate if the yarn is small enough to be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("Grüße");
+  /// assert_eq!(yarn.to_ascii_uppercase(), "GRüßE");
+  /// ```
+  pub fn to_ascii_uppercase(&self) -> Self {
+    let mut copy = self.to_owned_yarn();
+    copy.make_ascii_uppercase();
+    copy
+  }
+
+  /// Returns a copy of this yarn with every ASCII letter mapped to lowercase.
+  ///
+  /// Non-ASCII characters are unchanged. This
+  /// does not allocate if the yarn is small enough to be inlined.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("A VERY LONG STRING, NOT INLINED");
+  /// assert_eq!(yarn.to_ascii_lowercase(), "a very long string, not inlined");
+  /// ```
+  pub fn to_ascii_lowercase(&self) -> Self {
+    let mut copy = self.to_owned_yarn();
+    copy.make_ascii_lowercase();
+    copy
+  }
+
+  /// Maps every ASCII letter in this yarn to uppercase, in place.
+  ///
+  /// ASCII case mapping never changes the length of a yarn, so this never
+  /// reallocates an inlined or heap-allocated yarn. Other yarns are copied
+  /// first, like in [`YarnBox::push_str()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let mut yarn = Yarn::new("Jellybeans!");
+  /// yarn.make_ascii_uppercase();
+  /// assert_eq!(yarn, "JELLYBEANS!");
+  /// ```
+  pub fn make_ascii_uppercase(&mut self) {
+    unsafe {
+      // SAFETY: ASCII case mapping only changes ASCII bytes, and
+      // replaces them with other ASCII bytes, so UTF-8 validity is preserved.
+      self.bytes_mut().make_ascii_uppercase()
+    }
+  }
+
+  /// Maps every ASCII letter in this yarn to lowercase, in place.
+  ///
+  /// See [`YarnBox::make_ascii_uppercase()`].
+  pub fn make_ascii_lowercase(&mut self) {
+    unsafe {
+      // SAFETY: ASCII case mapping only changes ASCII bytes, and
+      // replaces them with other ASCII bytes, so UTF-8 validity is preserved.
+      self.bytes_mut().make_ascii_lowercase()
+    }
+  }
+
+  /// Returns the lowercase equivalent of this yarn, according to Unicode.
+  ///
+  /// Unlike [`Yarn::to_ascii_lowercase()`], the result may have a different
+  /// length. It is built up inline, and only spills onto the heap if it
+  /// does not fit.
+  ///
+  /// Each character is mapped independently with [`char::to_lowercase()`], so
+  /// unlike [`str::to_lowercase()`], a word-final `Σ` becomes `σ`, not `ς`.
+  ///
+  /// Requires the `unicode-case` feature.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("ὈΔΥΣΣΕΎΣ");
+  /// assert_eq!(yarn.to_lowercase(), "ὀδυσσεύσ");
+  ///
+  /// let yarn = Yarn::new("İ");
+  /// assert_eq!(yarn.to_lowercase(), "i\u{307}");
+  /// ```
+  #[cfg(feature = "unicode-case")]
+  pub fn to_lowercase(&self) -> YarnBox<'static, str> {
+    self.chars().flat_map(char::to_lowercase).collect()
+  }
+
+  /// Returns the uppercase equivalent of this yarn, according to Unicode.
+  ///
+  /// Unlike [`Yarn::to_ascii_uppercase()`], the result may have a different
+  /// length. It is built up inline, and only spills onto the heap if it
+  /// does not fit.
+  ///
+  /// Requires the `unicode-case` feature.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let yarn = Yarn::new("Grüße");
+  /// assert_eq!(yarn.to_uppercase(), "GRÜSSE");
+  /// ```
+  #[cfg(feature = "unicode-case")]
+  pub fn to_uppercase(&self) -> YarnBox<'static, str> {
+    self.chars().flat_map(char::to_uppercase).collect()
   }
 
   /// Converts this yarn into a boxed slice, potentially by copying it.
@@ -712,12 +3089,37 @@ impl YarnBox<'_, str> {
     self.into_string().into()
   }
 
+  /// Converts this yarn into a [`Cow`], without copying borrowed data.
+  ///
+  /// Static and aliased yarns become [`Cow::Borrowed`]. Inlined and heap
+  /// yarns become [`Cow::Owned`], via [`Yarn::into_string()`].
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// # use std::borrow::Cow;
+  /// let cow = Yarn::from_static("a static string").into_cow();
+  /// assert!(matches!(cow, Cow::Borrowed("a static string")));
+  ///
+  /// let cow = yarn!("{}", 42).into_cow();
+  /// assert!(matches!(cow, Cow::Owned(_)));
+  /// ```
+  pub fn into_cow(self) -> Cow<'a, str> {
+    match self.borrowed() {
+      Some(s) => Cow::Borrowed(s),
+      None => Cow::Owned(self.into_string()),
+    }
+  }
+
   /// Converts this yarn into a string, potentially by copying it.
+  ///
+  /// Like [`YarnBox::into_vec()`], this reuses a uniquely owned heap allocation
+  /// without reallocating.
   pub fn into_string(self) -> String {
     unsafe { String::from_utf8_unchecked(self.into_bytes().into_vec()) }
   }
 }
 
+#[cfg(not(feature = "allocator_api"))]
 impl<Buf> Deref for YarnBox<'_, Buf>
 where
   Buf: crate::Buf + ?Sized,
@@ -726,32 +3128,205 @@ where
   fn deref(&self) -> &Buf {
     self.as_slice()
   }
-}
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<Buf> Drop for YarnBox<'_, Buf>
+where
+  Buf: crate::Buf + ?Sized,
+{
+  fn drop(&mut self) {
+    let layout = buf_trait::layout_of(self.as_slice());
+    unsafe { self.raw.destroy_in(&Global, layout) }
+  }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<Buf> Clone for YarnBox<'_, Buf>
+where
+  Buf: crate::Buf + ?Sized,
+{
+  fn clone(&self) -> Self {
+    unsafe {
+      // SAFETY: clone_in() gives shared copies their own reference count, and
+      // copies unshared heap buffers.
+      let layout = buf_trait::layout_of(self.as_slice());
+      Self::from_raw(self.raw.clone_in(&Global, layout))
+    }
+  }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<Buf, A> Deref for YarnBox<'_, Buf, A>
+where
+  Buf: crate::Buf + ?Sized,
+  A: Allocator,
+{
+  type Target = Buf;
+  fn deref(&self) -> &Buf {
+    unsafe { buf_trait::as_buf(self.raw.as_slice()) }
+  }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<Buf, A> Drop for YarnBox<'_, Buf, A>
+where
+  Buf: crate::Buf + ?Sized,
+  A: Allocator,
+{
+  fn drop(&mut self) {
+    let layout = buf_trait::layout_of(&**self);
+    unsafe { self.raw.destroy_in(&self.alloc, layout) }
+  }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<Buf, A> Clone for YarnBox<'_, Buf, A>
+where
+  Buf: crate::Buf + ?Sized,
+  A: Allocator + Clone,
+{
+  fn clone(&self) -> Self {
+    let layout = buf_trait::layout_of(&**self);
+    let alloc = self.alloc.clone();
+    unsafe {
+      // SAFETY: clone_in() gives shared copies their own reference count, and
+      // copies unshared heap buffers with the clone of their allocator.
+      let raw = self.raw.clone_in(&alloc, layout);
+      YarnBox { raw, alloc, _ph: PhantomData }
+    }
+  }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<'a, Buf, A> YarnBox<'a, Buf, A>
+where
+  Buf: crate::Buf + ?Sized,
+  A: Allocator,
+{
+  /// Returns a new, empty yarn that will allocate with `alloc`.
+  ///
+  /// Requires the `allocator_api` feature.
+  pub const fn new_in(alloc: A) -> Self {
+    let raw = *RawYarn::empty_aligned(Self::align_in());
+    Self { raw, alloc, _ph: PhantomData }
+  }
+
+  /// Returns a new, empty yarn that can hold `cap` bytes without
+  /// reallocating, which it allocates with `alloc`.
+  ///
+  /// Requires the `allocator_api` feature.
+  pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+    unsafe {
+      // SAFETY: The alignment of any type is a power of two.
+      let raw = RawYarn::with_capacity_in(&alloc, Self::align_in(), cap);
+      Self { raw, alloc, _ph: PhantomData }
+    }
+  }
+
+  /// Returns a copy of `buf`, allocated with `alloc` if it is too big to be
+  /// inlined.
+  ///
+  /// Requires the `allocator_api` feature.
+  pub fn copy_in(buf: &Buf, alloc: A) -> Self {
+    unsafe {
+      // SAFETY: The layout and pointer both come from `buf`.
+      let raw = RawYarn::copy_slice_in(
+        &alloc,
+        buf_trait::layout_of(buf),
+        buf as *const Buf as *const u8,
+      );
+      Self { raw, alloc, _ph: PhantomData }
+    }
+  }
+
+  /// Returns a reference to the allocator this yarn allocates with.
+  ///
+  /// Requires the `allocator_api` feature.
+  pub fn allocator(&self) -> &A {
+    &self.alloc
+  }
+
+  /// Appends `buf` to the end of this yarn, allocating with this yarn's
+  /// allocator if necessary.
+  ///
+  /// Requires the `allocator_api` feature.
+  pub fn append_in(&mut self, buf: &Buf) {
+    unsafe {
+      // SAFETY: Concatenating two Bufs produces a valid Buf, and this yarn
+      // only ever allocates with self.alloc.
+      self
+        .raw
+        .append_in(&self.alloc, Self::align_in(), buf.as_bytes())
+    }
+  }
+
+  /// Shrinks this yarn's buffer to fit its data, freeing it with this yarn's
+  /// allocator if the data can be inlined.
+  ///
+  /// Requires the `allocator_api` feature.
+  pub fn shrink_to_fit_in(&mut self) {
+    unsafe {
+      // SAFETY: This is the alignment this yarn was allocated with, and this
+      // yarn only ever allocates with self.alloc.
+      self.raw.shrink_to_fit_in(&self.alloc, Self::align_in())
+    }
+  }
 
-impl<Buf> Drop for YarnBox<'_, Buf>
-where
-  Buf: crate::Buf + ?Sized,
-{
-  fn drop(&mut self) {
-    unsafe { self.raw.destroy(buf_trait::layout_of(self.as_slice())) }
+  /// Shortens this yarn to its first `len` bytes, copying it with this yarn's
+  /// allocator if its buffer is not uniquely owned.
+  ///
+  /// # Safety
+  ///
+  /// `len` must leave a valid `Buf`.
+  unsafe fn truncate_bytes_in(&mut self, len: usize) {
+    if len < self.raw.len() {
+      self.raw.truncate_in(&self.alloc, Self::align_in(), len)
+    }
+  }
+
+  const fn align_in() -> usize {
+    mem::align_of::<Buf::Element>()
   }
 }
 
-impl<Buf> Clone for YarnBox<'_, Buf>
+#[cfg(feature = "allocator_api")]
+impl<T, A> YarnBox<'_, [T], A>
 where
-  Buf: crate::Buf + ?Sized,
+  [T]: crate::Buf,
+  A: Allocator,
 {
-  fn clone(&self) -> Self {
-    if let Some(yr) = self.to_ref() {
-      return yr.to_box();
+  /// Shortens this yarn to its first `len` elements, like
+  /// [`YarnBox::truncate()`].
+  ///
+  /// Requires the `allocator_api` feature.
+  pub fn truncate_in(&mut self, len: usize) {
+    unsafe {
+      // SAFETY: Any prefix of a slice that ends on an element boundary is a
+      // valid slice.
+      self.truncate_bytes_in(len.saturating_mul(mem::size_of::<T>()))
     }
+  }
+}
 
-    unsafe {
-      let copy = RawYarn::copy_slice(
-        buf_trait::layout_of(self.as_slice()),
-        self.as_bytes().as_ptr(),
-      );
-      Self::from_raw(copy)
+#[cfg(feature = "allocator_api")]
+impl<A: Allocator> YarnBox<'_, str, A> {
+  /// Shortens this yarn to its first `len` bytes, like
+  /// [`YarnBox::truncate()`].
+  ///
+  /// Requires the `allocator_api` feature.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `len` does not lie on a `char` boundary.
+  pub fn truncate_in(&mut self, len: usize) {
+    if len < self.raw.len() {
+      assert!(self.is_char_boundary(len));
+      unsafe {
+        // SAFETY: A prefix of a string that ends on a char boundary is a valid
+        // string.
+        self.truncate_bytes_in(len)
+      }
     }
   }
 }
@@ -774,6 +3349,15 @@ where
   Slice: AsRef<Buf> + ?Sized,
 {
   fn eq(&self, that: &Slice) -> bool {
+    #[cfg(feature = "simd")]
+    if mem::size_of::<Buf::Element>() == 1 {
+      // Byte-sized elements are equal exactly when their bytes are.
+      return crate::simd::eq(
+        self.as_slice().as_bytes(),
+        that.as_ref().as_bytes(),
+      );
+    }
+
     self.as_slice() == that.as_ref()
   }
 }
@@ -813,3 +3397,809 @@ impl<Buf: crate::Buf + ?Sized> Default for &YarnBox<'_, Buf> {
     YarnBox::empty()
   }
 }
+
+/// Concatenates two yarns.
+///
+/// The left operand's buffer is reused if it owns one with enough spare
+/// capacity; otherwise, the result is inlined if it fits.
+///
+/// ```
+/// # use byteyarn::*;
+/// let yarn = Yarn::new("jelly") + "beans";
+/// assert_eq!(yarn, "jellybeans");
+///
+/// let yarn = yarn + &Yarn::new(" & KNUCKLES");
+/// assert_eq!(yarn, "jellybeans & KNUCKLES");
+/// ```
+impl<Buf, Rhs> Add<Rhs> for YarnBox<'_, Buf>
+where
+  Buf: crate::Buf + ?Sized,
+  Rhs: AsRef<Buf>,
+{
+  type Output = Self;
+
+  fn add(mut self, rhs: Rhs) -> Self {
+    self += rhs;
+    self
+  }
+}
+
+/// Appends to a yarn in place.
+///
+/// Appending an empty buffer is a no-op, and will not copy or allocate.
+///
+/// ```
+/// # use byteyarn::*;
+/// let mut yarn = ByteYarn::new(b"jelly");
+/// yarn += b"beans";
+/// yarn += b"";
+/// assert_eq!(yarn, b"jellybeans");
+/// ```
+impl<Buf, Rhs> AddAssign<Rhs> for YarnBox<'_, Buf>
+where
+  Buf: crate::Buf + ?Sized,
+  Rhs: AsRef<Buf>,
+{
+  fn add_assign(&mut self, rhs: Rhs) {
+    unsafe {
+      // SAFETY: Concatenating two values of the same Buf produces a valid Buf.
+      self.append_bytes(rhs.as_ref().as_bytes())
+    }
+  }
+}
+
+impl Extend<char> for YarnBox<'_, str> {
+  fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+    for c in iter {
+      self.push(c);
+    }
+  }
+}
+
+impl<'a> Extend<&'a str> for YarnBox<'_, str> {
+  fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+    for s in iter {
+      self.push_str(s);
+    }
+  }
+}
+
+impl Extend<String> for YarnBox<'_, str> {
+  fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+    for s in iter {
+      self.push_str(&s);
+    }
+  }
+}
+
+impl<'a> Extend<YarnBox<'a, str>> for YarnBox<'_, str> {
+  fn extend<I: IntoIterator<Item = YarnBox<'a, str>>>(&mut self, iter: I) {
+    for s in iter {
+      self.push_str(&s);
+    }
+  }
+}
+
+impl<'a> Extend<YarnRef<'a, str>> for YarnBox<'_, str> {
+  fn extend<I: IntoIterator<Item = YarnRef<'a, str>>>(&mut self, iter: I) {
+    for s in iter {
+      self.push_str(&s);
+    }
+  }
+}
+
+impl Extend<u8> for YarnBox<'_, [u8]> {
+  fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+    for b in iter {
+      unsafe {
+        // SAFETY: Any bytes can be appended to a byte yarn.
+        self.append_bytes(&[b])
+      }
+    }
+  }
+}
+
+impl<'a> Extend<&'a [u8]> for YarnBox<'_, [u8]> {
+  fn extend<I: IntoIterator<Item = &'a [u8]>>(&mut self, iter: I) {
+    for b in iter {
+      unsafe {
+        // SAFETY: Any bytes can be appended to a byte yarn.
+        self.append_bytes(b)
+      }
+    }
+  }
+}
+
+impl<'a> Extend<YarnBox<'a, [u8]>> for YarnBox<'_, [u8]> {
+  fn extend<I: IntoIterator<Item = YarnBox<'a, [u8]>>>(&mut self, iter: I) {
+    for b in iter {
+      self.extend([b.as_slice()]);
+    }
+  }
+}
+
+impl<'a> Extend<YarnRef<'a, [u8]>> for YarnBox<'_, [u8]> {
+  fn extend<I: IntoIterator<Item = YarnRef<'a, [u8]>>>(&mut self, iter: I) {
+    for b in iter {
+      self.extend([b.as_slice()]);
+    }
+  }
+}
+
+impl Extend<Vec<u8>> for YarnBox<'_, [u8]> {
+  fn extend<I: IntoIterator<Item = Vec<u8>>>(&mut self, iter: I) {
+    for b in iter {
+      self.extend([b.as_slice()]);
+    }
+  }
+}
+
+impl<'b> IntoIterator for &'b YarnBox<'_, [u8]> {
+  type Item = u8;
+  type IntoIter = iter::Copied<slice::Iter<'b, u8>>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.bytes()
+  }
+}
+
+impl<'b> IntoIterator for &'b YarnRef<'_, [u8]> {
+  type Item = u8;
+  type IntoIter = iter::Copied<slice::Iter<'b, u8>>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.bytes()
+  }
+}
+
+/// Collects an iterator of characters, strings, or yarns into a yarn.
+///
+/// The yarn is built up inline, and only spills onto the heap once it no longer
+/// fits. To put a separator between the pieces, use [`YarnJoin`].
+///
+/// ```
+/// # use byteyarn::*;
+/// let yarn: Yarn = "stressed".chars().rev().collect();
+/// assert_eq!(yarn, "desserts");
+///
+/// let yarn: Yarn = ["jelly", "beans"].into_iter().collect();
+/// assert_eq!(yarn, "jellybeans");
+///
+/// let empty: Yarn = Vec::<String>::new().into_iter().collect();
+/// assert_eq!(empty, "");
+/// ```
+impl<T> FromIterator<T> for YarnBox<'_, str>
+where
+  Self: Extend<T>,
+{
+  fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+    let mut yarn = Self::default();
+    yarn.extend(iter);
+    yarn
+  }
+}
+
+/// Collects an iterator of bytes, byte strings, or byte yarns into a yarn.
+///
+/// The yarn is built up inline, and only spills onto the heap once it no longer
+/// fits.
+///
+/// ```
+/// # use byteyarn::*;
+/// let yarn: ByteYarn = (b'a'..=b'e').collect();
+/// assert_eq!(yarn, b"abcde");
+///
+/// let yarn: ByteYarn = [&b"\xff"[..], b"\x00"].into_iter().collect();
+/// assert_eq!(yarn, b"\xff\x00");
+/// ```
+impl<T> FromIterator<T> for YarnBox<'_, [u8]>
+where
+  Self: Extend<T>,
+{
+  fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+    let mut yarn = Self::default();
+    yarn.extend(iter);
+    yarn
+  }
+}
+
+#[test]
+fn collect_stays_inline() {
+  use crate::Yarn;
+
+  let empty = Yarn::from_iter(Vec::<char>::new());
+  assert!(empty.raw.is_small());
+  assert!(empty.is_empty());
+
+  let small = "abc".chars().collect::<Yarn>();
+  assert!(small.raw.is_small());
+  assert_eq!(small, "abc");
+
+  let big = ["a"; RawYarn::SSO_LEN + 1].into_iter().collect::<Yarn>();
+  assert!(big.raw.on_heap());
+  assert_eq!(big.len(), RawYarn::SSO_LEN + 1);
+}
+
+#[test]
+fn add_assign_reuses_buffer() {
+  use crate::Yarn;
+
+  let mut yarn = Yarn::with_capacity(64);
+  yarn += "a string that does not fit inline";
+  let ptr = yarn.as_ptr();
+
+  yarn += "";
+  assert_eq!(yarn.as_ptr(), ptr);
+  yarn += "!";
+  assert_eq!(yarn.as_ptr(), ptr);
+  assert_eq!(yarn, "a string that does not fit inline!");
+}
+
+#[test]
+fn replace_stays_inline() {
+  use crate::Yarn;
+
+  let yarn = Yarn::from_string("a string that does not fit inline".into());
+  let short = yarn.replace("a string that does not fit ", "");
+  assert!(short.raw.is_small());
+  assert_eq!(short, "inline");
+
+  let yarn = Yarn::new("abc");
+  let long = yarn.replace('b', "a string that does not fit");
+  assert!(long.raw.on_heap());
+  assert_eq!(long, "aa string that does not fitc");
+}
+
+#[test]
+fn ascii_case_mapping_in_place() {
+  use crate::Yarn;
+
+  let mut small = Yarn::from_char('a');
+  small.make_ascii_uppercase();
+  assert!(small.raw.is_small());
+  assert_eq!(small, "A");
+
+  let mut heap = Yarn::from_string("a string that does not fit inline".into());
+  let ptr = heap.as_ptr();
+  heap.make_ascii_uppercase();
+  assert_eq!(heap.as_ptr(), ptr);
+  assert_eq!(heap, "A STRING THAT DOES NOT FIT INLINE");
+  heap.make_ascii_lowercase();
+  assert_eq!(heap.as_ptr(), ptr);
+  assert_eq!(heap, "a string that does not fit inline");
+
+  let mut lit = Yarn::from_static("a string that does not fit inline");
+  lit.make_ascii_uppercase();
+  assert!(lit.raw.on_heap());
+  assert_eq!(lit, "A STRING THAT DOES NOT FIT INLINE");
+
+  let upper = Yarn::from_static("short").to_ascii_uppercase();
+  assert!(upper.raw.is_small());
+  assert_eq!(upper, "SHORT");
+}
+
+#[test]
+fn map_bytes_in_place() {
+  use crate::ByteYarn;
+
+  let mut heap =
+    ByteYarn::from_vec(b"a byte string that does not fit inline".into());
+  let ptr = heap.as_ptr();
+  heap.map_bytes_mut(|b| b ^ 0xff);
+  assert_eq!(heap.as_ptr(), ptr);
+  assert_eq!(heap.map_bytes(|b| !b), b"a byte string that does not fit inline");
+
+  let lit = ByteYarn::from_static(b"\x00\x01\x02");
+  let mapped = lit.map_bytes(|b| b + 1);
+  assert!(mapped.raw.is_small());
+  assert_eq!(mapped, b"\x01\x02\x03");
+  assert_eq!(lit, b"\x00\x01\x02");
+}
+
+#[test]
+#[cfg(feature = "unicode-case")]
+fn unicode_case_mapping_spills() {
+  use crate::Yarn;
+
+  // "ΐ" is two bytes, but its uppercase form is six, which no longer fits.
+  let yarn = Yarn::new("abcdefghijkΐ");
+  assert!(yarn.raw.is_small());
+
+  let upper = yarn.to_uppercase();
+  assert!(upper.raw.on_heap());
+  assert_eq!(upper, "ABCDEFGHIJK\u{399}\u{308}\u{301}");
+
+  let lower = Yarn::new("ẞ").to_lowercase();
+  assert!(lower.raw.is_small());
+  assert_eq!(lower, "ß");
+}
+
+#[test]
+fn repeat_allocates_once() {
+  use crate::Yarn;
+
+  let inline = Yarn::new("abc").repeat(5);
+  assert!(inline.raw.is_small());
+  assert_eq!(inline, "abcabcabcabcabc");
+
+  let heap = Yarn::new("abc").repeat(7);
+  assert!(heap.raw.on_heap());
+  assert_eq!(heap.capacity(), heap.len());
+  assert_eq!(heap, "abc".repeat(7));
+
+  assert!(Yarn::new("").repeat(usize::MAX).is_empty());
+  let big = Yarn::from_static("a string that does not fit inline");
+  assert!(std::panic::catch_unwind(|| big.repeat(usize::MAX)).is_err());
+}
+
+#[test]
+fn shared_yarns_are_never_mutated() {
+  use crate::Yarn;
+  use std::sync::Arc;
+
+  let arc: Arc<str> = Arc::from("a string that does not fit inline");
+  let mut yarn = Yarn::from_arc(arc.clone());
+  assert!(yarn.raw.is_shared());
+  assert_eq!(yarn.capacity(), yarn.len());
+
+  let copy = yarn.clone();
+  assert_eq!(Arc::strong_count(&arc), 3);
+  assert_eq!(copy.as_ptr(), arc.as_ptr());
+
+  yarn.push_str("!");
+  assert!(yarn.raw.is_unique());
+  assert_eq!(Arc::strong_count(&arc), 2);
+  assert_eq!(yarn, "a string that does not fit inline!");
+
+  let mut upper = copy.clone();
+  upper.make_ascii_uppercase();
+  assert_eq!(upper, "A STRING THAT DOES NOT FIT INLINE");
+  assert_eq!(&*arc, "a string that does not fit inline");
+
+  assert_eq!(&*copy.clone().into_box(), "a string that does not fit inline");
+  drop(copy);
+  assert_eq!(Arc::strong_count(&arc), 1);
+
+  let small = Yarn::from_arc(Arc::from("small"));
+  assert!(small.raw.is_small());
+}
+
+#[test]
+fn over_aligned_data() {
+  #[derive(Copy, Clone, PartialEq, Debug, zerocopy::AsBytes)]
+  #[repr(C, align(32))]
+  struct Lane([u8; 32]);
+
+  let lanes = [Lane([1; 32]), Lane([2; 32])];
+  let is_aligned =
+    |y: &YarnBox<[Lane]>| (y.as_slice().as_ptr() as usize) % 32 == 0;
+
+  let empty = YarnBox::<[Lane]>::default();
+  assert!(empty.is_empty());
+  assert!(is_aligned(&empty));
+
+  let copy = YarnBox::<[Lane]>::copy(&lanes);
+  assert!(copy.raw.on_heap());
+  assert!(is_aligned(&copy));
+  assert_eq!(copy.as_slice(), &lanes);
+
+  // Even data that would fit inline needs to go on the heap.
+  let one = YarnBox::<[Lane]>::copy(&lanes[..1]).clone() + &lanes[1..];
+  assert!(is_aligned(&one));
+  assert_eq!(one.as_slice(), &lanes);
+
+  let boxed = YarnBox::<[Lane]>::from_box(Box::new(lanes));
+  assert!(is_aligned(&boxed));
+  assert_eq!(*boxed.into_box(), lanes);
+
+  let repeated = YarnBox::<[Lane]>::new(&lanes).repeat(3);
+  assert!(is_aligned(&repeated));
+  assert_eq!(repeated.len(), 6 * 32);
+}
+
+#[test]
+fn map_lookup_by_borrowed_form() {
+  use std::collections::HashMap;
+
+  let heap = "a string that is too long to inline";
+  let mut map = HashMap::new();
+  map.insert(crate::Yarn::from("small"), 1);
+  map.insert(crate::Yarn::from_static("static string"), 2);
+  map.insert(crate::Yarn::from(heap.to_string()), 3);
+  assert_eq!(map.get("small"), Some(&1));
+  assert_eq!(map.get("static string"), Some(&2));
+  assert_eq!(map.get(heap), Some(&3));
+  assert_eq!(map.get("missing"), None);
+
+  let mut map = HashMap::new();
+  map.insert(crate::ByteYarn::copy(b"\xff\xfe"), 1);
+  map.insert(crate::ByteYarn::from(heap.as_bytes().to_vec()), 2);
+  assert_eq!(map.get(b"\xff\xfe".as_slice()), Some(&1));
+  assert_eq!(map.get(heap.as_bytes()), Some(&2));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Deliberately leaks memory.
+fn into_leaked_reuses_heap_allocations() {
+  let long = "a string that is too long to inline";
+
+  let heap = crate::Yarn::from(long.to_string());
+  let ptr = heap.as_ptr();
+  let leaked = heap.into_leaked();
+  assert_eq!(leaked.as_ptr(), ptr);
+  assert_eq!(leaked, long);
+
+  let shared = crate::Yarn::from_arc(long.into());
+  let ptr = shared.as_ptr();
+  assert_eq!(shared.clone().into_leaked().as_ptr(), ptr);
+
+  let static_ = crate::Yarn::from_static(long);
+  assert_eq!(static_.into_leaked().as_ptr(), long.as_ptr());
+
+  let small = crate::Yarn::from("small");
+  assert!(small.raw.is_small());
+  assert_eq!(small.into_leaked(), "small");
+}
+
+#[test]
+fn into_vec_reuses_allocation() {
+  let long = "a string that is too long to inline";
+
+  let mut yarn = crate::Yarn::from(long);
+  yarn.push_str("!");
+  let ptr = yarn.as_ptr();
+  let cap = yarn.capacity();
+  let string = yarn.into_string();
+  assert_eq!(string, format!("{long}!"));
+  // The data moves to the front of the allocation, over the header.
+  assert_eq!(string.as_ptr(), ptr.wrapping_sub(mem::size_of::<usize>()));
+  assert_eq!(string.capacity(), cap + mem::size_of::<usize>());
+
+  let words = YarnBox::<[u64]>::from_vec(vec![1, 2, 3, 4]);
+  assert!(words.raw.on_heap());
+  let ptr = words.as_ptr();
+  let vec = words.into_vec();
+  assert_eq!(vec, [1, 2, 3, 4]);
+  assert_eq!(vec.as_ptr(), ptr.wrapping_sub(1));
+
+  let shared = crate::Yarn::from_arc(long.into());
+  let string = shared.clone().into_string();
+  assert_ne!(string.as_ptr(), shared.as_ptr());
+  assert_eq!(string, long);
+
+  assert_eq!(crate::Yarn::from("small").into_string(), "small");
+
+  // Yarns of zero-sized elements are always empty, even with spare capacity.
+  for cap in [8, 64] {
+    let units = YarnBox::<[()]>::with_capacity(cap);
+    assert_eq!(units.raw.on_heap(), cap > RawYarn::SSO_LEN);
+    assert_eq!(units.into_vec(), []);
+  }
+}
+
+#[test]
+fn truncate_keeps_allocation() {
+  let mut yarn = crate::Yarn::with_capacity(64);
+  yarn.push_str("a string that is too long to inline");
+  let ptr = yarn.as_ptr();
+  yarn.truncate(8);
+  assert_eq!(yarn, "a string");
+  assert!(yarn.raw.on_heap());
+  yarn.push_str(" that grows again");
+  assert_eq!(yarn.as_ptr(), ptr);
+
+  let text = "a static string that is too long to inline";
+  let mut static_ = crate::Yarn::from_static(text);
+  static_.truncate(20);
+  assert_eq!(static_, &text[..20]);
+  assert_eq!(static_.as_ptr(), text.as_ptr());
+
+  let mut shared = crate::Yarn::from_arc(text.into());
+  let copy = shared.clone();
+  shared.truncate(20);
+  assert_eq!(shared, &text[..20]);
+  assert_eq!(copy, text);
+
+  let mut small = crate::Yarn::from("small");
+  small.clear();
+  assert!(small.is_empty());
+  assert!(small.raw.is_small());
+}
+
+#[test]
+fn insert_and_remove_stay_inline() {
+  let mut yarn = crate::Yarn::from_static("jellys");
+  yarn.insert_str(5, "bean");
+  assert_eq!(yarn, "jellybeans");
+  assert!(yarn.raw.is_small());
+
+  let text = "a static string that is too long to inline";
+  let mut yarn = crate::Yarn::from_static(text);
+  yarn.insert(0, '>');
+  assert_eq!(yarn, format!(">{text}"));
+  assert!(yarn.raw.on_heap());
+  assert_eq!(yarn.remove(0), '>');
+  assert_eq!(yarn.remove(1), ' ');
+  assert_eq!(yarn, text.replacen(' ', "", 1));
+
+  let mut short = crate::Yarn::from_static(text);
+  short.truncate(10);
+  assert_eq!(short.remove(9), 's');
+  assert_eq!(short, "a static ");
+  assert!(short.raw.is_small());
+}
+
+#[test]
+fn retain_copies_only_when_needed() {
+  let text = "a static string that is too long to inline";
+  let mut yarn = crate::Yarn::from_static(text);
+  yarn.retain(|c| c != '_');
+  assert!(yarn.is_static());
+  assert_eq!(yarn.as_ptr(), text.as_ptr());
+
+  yarn.retain(|c| !c.is_whitespace());
+  assert_eq!(yarn, "astaticstringthatistoolongtoinline");
+  assert!(yarn.raw.on_heap());
+
+  let mut yarn = crate::Yarn::from_static("ñ🐈‍⬛ and a few more words é");
+  yarn.retain(|c| !c.is_ascii());
+  assert_eq!(yarn, "ñ🐈\u{200d}⬛é");
+  assert!(yarn.raw.is_small());
+
+  let mut calls = 0;
+  let mut yarn =
+    crate::ByteYarn::from_static(b"\xffbytes that are too long to inline");
+  yarn.retain(|b| {
+    calls += 1;
+    b != b' '
+  });
+  assert_eq!(yarn, b"\xffbytesthataretoolongtoinline");
+  assert_eq!(calls, 34);
+}
+
+#[test]
+fn retain_panic_leaves_valid_yarn() {
+  let mut yarn =
+    crate::Yarn::from_static("é a string with é too long to inline");
+  let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+    yarn.retain(|c| {
+      assert_ne!(c, 'w');
+      c != ' '
+    })
+  }));
+  assert!(result.is_err());
+  assert_eq!(yarn, "éastring");
+}
+
+#[test]
+fn remove_matches_copies_only_when_needed() {
+  let text = "a static string that is too long to inline";
+  let mut yarn = crate::Yarn::from_static(text);
+  yarn.remove_matches("xyz");
+  yarn.remove_matches("");
+  assert!(yarn.is_static());
+  assert_eq!(yarn.as_ptr(), text.as_ptr());
+
+  yarn.remove_matches(text);
+  assert_eq!(yarn, "");
+
+  // Overlapping candidates are matched from the left, and removing a match
+  // does not create a new one.
+  let mut yarn = crate::Yarn::from_static("aaaaa ababab abaaba  and more text");
+  yarn.remove_matches("aa");
+  assert_eq!(yarn, "a ababab abba  and more text");
+  assert!(yarn.raw.on_heap());
+  yarn.remove_matches("aba");
+  assert_eq!(yarn, "a bab abba  and more text");
+  yarn.remove_matches(' ');
+  assert_eq!(yarn, "abababbaandmoretext");
+  yarn.remove_matches(|c| c != 'a');
+  assert_eq!(yarn, "aaaaa");
+  assert!(yarn.raw.is_small());
+}
+
+#[test]
+fn reverse_keeps_multibyte_chars() {
+  let yarn = crate::Yarn::from_static("ñ🐈‍⬛é");
+  let reversed = yarn.reverse_chars();
+  assert_eq!(reversed, "é⬛\u{200d}🐈ñ");
+  assert!(reversed.raw.is_small());
+  assert_eq!(reversed.reverse_chars(), yarn);
+
+  let long =
+    crate::Yarn::from_static("a string that is too long to inline: ÿ€𝄞");
+  let reversed = long.reverse_chars();
+  assert_eq!(reversed, long.chars().rev().collect::<String>());
+  assert!(reversed.raw.on_heap());
+
+  // Reversing bytes, on the other hand, scrambles multibyte characters.
+  let bytes = long.clone().into_bytes().reverse_bytes();
+  let mut expected = long.as_bytes().to_vec();
+  expected.reverse();
+  assert_eq!(bytes, expected);
+  assert!(str::from_utf8(bytes.as_slice()).is_err());
+  assert_eq!(bytes.reverse_bytes(), long.as_bytes());
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn custom_allocator_is_balanced() {
+  use std::alloc::AllocError;
+  use std::alloc::Allocator;
+  use std::cell::Cell;
+  use std::cell::RefCell;
+
+  #[derive(Copy, Clone)]
+  struct Counting<'a> {
+    live: &'a RefCell<Vec<(NonNull<u8>, Layout)>>,
+    total: &'a Cell<usize>,
+  }
+
+  unsafe impl Allocator for Counting<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+      let ptr = Global.allocate(layout)?;
+      self.live.borrow_mut().push((ptr.cast(), layout));
+      self.total.set(self.total.get() + 1);
+      Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+      // Catch buffers that some other allocator allocated or grew.
+      let mut live = self.live.borrow_mut();
+      let idx = live.iter().position(|&p| p == (ptr, layout));
+      live.swap_remove(idx.expect("freed a buffer this allocator did not own"));
+      Global.deallocate(ptr, layout)
+    }
+  }
+
+  let (live, total) = (RefCell::new(Vec::new()), Cell::new(0));
+  let alloc = Counting { live: &live, total: &total };
+  {
+    let small = YarnBox::<str, _>::copy_in("small", alloc);
+    assert_eq!(total.get(), 0);
+
+    let mut big =
+      YarnBox::<str, _>::copy_in("a string that is too long to inline", alloc);
+    assert_eq!(live.borrow().len(), 1);
+
+    let copy = big.clone();
+    assert_eq!(live.borrow().len(), 2);
+
+    big.append_in(", and now it has to grow");
+    assert_eq!(
+      &*big,
+      "a string that is too long to inline, and now it has to grow"
+    );
+    assert_eq!(&*copy, "a string that is too long to inline");
+
+    let empty = YarnBox::<[u8], _>::new_in(alloc);
+    let mut spare = YarnBox::<[u8], _>::with_capacity_in(64, alloc);
+    spare.append_in(&[1, 2, 3]);
+    drop((small, empty, spare.clone()));
+  }
+  assert_eq!(live.borrow().len(), 0);
+  assert!(total.get() > 3);
+
+  // Over-aligned data never inlines, so even short appends allocate.
+  #[derive(Copy, Clone, PartialEq, Debug, zerocopy::AsBytes)]
+  #[repr(C, align(16))]
+  struct Quad([u8; 16]);
+
+  {
+    let mut quads = YarnBox::<[Quad], _>::new_in(alloc);
+    quads.append_in(&[Quad([1; 16])]);
+    assert_eq!(live.borrow().len(), 1);
+    assert_eq!(quads.as_ptr() as usize % 16, 0);
+
+    let copy = quads.clone();
+    quads.append_in(&[Quad([2; 16]); 3]);
+    assert_eq!(live.borrow().len(), 2);
+
+    quads.truncate_in(1);
+    quads.shrink_to_fit_in();
+    assert_eq!(&*quads, &*copy);
+    assert_eq!(live.borrow().len(), 2);
+
+    let mut text = YarnBox::<str, _>::with_capacity_in(64, alloc);
+    text.append_in("a string that is too long to inline");
+    text.truncate_in(8);
+    text.shrink_to_fit_in();
+    assert_eq!(&*text, "a string");
+    assert_eq!(live.borrow().len(), 2);
+  }
+  assert_eq!(live.borrow().len(), 0);
+
+  // The global allocator does not take up any space.
+  assert_eq!(mem::size_of::<Option<crate::Yarn>>(), mem::size_of::<RawYarn>());
+}
+
+#[test]
+fn shrink_to_fit_reinlines() {
+  let mut yarn = crate::Yarn::with_capacity(64);
+  yarn.push_str("a string that is too long to inline");
+  yarn.shrink_to_fit();
+  assert!(yarn.raw.on_heap());
+  assert_eq!(yarn.capacity(), yarn.len());
+  assert_eq!(yarn, "a string that is too long to inline");
+
+  yarn.truncate(8);
+  assert!(yarn.raw.on_heap());
+  yarn.shrink_to_fit();
+  assert!(yarn.raw.is_small());
+  assert_eq!(yarn, "a string");
+
+  let mut shared =
+    crate::Yarn::from_arc("a shared string that is not inlined".into());
+  let ptr = shared.as_ptr();
+  shared.shrink_to_fit();
+  assert_eq!(shared.as_ptr(), ptr);
+}
+
+#[test]
+fn bytes_of_inline_and_heap() {
+  use crate::ByteYarn;
+
+  let small = ByteYarn::new(b"\x00\xff");
+  let big = ByteYarn::from(vec![0xa5u8; RawYarn::SSO_LEN * 2]);
+  assert!(small.is_inlined());
+  assert!(big.is_heap());
+
+  for yarn in [small, big] {
+    let bytes = yarn.as_slice().to_vec();
+    assert_eq!(yarn.bytes().collect::<Vec<_>>(), bytes);
+    assert_eq!((&yarn).into_iter().rev().collect::<Vec<_>>(), {
+      let mut rev = bytes.clone();
+      rev.reverse();
+      rev
+    });
+
+    let r = yarn.as_ref();
+    assert_eq!(r.iter().collect::<Vec<_>>(), bytes);
+    assert_eq!((&r).into_iter().len(), bytes.len());
+  }
+}
+
+#[test]
+fn cow_round_trip() {
+  use crate::Yarn;
+
+  let owned = Yarn::from(String::from("an owned string on the heap"));
+  let Cow::Owned(s) = owned.into_cow() else {
+    panic!("expected an owned string");
+  };
+  let yarn = Yarn::from(Cow::Owned(s));
+  assert!(yarn.raw.on_heap());
+  assert_eq!(yarn, "an owned string on the heap");
+
+  let text = String::from("an aliased string");
+  let Cow::Borrowed(s) = YarnBox::<str>::new(&text).into_cow() else {
+    panic!("expected a borrowed string");
+  };
+  assert_eq!(s.as_ptr(), text.as_ptr());
+
+  let small = Yarn::new("tiny");
+  assert!(small.raw.is_small());
+  assert!(matches!(small.into_cow(), Cow::Owned(s) if s == "tiny"));
+
+  let yarn = Yarn::from(Cow::Borrowed("tiny"));
+  assert_eq!(yarn.to_ref().unwrap().immortalize().unwrap(), "tiny");
+}
+
+#[test]
+fn padding_stays_inline() {
+  use crate::Yarn;
+
+  let yarn = Yarn::new("abc");
+  for width in 0..20 {
+    let padded = yarn.center(width, '-');
+    assert_eq!(padded, format!("{:-^width$}", "abc"));
+    assert_eq!(padded.raw.is_small(), padded.len() <= RawYarn::SSO_LEN);
+    assert_eq!(yarn.pad_start(width, ' '), format!("{:>width$}", "abc"));
+    assert_eq!(yarn.pad_end(width, 'é'), format!("{:é<width$}", "abc"));
+  }
+
+  let wide = "a string wider than the padding";
+  let padded = Yarn::from_static(wide).pad_start(10, ' ');
+  assert_eq!(padded.as_ptr(), wide.as_ptr());
+  assert_eq!(YarnRef::new(wide).center(10, ' ').as_ptr(), wide.as_ptr());
+}
@@ -0,0 +1,106 @@
+//! Support for the `byarn!()` macro.
+
+use core::alloc::Layout;
+use core::slice;
+
+use crate::raw::RawYarn;
+use crate::ByteYarn;
+
+#[cfg(doc)]
+use crate::*;
+
+/// Checks that `fmt` is a valid format string for [`byarn!()`] with `args`
+/// arguments.
+///
+/// This is intended to be called in a `const` context, so that errors are
+/// reported at compile time.
+pub const fn check(fmt: &[u8], args: usize) {
+  let mut holes = 0;
+  let mut i = 0;
+  while i < fmt.len() {
+    let next = if i + 1 < fmt.len() { fmt[i + 1] } else { 0 };
+    match (fmt[i], next) {
+      (b'{', b'{') | (b'}', b'}') => i += 2,
+      (b'{', b'}') => {
+        holes += 1;
+        i += 2;
+      }
+      (b'{', _) => panic!("byarn!: only `{{}}` placeholders are supported"),
+      (b'}', _) => panic!("byarn!: unmatched `}}` in format string"),
+      _ => i += 1,
+    }
+  }
+
+  if holes != args {
+    panic!("byarn!: wrong number of arguments for format string");
+  }
+}
+
+/// Builds a yarn by substituting `args` into `fmt`.
+///
+/// `fmt` must have been validated with [`check()`].
+pub fn format(fmt: &'static [u8], args: &[&[u8]]) -> ByteYarn {
+  if args.is_empty() && !fmt.iter().any(|b| matches!(b, b'{' | b'}')) {
+    return ByteYarn::from_static(fmt);
+  }
+
+  let pieces = Pieces { fmt, args: args.iter() };
+  let len = pieces.clone().map(<[u8]>::len).sum();
+
+  unsafe {
+    // SAFETY: `pieces` yields exactly `len` bytes, and any bytes are a valid
+    // [u8].
+    ByteYarn::from_raw(RawYarn::concat(
+      Layout::from_size_align_unchecked(len, 1),
+      pieces,
+    ))
+  }
+}
+
+/// An iterator over the pieces of a formatted byte string.
+#[derive(Clone)]
+struct Pieces<'a> {
+  fmt: &'a [u8],
+  args: slice::Iter<'a, &'a [u8]>,
+}
+
+impl<'a> Iterator for Pieces<'a> {
+  type Item = &'a [u8];
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.fmt {
+      [] => None,
+      [b'{', b'}', rest @ ..] => {
+        self.fmt = rest;
+        Some(self.args.next().copied().unwrap_or_default())
+      }
+      [b'{', b'{', rest @ ..] | [b'}', b'}', rest @ ..] => {
+        let brace = &self.fmt[..1];
+        self.fmt = rest;
+        Some(brace)
+      }
+      _ => {
+        let len = self.fmt[1..]
+          .iter()
+          .position(|b| matches!(b, b'{' | b'}'))
+          .map_or(self.fmt.len(), |n| n + 1);
+        let (literal, rest) = self.fmt.split_at(len);
+        self.fmt = rest;
+        Some(literal)
+      }
+    }
+  }
+}
+
+#[test]
+fn byarn_stays_inline() {
+  let small = crate::byarn!(b"<{}:{}>", b"ab", [0xffu8]);
+  assert_eq!(small, b"<ab:\xff>");
+  assert!(small.into_raw().is_small());
+
+  let empty = crate::byarn!(b"{}", "");
+  assert!(empty.is_empty());
+
+  let big = crate::byarn!(b"{}{{{}}}", [0u8; 32], "x");
+  assert_eq!(big.len(), 35);
+}
@@ -0,0 +1,143 @@
+//! ASCII case-insensitive comparisons.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::ops::Deref;
+
+#[cfg(doc)]
+use crate::*;
+
+/// Compares two byte strings lexicographically, as if both had been converted
+/// to ASCII lowercase.
+pub(crate) fn cmp(a: &[u8], b: &[u8]) -> Ordering {
+  let a = a.iter().map(u8::to_ascii_lowercase);
+  let b = b.iter().map(u8::to_ascii_lowercase);
+  a.cmp(b)
+}
+
+/// A wrapper that compares, orders, and hashes a string while ignoring ASCII
+/// case.
+///
+/// This wraps anything that dereferences to a `str` or `[u8]`, such as a
+/// [`Yarn`], and is useful as the key of a map.
+///
+/// ```
+/// # use byteyarn::*;
+/// use std::collections::HashMap;
+///
+/// let mut headers = HashMap::new();
+/// headers.insert(AsciiCaseless(Yarn::new("Content-Type")), "text/plain");
+///
+/// let key = AsciiCaseless(Yarn::new("content-type"));
+/// assert_eq!(headers.get(&key), Some(&"text/plain"));
+/// assert!(AsciiCaseless("ABC") < AsciiCaseless("abd"));
+/// ```
+#[derive(Copy, Clone, Default)]
+pub struct AsciiCaseless<Y>(pub Y);
+
+impl<Y> AsciiCaseless<Y>
+where
+  Y: Deref,
+  Y::Target: AsRef<[u8]>,
+{
+  fn bytes(&self) -> &[u8] {
+    (*self.0).as_ref()
+  }
+}
+
+impl<Y> PartialEq for AsciiCaseless<Y>
+where
+  Y: Deref,
+  Y::Target: AsRef<[u8]>,
+{
+  fn eq(&self, that: &Self) -> bool {
+    self.bytes().eq_ignore_ascii_case(that.bytes())
+  }
+}
+
+impl<Y> Eq for AsciiCaseless<Y>
+where
+  Y: Deref,
+  Y::Target: AsRef<[u8]>,
+{
+}
+
+impl<Y> PartialOrd for AsciiCaseless<Y>
+where
+  Y: Deref,
+  Y::Target: AsRef<[u8]>,
+{
+  fn partial_cmp(&self, that: &Self) -> Option<Ordering> {
+    Some(self.cmp(that))
+  }
+}
+
+impl<Y> Ord for AsciiCaseless<Y>
+where
+  Y: Deref,
+  Y::Target: AsRef<[u8]>,
+{
+  fn cmp(&self, that: &Self) -> Ordering {
+    cmp(self.bytes(), that.bytes())
+  }
+}
+
+impl<Y> Hash for AsciiCaseless<Y>
+where
+  Y: Deref,
+  Y::Target: AsRef<[u8]>,
+{
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    for b in self.bytes() {
+      state.write_u8(b.to_ascii_lowercase());
+    }
+    // Like str, terminate with a byte that cannot appear in UTF-8, so that
+    // hashing a sequence of these is prefix-free.
+    state.write_u8(0xff);
+  }
+}
+
+impl<Y: fmt::Debug> fmt::Debug for AsciiCaseless<Y> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.0.fmt(f)
+  }
+}
+
+impl<Y: fmt::Display> fmt::Display for AsciiCaseless<Y> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.0.fmt(f)
+  }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn caseless_keys() {
+  use std::collections::BTreeSet;
+  use std::collections::HashSet;
+
+  use crate::ByteYarn;
+  use crate::Yarn;
+
+  let words = ["Alpha", "ALPHA", "beta", "BETA", "Gamma", "alphabet"];
+  let hashed = words
+    .iter()
+    .map(|&w| AsciiCaseless(Yarn::new(w)))
+    .collect::<HashSet<_>>();
+  assert_eq!(hashed.len(), 4);
+  assert!(hashed.contains(&AsciiCaseless(Yarn::new("gAMMA"))));
+
+  let sorted = words
+    .iter()
+    .map(|&w| AsciiCaseless(Yarn::new(w)))
+    .collect::<BTreeSet<_>>();
+  let sorted = sorted
+    .iter()
+    .map(|k| k.0.to_ascii_lowercase())
+    .collect::<Vec<_>>();
+  assert_eq!(sorted, ["alpha", "alphabet", "beta", "gamma"]);
+
+  let bytes = AsciiCaseless(ByteYarn::new(b"\xffQ"));
+  assert_eq!(bytes, AsciiCaseless(ByteYarn::new(b"\xffq")));
+}
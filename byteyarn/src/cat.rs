@@ -0,0 +1,61 @@
+//! Support for the `cat!()` macro.
+
+use core::mem;
+
+use crate::YarnBox;
+use crate::YarnRef;
+
+#[cfg(doc)]
+use crate::*;
+
+/// A piece of a compile-time concatenation.
+///
+/// This has a `size()` and a `write()` function for each type that can be
+/// concatenated in a `const` context. Both consume the piece without running
+/// its destructor, since destructors cannot run at compile time.
+pub struct Piece<T>(pub T);
+
+macro_rules! pieces {
+  ($($ty:ty => |$x:ident| $bytes:expr;)*) => {$(
+    impl Piece<$ty> {
+      /// Returns the length of this piece, in bytes.
+      #[allow(clippy::forget_non_drop)]
+      pub const fn size(self) -> usize {
+        let $x = &self.0;
+        let len = $bytes.len();
+        mem::forget(self);
+        len
+      }
+
+      /// Copies this piece into `buf` at `at`, returning the offset just past
+      /// it.
+      #[allow(clippy::forget_non_drop)]
+      pub const fn write(self, buf: &mut [u8], mut at: usize) -> usize {
+        let $x = &self.0;
+        let bytes = $bytes;
+        let mut i = 0;
+        while i < bytes.len() {
+          buf[at] = bytes[i];
+          at += 1;
+          i += 1;
+        }
+        mem::forget(self);
+        at
+      }
+    }
+  )*};
+}
+
+pieces! {
+  &str => |x| x.as_bytes();
+  YarnBox<'_, str> => |x| x.as_bytes();
+  YarnRef<'_, str> => |x| x.as_bytes();
+}
+
+/// Converts the result of a compile-time concatenation back into a string.
+pub const fn to_str(bytes: &'static [u8]) -> &'static str {
+  match core::str::from_utf8(bytes) {
+    Ok(s) => s,
+    Err(_) => panic!("cat!: concatenation produced invalid UTF-8"),
+  }
+}
@@ -1,10 +1,20 @@
-use std::borrow::Borrow;
-use std::fmt;
-use std::str::Utf8Error;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::str::Utf8Error;
 
 use crate::YarnBox;
 use crate::YarnRef;
 
+#[cfg(feature = "std")]
+use std::ffi::OsStr;
+#[cfg(feature = "std")]
+use std::path::Path;
+
 #[derive(Clone, Debug)]
 pub struct NonCopy(());
 
@@ -14,6 +24,38 @@ impl fmt::Display for NonCopy {
   }
 }
 
+/// An error returned by [`ByteYarn::parse()`].
+///
+/// [`ByteYarn::parse()`]: crate::ByteYarn::parse
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError<E> {
+  /// The yarn was not valid UTF-8.
+  Utf8(Utf8Error),
+  /// The yarn was valid UTF-8, but [`FromStr`] rejected it.
+  ///
+  /// [`FromStr`]: core::str::FromStr
+  Parse(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ParseError<E> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Utf8(e) => e.fmt(f),
+      Self::Parse(e) => e.fmt(f),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for ParseError<E> {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Utf8(e) => Some(e),
+      Self::Parse(e) => Some(e),
+    }
+  }
+}
+
 impl<'a, Buf> TryFrom<YarnBox<'a, Buf>> for YarnRef<'a, Buf>
 where
   Buf: crate::Buf + ?Sized,
@@ -152,6 +194,18 @@ impl From<Box<str>> for YarnBox<'_, [u8]> {
   }
 }
 
+impl From<Arc<str>> for YarnBox<'_, str> {
+  fn from(s: Arc<str>) -> Self {
+    Self::from_arc(s)
+  }
+}
+
+impl From<Arc<[u8]>> for YarnBox<'_, [u8]> {
+  fn from(s: Arc<[u8]>) -> Self {
+    Self::from_arc(s)
+  }
+}
+
 impl From<String> for YarnBox<'_, str> {
   fn from(s: String) -> Self {
     Self::from_string(s)
@@ -164,6 +218,58 @@ impl From<String> for YarnBox<'_, [u8]> {
   }
 }
 
+/// Borrowed strings become static yarns, so they can be
+/// [immortalized](YarnBox::immortalize) without copying.
+///
+/// ```
+/// # use byteyarn::*;
+/// # use std::borrow::Cow;
+/// let yarn = Yarn::from(Cow::Borrowed("a static string"));
+/// assert!(yarn.is_static());
+///
+/// let yarn = Yarn::from(Cow::<str>::Owned("a longer owned string".into()));
+/// assert!(yarn.is_heap());
+/// ```
+impl From<Cow<'static, str>> for YarnBox<'_, str> {
+  fn from(s: Cow<'static, str>) -> Self {
+    match s {
+      Cow::Borrowed(s) => YarnBox::from_static(s),
+      Cow::Owned(s) => Self::from_string(s),
+    }
+  }
+}
+
+/// Borrowed slices become static yarns, so they can be
+/// [immortalized](YarnBox::immortalize) without copying.
+impl<T> From<Cow<'static, [T]>> for YarnBox<'_, [T]>
+where
+  [T]: crate::Buf,
+  T: Clone,
+{
+  fn from(s: Cow<'static, [T]>) -> Self {
+    match s {
+      Cow::Borrowed(s) => YarnBox::from_static(s),
+      Cow::Owned(s) => Self::from_vec(s),
+    }
+  }
+}
+
+impl<'a> From<YarnBox<'a, str>> for Cow<'a, str> {
+  fn from(s: YarnBox<'a, str>) -> Self {
+    s.into_cow()
+  }
+}
+
+impl<'a, T> From<YarnBox<'a, [T]>> for Cow<'a, [T]>
+where
+  [T]: crate::Buf,
+  T: Clone,
+{
+  fn from(s: YarnBox<'a, [T]>) -> Self {
+    s.into_cow()
+  }
+}
+
 impl<Buf> From<YarnBox<'_, Buf>> for Box<[u8]>
 where
   Buf: crate::Buf + ?Sized,
@@ -261,3 +367,70 @@ where
     self.as_slice()
   }
 }
+
+#[cfg(feature = "std")]
+impl AsRef<Path> for YarnBox<'_, str> {
+  fn as_ref(&self) -> &Path {
+    Path::new(self.as_str())
+  }
+}
+
+#[cfg(feature = "std")]
+impl AsRef<Path> for YarnRef<'_, str> {
+  fn as_ref(&self) -> &Path {
+    Path::new(self.as_str())
+  }
+}
+
+#[cfg(feature = "std")]
+impl AsRef<OsStr> for YarnBox<'_, str> {
+  fn as_ref(&self) -> &OsStr {
+    OsStr::new(self.as_str())
+  }
+}
+
+#[cfg(feature = "std")]
+impl AsRef<OsStr> for YarnRef<'_, str> {
+  fn as_ref(&self) -> &OsStr {
+    OsStr::new(self.as_str())
+  }
+}
+
+// NOTE: Yarns already implement PartialEq for anything that is AsRef<str>, and
+// Path could someday be one of those, so paths can only be compared with
+// yarns from the left.
+macro_rules! path_eq {
+  ($($path:ty),*) => {$(
+    #[cfg(feature = "std")]
+    impl PartialEq<YarnBox<'_, str>> for $path {
+      fn eq(&self, that: &YarnBox<str>) -> bool {
+        AsRef::<Path>::as_ref(self) == Path::new(that.as_str())
+      }
+    }
+
+    #[cfg(feature = "std")]
+    impl PartialEq<YarnRef<'_, str>> for $path {
+      fn eq(&self, that: &YarnRef<str>) -> bool {
+        AsRef::<Path>::as_ref(self) == Path::new(that.as_str())
+      }
+    }
+  )*};
+}
+
+path_eq!(Path, &Path);
+
+#[cfg(feature = "std")]
+#[test]
+fn paths() {
+  use crate::Yarn;
+
+  let yarn = Yarn::from("src/lib.rs".to_string());
+  assert_eq!(Path::new(&yarn).file_name(), Some(OsStr::new("lib.rs")));
+  assert_eq!(Path::new("src/lib.rs"), yarn);
+  assert_eq!(Path::new("src/./lib.rs"), yarn.as_ref());
+  assert_ne!(Path::new("src"), yarn);
+
+  let dir: &Path = Path::new("src");
+  assert_eq!(dir, Yarn::new("src/"));
+  assert_eq!(<Yarn as AsRef<OsStr>>::as_ref(&yarn), "src/lib.rs");
+}
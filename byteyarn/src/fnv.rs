@@ -0,0 +1,35 @@
+//! A stable content hash.
+
+/// Hashes `bytes` with 64-bit FNV-1a.
+///
+/// The result depends only on the bytes themselves, so it is the same on
+/// every platform, and across versions of this crate.
+pub const fn hash(bytes: &[u8]) -> u64 {
+  const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+  const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+  let mut hash = OFFSET_BASIS;
+  let mut i = 0;
+  while i < bytes.len() {
+    hash ^= bytes[i] as u64;
+    hash = hash.wrapping_mul(PRIME);
+    i += 1;
+  }
+  hash
+}
+
+#[test]
+fn known_hashes() {
+  use crate::ByteYarn;
+  use crate::Yarn;
+
+  assert_eq!(Yarn::new("").stable_hash(), 0xcbf29ce484222325);
+  assert_eq!(Yarn::new("a").stable_hash(), 0xaf63dc4c8601ec8c);
+  assert_eq!(Yarn::new("foobar").stable_hash(), 0x85944171f73967e8);
+
+  let big = "a string long enough to live on the heap".to_string();
+  let yarn = Yarn::from(big.clone());
+  assert_eq!(yarn.stable_hash(), hash(big.as_bytes()));
+  assert_eq!(yarn.as_ref().stable_hash(), yarn.stable_hash());
+  assert_eq!(ByteYarn::from(yarn).stable_hash(), hash(big.as_bytes()));
+}
@@ -0,0 +1,81 @@
+//! Hexadecimal encoding.
+
+use core::fmt;
+
+use crate::ByteYarn;
+use crate::Yarn;
+
+/// An error returned by [`ByteYarn::from_hex()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HexError {
+  /// The input had an odd number of digits.
+  OddLength,
+  /// The input contained a character that is not a hex digit, at the given
+  /// byte offset.
+  InvalidDigit(usize),
+}
+
+impl fmt::Display for HexError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::OddLength => f.write_str("odd number of hex digits"),
+      Self::InvalidDigit(idx) => write!(f, "invalid hex digit at offset {idx}"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
+
+/// Encodes `bytes` as pairs of hex digits.
+pub(crate) fn encode(bytes: &[u8], upper: bool) -> Yarn {
+  let digits = if upper { b"0123456789ABCDEF" } else { b"0123456789abcdef" };
+
+  let mut yarn = Yarn::with_capacity(bytes.len() * 2);
+  for &b in bytes {
+    yarn.push(digits[(b >> 4) as usize] as char);
+    yarn.push(digits[(b & 0xf) as usize] as char);
+  }
+  yarn
+}
+
+/// Decodes pairs of hex digits of either case.
+pub(crate) fn decode(hex: &str) -> Result<ByteYarn, HexError> {
+  let hex = hex.as_bytes();
+  if hex.len() % 2 != 0 {
+    return Err(HexError::OddLength);
+  }
+
+  let digit = |idx: usize| {
+    let d = match hex[idx] {
+      b @ b'0'..=b'9' => b - b'0',
+      b @ b'a'..=b'f' => b - b'a' + 10,
+      b @ b'A'..=b'F' => b - b'A' + 10,
+      _ => return Err(HexError::InvalidDigit(idx)),
+    };
+    Ok(d)
+  };
+
+  let mut yarn = ByteYarn::with_capacity(hex.len() / 2);
+  for i in (0..hex.len()).step_by(2) {
+    yarn.extend([digit(i)? << 4 | digit(i + 1)?]);
+  }
+  Ok(yarn)
+}
+
+#[test]
+fn hex_round_trip() {
+  let small = ByteYarn::new(b"\x00\x7f\xff");
+  let hex = small.to_hex();
+  assert_eq!(hex, "007fff");
+  assert!(hex.is_inlined());
+  assert_eq!(ByteYarn::from_hex(&hex).unwrap(), small);
+  assert!(ByteYarn::from_hex(&hex).unwrap().is_inlined());
+
+  let big = (0..=255).collect::<ByteYarn>();
+  assert_eq!(ByteYarn::from_hex(&big.to_hex_upper()).unwrap(), big);
+
+  assert_eq!(ByteYarn::from_hex("abc"), Err(HexError::OddLength));
+  assert_eq!(ByteYarn::from_hex("0g"), Err(HexError::InvalidDigit(1)));
+  assert_eq!(ByteYarn::from_hex("é"), Err(HexError::InvalidDigit(0)));
+}
@@ -0,0 +1,415 @@
+//! Iterators that yield sub-yarns.
+
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::Pattern;
+use crate::YarnBox;
+use crate::YarnRef;
+
+#[cfg(doc)]
+use crate::*;
+
+/// An iterator over the pieces of a yarn, separated by a [`Pattern`].
+///
+/// See [`Yarn::split()`] and [`Yarn::splitn()`].
+#[derive(Clone)]
+pub struct Split<'a, P> {
+  yarn: YarnRef<'a, str>,
+  pat: P,
+  // The start of the next piece.
+  start: usize,
+  // Where to start searching for the next match, which may be past `start`
+  // after an empty match.
+  search: usize,
+  // The maximum number of pieces left to yield.
+  limit: usize,
+}
+
+impl<'a, P: Pattern> Split<'a, P> {
+  pub(crate) fn new(yarn: YarnRef<'a, str>, pat: P, limit: usize) -> Self {
+    Self { yarn, pat, start: 0, search: 0, limit }
+  }
+}
+
+impl<'a, P: Pattern> Iterator for Split<'a, P> {
+  type Item = YarnRef<'a, str>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.limit == 0 {
+      return None;
+    }
+    self.limit -= 1;
+
+    let text = self.yarn.as_str();
+    let found = match self.limit > 0 && self.search <= text.len() {
+      true => self.pat.find_in(&text[self.search..]),
+      false => None,
+    };
+
+    let Some(found) = found else {
+      // This is the last piece.
+      self.limit = 0;
+      return Some(self.yarn.subyarn(&text[self.start..]));
+    };
+
+    let (start, end) = (self.search + found.start, self.search + found.end);
+    let piece = &text[self.start..start];
+
+    self.start = end;
+    self.search = end;
+    if start == end {
+      // Skip over the next character, so that we don't produce the same empty
+      // match again.
+      self.search += text[end..].chars().next().map_or(1, char::len_utf8);
+    }
+
+    Some(self.yarn.subyarn(piece))
+  }
+}
+
+/// An iterator over the matches of a [`Pattern`] in a yarn, and their offsets.
+///
+/// See [`Yarn::match_indices()`].
+#[derive(Clone)]
+pub struct MatchIndices<'a, P> {
+  yarn: YarnRef<'a, str>,
+  pat: P,
+  // Where to start searching for the next match, which is one character past
+  // the end of the previous match if it was empty.
+  search: usize,
+}
+
+impl<'a, P: Pattern> MatchIndices<'a, P> {
+  pub(crate) fn new(yarn: YarnRef<'a, str>, pat: P) -> Self {
+    Self { yarn, pat, search: 0 }
+  }
+}
+
+impl<'a, P: Pattern> Iterator for MatchIndices<'a, P> {
+  type Item = (usize, YarnRef<'a, str>);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let text = self.yarn.as_str();
+    let found = match self.search <= text.len() {
+      true => self.pat.find_in(&text[self.search..]),
+      false => None,
+    };
+
+    let Some(found) = found else {
+      self.search = text.len() + 1;
+      return None;
+    };
+
+    let (start, end) = (self.search + found.start, self.search + found.end);
+    self.search = end;
+    if start == end {
+      // Skip over the next character, so that we don't produce the same empty
+      // match again.
+      self.search += text[end..].chars().next().map_or(1, char::len_utf8);
+    }
+
+    Some((start, self.yarn.subyarn(&text[start..end])))
+  }
+}
+
+/// An iterator over the matches of a [`Pattern`] in a yarn.
+///
+/// See [`Yarn::matches()`].
+#[derive(Clone)]
+pub struct Matches<'a, P>(MatchIndices<'a, P>);
+
+impl<'a, P: Pattern> Matches<'a, P> {
+  pub(crate) fn new(yarn: YarnRef<'a, str>, pat: P) -> Self {
+    Self(MatchIndices::new(yarn, pat))
+  }
+}
+
+impl<'a, P: Pattern> Iterator for Matches<'a, P> {
+  type Item = YarnRef<'a, str>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.0.next().map(|(_, m)| m)
+  }
+}
+
+/// An iterator over the extended grapheme clusters of a yarn, and their
+/// offsets.
+///
+/// See [`Yarn::grapheme_indices()`].
+#[cfg(feature = "unicode")]
+#[derive(Clone)]
+pub struct GraphemeIndices<'a> {
+  yarn: YarnRef<'a, str>,
+  // The start of the next grapheme cluster.
+  start: usize,
+}
+
+#[cfg(feature = "unicode")]
+impl<'a> GraphemeIndices<'a> {
+  pub(crate) fn new(yarn: YarnRef<'a, str>) -> Self {
+    Self { yarn, start: 0 }
+  }
+}
+
+#[cfg(feature = "unicode")]
+impl<'a> Iterator for GraphemeIndices<'a> {
+  type Item = (usize, YarnRef<'a, str>);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let text = self.yarn.as_str();
+    let rest = &text[self.start..];
+    let grapheme = rest.graphemes(true).next()?;
+
+    let start = self.start;
+    self.start += grapheme.len();
+    Some((start, self.yarn.subyarn(grapheme)))
+  }
+}
+
+/// An iterator over the extended grapheme clusters of a yarn.
+///
+/// See [`Yarn::graphemes()`].
+#[cfg(feature = "unicode")]
+#[derive(Clone)]
+pub struct Graphemes<'a>(GraphemeIndices<'a>);
+
+#[cfg(feature = "unicode")]
+impl<'a> Graphemes<'a> {
+  pub(crate) fn new(yarn: YarnRef<'a, str>) -> Self {
+    Self(GraphemeIndices::new(yarn))
+  }
+}
+
+#[cfg(feature = "unicode")]
+impl<'a> Iterator for Graphemes<'a> {
+  type Item = YarnRef<'a, str>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.0.next().map(|(_, g)| g)
+  }
+}
+
+/// An iterator over the lines of a yarn.
+///
+/// See [`Yarn::lines()`].
+#[derive(Clone)]
+pub struct Lines<'a> {
+  yarn: YarnRef<'a, str>,
+  // The start of the next line.
+  start: usize,
+}
+
+impl<'a> Lines<'a> {
+  pub(crate) fn new(yarn: YarnRef<'a, str>) -> Self {
+    Self { yarn, start: 0 }
+  }
+}
+
+impl<'a> Iterator for Lines<'a> {
+  type Item = YarnRef<'a, str>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let text = self.yarn.as_str();
+    let rest = text.get(self.start..).filter(|r| !r.is_empty())?;
+
+    let line = match rest.find('\n') {
+      Some(end) => {
+        self.start += end + 1;
+        let line = &rest[..end];
+        line.strip_suffix('\r').unwrap_or(line)
+      }
+      None => {
+        self.start = text.len();
+        rest
+      }
+    };
+
+    Some(self.yarn.subyarn(line))
+  }
+}
+
+/// An iterator over the characters removed from a yarn.
+///
+/// The whole range is removed from the yarn when this iterator is dropped,
+/// even if not all of it has been yielded. If it is leaked instead, the yarn
+/// is left unchanged.
+///
+/// See [`Yarn::drain()`].
+pub struct Drain<'y, 'a> {
+  yarn: &'y mut YarnBox<'a, str>,
+  // The range being removed.
+  start: usize,
+  end: usize,
+  // The part of that range that has not been yielded yet.
+  front: usize,
+  back: usize,
+}
+
+impl<'y, 'a> Drain<'y, 'a> {
+  pub(crate) fn new(
+    yarn: &'y mut YarnBox<'a, str>,
+    start: usize,
+    end: usize,
+  ) -> Self {
+    Self {
+      yarn,
+      start,
+      end,
+      front: start,
+      back: end,
+    }
+  }
+
+  /// Returns the part of the drained range that has not been yielded yet.
+  pub fn as_str(&self) -> &str {
+    &self.yarn.as_str()[self.front..self.back]
+  }
+}
+
+impl Iterator for Drain<'_, '_> {
+  type Item = char;
+
+  fn next(&mut self) -> Option<char> {
+    let c = self.as_str().chars().next()?;
+    self.front += c.len_utf8();
+    Some(c)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.as_str().chars().size_hint()
+  }
+}
+
+impl DoubleEndedIterator for Drain<'_, '_> {
+  fn next_back(&mut self) -> Option<char> {
+    let c = self.as_str().chars().next_back()?;
+    self.back -= c.len_utf8();
+    Some(c)
+  }
+}
+
+impl FusedIterator for Drain<'_, '_> {}
+
+impl fmt::Debug for Drain<'_, '_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_tuple("Drain").field(&self.as_str()).finish()
+  }
+}
+
+impl Drop for Drain<'_, '_> {
+  fn drop(&mut self) {
+    unsafe {
+      // SAFETY: Drain::new is only called with a range of char boundaries,
+      // and removing it leaves a valid string.
+      self.yarn.remove_bytes(self.start, self.end)
+    }
+  }
+}
+
+#[test]
+fn lines_alias_heap_yarns() {
+  use crate::Yarn;
+
+  let text = "the first line is long enough\r\nand so is the second one\nx";
+  let yarn = Yarn::from(text.to_string());
+  let lines = yarn.lines().collect::<Vec<_>>();
+  assert_eq!(
+    lines,
+    ["the first line is long enough", "and so is the second one", "x"]
+  );
+  assert_eq!(lines[0].as_ptr(), yarn.as_ptr());
+
+  // Lines of an inlined yarn must be inlined, too, so they can outlive it.
+  let small = Yarn::from("a\nb\r\n");
+  let lines = small
+    .lines()
+    .map(|line| line.immortalize().unwrap())
+    .collect::<Vec<_>>();
+  drop(small);
+  assert_eq!(lines, ["a", "b"]);
+
+  for text in ["", "\n", "a\r", "\r\n\r\n", "a\n\nb"] {
+    let expected = text.lines().collect::<Vec<_>>();
+    assert_eq!(Yarn::new(text).lines().collect::<Vec<_>>(), expected);
+  }
+}
+
+#[test]
+fn matches_alias_heap_yarns() {
+  use crate::Yarn;
+
+  let needle = "a needle too long to inline";
+  let text = format!("{needle} in a haystack, and {needle}");
+  let yarn = Yarn::from(text);
+  let found = yarn.match_indices(needle).collect::<Vec<_>>();
+  assert_eq!(found.len(), 2);
+  for (i, m) in found {
+    assert_eq!(m, needle);
+    assert_eq!(m.as_ptr(), yarn.as_str()[i..].as_ptr());
+  }
+
+  for (text, pat) in
+    [("aaa", "aa"), ("abc", ""), ("", ""), ("ab", "x"), ("ééé", "é")]
+  {
+    let expected = text.match_indices(pat).collect::<Vec<_>>();
+    let yarn = Yarn::new(text);
+    let actual = yarn.match_indices(pat).collect::<Vec<_>>();
+    assert_eq!(actual.len(), expected.len());
+    for ((i, m), (j, n)) in actual.into_iter().zip(expected) {
+      assert_eq!((i, m.as_str()), (j, n));
+    }
+    assert_eq!(yarn.matches(pat).count(), text.matches(pat).count());
+  }
+
+  let yarn = Yarn::new("x.y.z");
+  assert_eq!(yarn.matches('.').count(), 2);
+  assert_eq!(yarn.matches(&Yarn::new(".y")).collect::<Vec<_>>(), [".y"]);
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn graphemes_alias_heap_yarns() {
+  use crate::Yarn;
+
+  let family = "👩\u{200d}👩\u{200d}👧\u{200d}👦";
+  let yarn = Yarn::from(format!("{family}a\r\n{family}"));
+  let graphemes = yarn.grapheme_indices().collect::<Vec<_>>();
+  assert_eq!(graphemes.len(), 4);
+  assert_eq!(graphemes[0].1, family);
+  assert_eq!(graphemes[0].1.as_ptr(), yarn.as_ptr());
+  assert_eq!(graphemes[2].1, "\r\n");
+
+  let (i, last) = graphemes[3];
+  assert_eq!(last.as_ptr(), yarn.as_str()[i..].as_ptr());
+  assert!(Yarn::new("").graphemes().next().is_none());
+}
+
+#[test]
+fn drain_removes_range_when_dropped() {
+  use crate::Yarn;
+
+  let text = "a static string 🐈‍⬛ that is too long to inline";
+  let mut yarn = Yarn::from_static(text);
+  let mut drain = yarn.drain(16..);
+  assert_eq!(drain.next(), Some('🐈'));
+  assert_eq!(drain.next_back(), Some('e'));
+  assert_eq!(drain.as_str(), "\u{200d}⬛ that is too long to inlin");
+  drop(drain);
+  assert_eq!(yarn, "a static string ");
+  assert!(yarn.is_heap());
+
+  // The allocation is kept, even once the yarn is small enough to inline.
+  let ptr = yarn.as_ptr();
+  assert_eq!(yarn.drain(1..=8).collect::<String>(), " static ");
+  assert_eq!(yarn, "astring ");
+  assert_eq!(yarn.as_ptr(), ptr);
+
+  // Leaking the iterator leaves the yarn as it was.
+  std::mem::forget(yarn.drain(..));
+  assert_eq!(yarn, "astring ");
+  yarn.drain(..);
+  assert!(yarn.is_empty());
+}
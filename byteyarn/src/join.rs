@@ -0,0 +1,119 @@
+//! Incremental joining of yarns.
+
+use core::fmt;
+
+use crate::YarnBox;
+
+#[cfg(doc)]
+use crate::*;
+
+/// A builder that concatenates pieces into a yarn, separated by a separator.
+///
+/// Unlike [`Yarn::join()`], this does not need all of the pieces up front: it
+/// builds the result inline as pieces are pushed, and only spills onto the
+/// heap once the result no longer fits. Joining no pieces produces an empty
+/// yarn without allocating.
+///
+/// ```
+/// # use byteyarn::*;
+/// let mut idents = YarnJoin::new(", ");
+/// idents.extend(["a", "b", "c"]);
+/// assert_eq!(idents.finish(), "a, b, c");
+///
+/// let mut join = YarnJoin::new(" + ");
+/// for i in 1..=3 {
+///   join.push(yarn!("x{i}"));
+/// }
+/// assert_eq!(join.finish(), "x1 + x2 + x3");
+/// ```
+pub struct YarnJoin<'s, Buf: crate::Buf + ?Sized + 'static> {
+  yarn: YarnBox<'static, Buf>,
+  sep: &'s Buf,
+  // Whether nothing has been pushed yet, which is distinct from `yarn` being
+  // empty, because the pieces themselves may be empty.
+  first: bool,
+}
+
+impl<'s, Buf: crate::Buf + ?Sized + 'static> YarnJoin<'s, Buf> {
+  /// Returns a new, empty builder that separates pieces with `sep`.
+  pub fn new(sep: &'s Buf) -> Self {
+    Self {
+      yarn: YarnBox::default(),
+      sep,
+      first: true,
+    }
+  }
+
+  /// Appends `piece` to the result, preceded by the separator if this is not
+  /// the first piece.
+  pub fn push(&mut self, piece: impl AsRef<Buf>) {
+    unsafe {
+      // SAFETY: Concatenating valid Bufs produces a valid Buf.
+      if !core::mem::take(&mut self.first) {
+        self.yarn.append_bytes(self.sep.as_bytes());
+      }
+      self.yarn.append_bytes(piece.as_ref().as_bytes());
+    }
+  }
+
+  /// Returns the yarn built so far.
+  pub fn as_yarn(&self) -> &YarnBox<'static, Buf> {
+    &self.yarn
+  }
+
+  /// Consumes this builder, returning the joined yarn.
+  pub fn finish(self) -> YarnBox<'static, Buf> {
+    self.yarn
+  }
+}
+
+impl<Buf: crate::Buf + ?Sized + 'static, T: AsRef<Buf>> Extend<T>
+  for YarnJoin<'_, Buf>
+{
+  fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+    for piece in iter {
+      self.push(piece);
+    }
+  }
+}
+
+impl<Buf> fmt::Debug for YarnJoin<'_, Buf>
+where
+  Buf: crate::Buf + ?Sized + 'static,
+  YarnBox<'static, Buf>: fmt::Debug,
+{
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Debug::fmt(&self.yarn, f)
+  }
+}
+
+#[test]
+fn join_spills_once() {
+  use crate::raw::RawYarn;
+  use crate::yarn;
+  use crate::Yarn;
+
+  let empty = YarnJoin::<str>::new(", ").finish();
+  assert!(empty.is_empty());
+  assert!(!empty.is_heap());
+
+  let mut join = YarnJoin::new(",");
+  join.extend(["", "a", ""]);
+  assert_eq!(join.as_yarn(), ",a,");
+  assert!(join.as_yarn().is_inlined());
+
+  let mut expected = String::from(",a,");
+  for i in 0..RawYarn::SSO_LEN {
+    join.push(yarn!("{i}"));
+    expected += &format!(",{i}");
+  }
+  assert!(join.as_yarn().is_heap());
+  assert!(join.as_yarn().capacity() > join.as_yarn().len());
+  assert_eq!(join.finish(), expected);
+
+  let ids = (0..3).map(|i| yarn!("id{i}"));
+  assert_eq!(Yarn::from_iter(ids.clone()), "id0id1id2");
+  let mut join = YarnJoin::new("|");
+  join.extend(ids);
+  assert_eq!(join.finish(), "id0|id1|id2");
+}
@@ -0,0 +1,62 @@
+//! JSON string escaping.
+
+use crate::Yarn;
+
+/// Escapes `s` as the contents of a JSON string literal.
+///
+/// If `ascii` is set, every non-ASCII character is also escaped, as `\uXXXX`
+/// or a surrogate pair thereof.
+pub(crate) fn escape(s: &str, ascii: bool) -> Yarn {
+  const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+  let mut yarn = Yarn::with_capacity(s.len());
+
+  // Copy runs of characters that need no escaping all at once.
+  let mut start = 0;
+  for (i, c) in s.char_indices() {
+    let short = match c {
+      '"' => "\\\"",
+      '\\' => "\\\\",
+      '\u{8}' => "\\b",
+      '\u{c}' => "\\f",
+      '\n' => "\\n",
+      '\r' => "\\r",
+      '\t' => "\\t",
+      '\0'..='\u{1f}' => "",
+      c if ascii && !c.is_ascii() => "",
+      _ => continue,
+    };
+
+    yarn.push_str(&s[start..i]);
+    start = i + c.len_utf8();
+    if !short.is_empty() {
+      yarn.push_str(short);
+      continue;
+    }
+
+    let mut units = [0; 2];
+    for &mut unit in c.encode_utf16(&mut units) {
+      yarn.push_str("\\u");
+      for shift in [12, 8, 4, 0] {
+        yarn.push(DIGITS[(unit >> shift & 0xf) as usize] as char);
+      }
+    }
+  }
+  yarn.push_str(&s[start..]);
+  yarn
+}
+
+#[test]
+fn json_escape() {
+  let yarn = Yarn::new("say \"hi\"\\\n\t\u{1}\u{7f}é🐈");
+  assert_eq!(yarn.json_escape(), "say \\\"hi\\\"\\\\\\n\\t\\u0001\u{7f}é🐈",);
+  assert_eq!(
+    yarn.json_escape_ascii(),
+    "say \\\"hi\\\"\\\\\\n\\t\\u0001\u{7f}\\u00e9\\ud83d\\udc08",
+  );
+
+  let plain = Yarn::from_static("nothing to escape here at all");
+  assert_eq!(plain.json_escape(), plain);
+  assert!(Yarn::new("a\tb").json_escape().is_inlined());
+  assert_eq!(Yarn::new("").json_escape(), "");
+}
@@ -9,12 +9,14 @@
 //! * Can be either an owned buffer or a borrowed buffer (like [`Cow<str>`]).
 //! * Can be upcast to `'static` lifetime if it was constructed from a
 //!   known-static string.
+//! * Can share a reference-counted buffer (like
+//!   [`Arc<str>`](std::sync::Arc)), so that cloning a large yarn is cheap.
 //! * `Option<Yarn>` has the same size and ABI as `Yarn`.
 //!
-//! The main caveat is that [`Yarn`]s cannot be easily appended to, since they
-//! do not track an internal capacity, and the slice returned by
-//! [`Yarn::as_slice()`] does not have the same pointer stability properties as
-//! [`String`] (these are rarely needed, though).
+//! The main caveat is that the slice returned by [`Yarn::as_slice()`] does
+//! not have the same pointer stability properties as [`String`] (these are
+//! rarely needed, though). Yarns can still be appended to with
+//! [`Yarn::push_str()`], which stays inlined for as long as the result fits.
 //!
 //! ---
 //!
@@ -67,27 +69,80 @@
 //! assert_eq!(sixteen[2], 3u16);
 //! ```
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 #![deny(missing_docs)]
 
+extern crate alloc;
+
 #[cfg(doc)]
-use std::borrow::Cow;
+use alloc::borrow::Cow;
 
+#[cfg(feature = "base64")]
+mod base64;
 mod boxed;
+mod byte_fmt;
+mod caseless;
+mod cat;
 mod convert;
+#[cfg(feature = "fnv")]
+mod fnv;
+mod hex;
+mod iter;
+mod join;
+mod json;
+mod num;
+mod pattern;
+mod percent;
+#[cfg(feature = "std")]
+mod pool;
 mod raw;
 mod reffed;
+#[cfg(feature = "simd")]
+mod simd;
 mod utf8;
+mod writer;
 
+#[cfg(feature = "base64")]
+pub use base64::Base64Error;
 pub use boxed::YarnBox;
+pub use caseless::AsciiCaseless;
+pub use convert::ParseError;
+pub use hex::HexError;
+pub use iter::Drain;
+#[cfg(feature = "unicode")]
+pub use iter::GraphemeIndices;
+#[cfg(feature = "unicode")]
+pub use iter::Graphemes;
+pub use iter::Lines;
+pub use iter::MatchIndices;
+pub use iter::Matches;
+pub use iter::Split;
+pub use join::YarnJoin;
+pub use num::Float;
+pub use num::Integer;
+pub use pattern::Pattern;
+pub use percent::EncodeSet;
+pub use percent::PercentError;
+#[cfg(feature = "std")]
+pub use pool::YarnPool;
+pub use reffed::StorageDebug;
 pub use reffed::YarnRef;
+pub use utf8::CharsLossy;
 pub use utf8::Utf8Chunks;
+pub use writer::YarnWriter;
 
 pub use buf_trait::Buf;
 
 // Macro stuff.
 #[doc(hidden)]
 pub mod m {
-  pub extern crate std;
+  pub extern crate core;
+
+  pub use crate::byte_fmt::check as check_byte_fmt;
+  pub use crate::byte_fmt::format as byte_fmt;
+  pub use crate::cat::to_str as cat_to_str;
+  pub use crate::cat::Piece as CatPiece;
 }
 
 /// An optimized Unicode string.
@@ -106,6 +161,110 @@ pub type ByteYarn = YarnBox<'static, [u8]>;
 #[macro_export]
 macro_rules! yarn {
   ($($args:tt)*) => {
-    $crate::Yarn::from_fmt($crate::m::std::format_args!($($args)*))
+    $crate::Yarn::from_fmt($crate::m::core::format_args!($($args)*))
+  };
+}
+
+/// Similar to [`yarn!()`], but returns a [`ByteYarn`], instead.
+///
+/// The format string must be a byte string literal, and the only supported
+/// placeholder is `{}`, which is replaced with the bytes of the corresponding
+/// argument; arguments can be anything that implements `AsRef<[u8]>`. As with
+/// [`format!()`], `{{` and `}}` produce literal braces, and a mismatch between
+/// placeholders and arguments is a compile-time error.
+///
+/// Like [`yarn!()`], this does not allocate if the result can be inlined.
+///
+/// ```
+/// # use byteyarn::*;
+/// let frame = byarn!(b"\x01{}\xff{}", "ok", vec![0xfeu8, 0xfd]);
+/// assert_eq!(frame, b"\x01ok\xff\xfe\xfd");
+///
+/// let latin1 = byarn!(b"{{caf\xe9}}");
+/// assert_eq!(latin1, b"{caf\xe9}");
+/// ```
+#[macro_export]
+macro_rules! byarn {
+  ($fmt:literal $(, $args:expr)* $(,)?) => {{
+    const FMT: &[u8] = $fmt;
+    const _: () = $crate::m::check_byte_fmt(
+      FMT,
+      <[&str]>::len(&[$($crate::m::core::stringify!($args)),*]),
+    );
+    $crate::m::byte_fmt(
+      FMT,
+      &[$($crate::m::core::convert::AsRef::<[u8]>::as_ref(&$args)),*],
+    )
+  }};
+}
+
+/// Concatenates strings and yarns into a [`Yarn`].
+///
+/// There are three forms, depending on what is being concatenated:
+///
+/// * If every argument is a literal, this expands to
+///   [`Yarn::from_static()`] of a [`concat!()`] of them, so the result is a
+///   single static string.
+/// * If the arguments are prefixed with `const`, each one must be a constant
+///   `&str`, [`Yarn`], or [`YarnRef<str>`]. They are concatenated at compile
+///   time into a static yarn, and the whole macro can be used to initialize a
+///   `const`.
+/// * Otherwise, the arguments may be anything that implements
+///   [`Display`](core::fmt::Display), and are concatenated at runtime, like
+///   [`yarn!()`]. This does not allocate if the result can be inlined.
+///
+/// ```
+/// # use byteyarn::*;
+/// const GREETING: &str = "hello";
+/// const WORLD: Yarn = Yarn::from_static("world");
+///
+/// const HELLO: Yarn = cat!(const GREETING, ", wide ", WORLD, "!");
+/// assert_eq!(HELLO, "hello, wide world!");
+/// assert!(HELLO.is_static());
+///
+/// let lit = cat!("jellybeans", " and ", "gumdrops");
+/// assert!(lit.is_static());
+///
+/// let name = Yarn::from(String::from("beans"));
+/// assert_eq!(cat!("jelly", name, 42), "jellybeans42");
+/// ```
+///
+/// The `const` form can be nested, and used anywhere a constant is expected,
+/// such as to build tables of yarns with no runtime cost:
+///
+/// ```
+/// # use byteyarn::*;
+/// const PREFIX: Yarn = cat!(const "keyword_table", "::");
+/// const KEYWORDS: [Yarn; 3] = [
+///   cat!(const PREFIX, "if"),
+///   cat!(const PREFIX, "else"),
+///   cat!(const PREFIX, "while"),
+/// ];
+///
+/// assert_eq!(KEYWORDS[2], "keyword_table::while");
+/// assert!(KEYWORDS.iter().all(Yarn::is_static));
+/// ```
+#[macro_export]
+macro_rules! cat {
+  (@hole $arg:expr) => { "{}" };
+  ($($lit:literal),* $(,)?) => {
+    $crate::Yarn::from_static($crate::m::core::concat!($($lit),*))
+  };
+  (const $($arg:expr),* $(,)?) => {{
+    const LEN: usize = 0 $(+ $crate::m::CatPiece($arg).size())*;
+    const BYTES: &[u8; LEN] = &{
+      let mut buf = [0; LEN];
+      let at = 0;
+      $(let at = $crate::m::CatPiece($arg).write(&mut buf, at);)*
+      let _ = at;
+      buf
+    };
+    $crate::Yarn::from_static($crate::m::cat_to_str(BYTES))
+  }};
+  ($($arg:expr),* $(,)?) => {
+    $crate::Yarn::from_fmt($crate::m::core::format_args!(
+      $crate::m::core::concat!($($crate::cat!(@hole $arg)),*),
+      $($arg),*
+    ))
   };
 }
@@ -0,0 +1,113 @@
+//! Formatting numbers into yarns.
+
+use core::fmt;
+use core::str;
+
+use crate::Yarn;
+
+#[cfg(doc)]
+use crate::*;
+
+mod sealed {
+  pub trait Sealed {}
+}
+
+/// A primitive integer type, which can be formatted with [`Yarn::from_int()`].
+///
+/// This trait is sealed, and cannot be implemented outside of this crate.
+pub trait Integer: Copy + sealed::Sealed {
+  /// Writes the decimal digits of this integer into the end of `buf`,
+  /// returning the index of the first one.
+  #[doc(hidden)]
+  fn write_decimal(self, buf: &mut [u8; 40]) -> usize;
+}
+
+macro_rules! integers {
+  ($($int:ty),*) => {$(
+    impl sealed::Sealed for $int {}
+    impl Integer for $int {
+      #[allow(unused_comparisons)]
+      fn write_decimal(self, buf: &mut [u8; 40]) -> usize {
+        let mut n = self.unsigned_abs();
+        let mut i = buf.len();
+        loop {
+          i -= 1;
+          buf[i] = b'0' + (n % 10) as u8;
+          n /= 10;
+          if n == 0 {
+            break;
+          }
+        }
+        if self < 0 {
+          i -= 1;
+          buf[i] = b'-';
+        }
+        i
+      }
+    }
+  )*};
+}
+
+// unsigned_abs() is not defined for unsigned types, so give them one.
+trait UnsignedAbs {
+  fn unsigned_abs(self) -> Self;
+}
+
+macro_rules! unsigned_abs {
+  ($($int:ty),*) => {$(
+    impl UnsignedAbs for $int {
+      fn unsigned_abs(self) -> Self {
+        self
+      }
+    }
+  )*};
+}
+
+unsigned_abs!(u8, u16, u32, u64, u128, usize);
+integers!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// A primitive floating-point type, which can be formatted with
+/// [`Yarn::from_float()`].
+///
+/// This trait is sealed, and cannot be implemented outside of this crate.
+pub trait Float: Copy + fmt::Debug + sealed::Sealed {}
+
+impl sealed::Sealed for f32 {}
+impl Float for f32 {}
+impl sealed::Sealed for f64 {}
+impl Float for f64 {}
+
+pub(crate) fn from_int(n: impl Integer) -> Yarn {
+  let mut buf = [0; 40];
+  let start = n.write_decimal(&mut buf);
+  let digits = unsafe {
+    // SAFETY: write_decimal() only writes ASCII digits and signs.
+    str::from_utf8_unchecked(&buf[start..])
+  };
+  Yarn::copy(digits)
+}
+
+pub(crate) fn from_float(f: impl Float) -> Yarn {
+  Yarn::from_fmt(format_args!("{f:?}"))
+}
+
+#[test]
+fn numbers() {
+  macro_rules! check {
+    ($($int:ty),*) => {$(
+      for n in [<$int>::MIN, <$int>::MAX, 0, 1, 9, 10, 99, 100] {
+        assert_eq!(Yarn::from_int(n), n.to_string());
+      }
+    )*};
+  }
+  check!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+  assert!(Yarn::from_int(i32::MIN).is_inlined());
+  assert!(Yarn::from_int(-99_999_999_999_999i64).is_inlined());
+
+  for f in [0.0, -0.0, 1.0, 0.1, 1e300, -1.5e-300, f64::NAN, f64::INFINITY] {
+    assert_eq!(Yarn::from_float(f), format!("{f:?}"));
+  }
+  assert_eq!(Yarn::from_float(0.1f32), "0.1");
+  assert!(Yarn::from_float(f32::MAX).is_inlined());
+}
@@ -0,0 +1,256 @@
+//! Patterns for searching within yarns.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use memchr::memmem;
+
+use crate::YarnBox;
+use crate::YarnRef;
+
+#[cfg(doc)]
+use crate::*;
+
+/// A pattern that can be searched for within a yarn.
+///
+/// This is a stable analogue of the standard library's string patterns, and
+/// is implemented for `char`, string slices, yarns, and `FnMut(char) -> bool`
+/// closures (which match a single character).
+///
+/// Byte yarns can be searched with `Pattern<[u8]>`, which is implemented for
+/// all of the above except closures, as well as for `u8`, byte strings, byte
+/// yarns, and `FnMut(u8) -> bool` closures. Substring searches are performed
+/// with `memchr`'s `memmem`.
+///
+/// All offsets are in bytes, relative to the start of the haystack.
+///
+/// ```
+/// # use byteyarn::*;
+/// let yarn = Yarn::new("jellybeans");
+/// assert_eq!(yarn.strip_prefix('j').unwrap(), "ellybeans");
+/// assert_eq!(yarn.strip_prefix("jelly").unwrap(), "beans");
+/// assert_eq!(yarn.strip_prefix(&Yarn::new("jelly")).unwrap(), "beans");
+/// assert_eq!(yarn.strip_suffix(|c: char| c.is_ascii_lowercase()).unwrap(), "jellybean");
+/// ```
+pub trait Pattern<Buf: ?Sized = str> {
+  /// If `haystack` starts with this pattern, returns the length of the match.
+  fn match_prefix(&mut self, haystack: &Buf) -> Option<usize>;
+
+  /// If `haystack` ends with this pattern, returns the length of the match.
+  fn match_suffix(&mut self, haystack: &Buf) -> Option<usize>;
+
+  /// Returns the range of the first match of this pattern in `haystack`.
+  fn find_in(&mut self, haystack: &Buf) -> Option<Range<usize>>;
+
+  /// Returns the range of the last match of this pattern in `haystack`.
+  fn rfind_in(&mut self, haystack: &Buf) -> Option<Range<usize>>;
+}
+
+impl Pattern for char {
+  fn match_prefix(&mut self, haystack: &str) -> Option<usize> {
+    haystack.starts_with(*self).then(|| self.len_utf8())
+  }
+
+  fn match_suffix(&mut self, haystack: &str) -> Option<usize> {
+    haystack.ends_with(*self).then(|| self.len_utf8())
+  }
+
+  fn find_in(&mut self, haystack: &str) -> Option<Range<usize>> {
+    let start = haystack.find(*self)?;
+    Some(start..start + self.len_utf8())
+  }
+
+  fn rfind_in(&mut self, haystack: &str) -> Option<Range<usize>> {
+    let start = haystack.rfind(*self)?;
+    Some(start..start + self.len_utf8())
+  }
+}
+
+impl<F: FnMut(char) -> bool> Pattern for F {
+  fn match_prefix(&mut self, haystack: &str) -> Option<usize> {
+    let c = haystack.chars().next()?;
+    self(c).then(|| c.len_utf8())
+  }
+
+  fn match_suffix(&mut self, haystack: &str) -> Option<usize> {
+    let c = haystack.chars().next_back()?;
+    self(c).then(|| c.len_utf8())
+  }
+
+  fn find_in(&mut self, haystack: &str) -> Option<Range<usize>> {
+    let (start, c) = haystack.char_indices().find(|&(_, c)| self(c))?;
+    Some(start..start + c.len_utf8())
+  }
+
+  fn rfind_in(&mut self, haystack: &str) -> Option<Range<usize>> {
+    let (start, c) = haystack.char_indices().rfind(|&(_, c)| self(c))?;
+    Some(start..start + c.len_utf8())
+  }
+}
+
+macro_rules! str_patterns {
+  ($($ty:ty),* $(,)?) => {$(
+    impl Pattern for $ty {
+      fn match_prefix(&mut self, haystack: &str) -> Option<usize> {
+        let needle: &str = self.as_ref();
+        haystack.starts_with(needle).then_some(needle.len())
+      }
+
+      fn match_suffix(&mut self, haystack: &str) -> Option<usize> {
+        let needle: &str = self.as_ref();
+        haystack.ends_with(needle).then_some(needle.len())
+      }
+
+      fn find_in(&mut self, haystack: &str) -> Option<Range<usize>> {
+        let needle: &str = self.as_ref();
+        let start = haystack.find(needle)?;
+        Some(start..start + needle.len())
+      }
+
+      fn rfind_in(&mut self, haystack: &str) -> Option<Range<usize>> {
+        let needle: &str = self.as_ref();
+        let start = haystack.rfind(needle)?;
+        Some(start..start + needle.len())
+      }
+    }
+  )*};
+}
+
+str_patterns! {
+  &str,
+  &String,
+  YarnBox<'_, str>,
+  &YarnBox<'_, str>,
+  YarnRef<'_, str>,
+  &YarnRef<'_, str>,
+}
+
+impl Pattern<[u8]> for u8 {
+  fn match_prefix(&mut self, haystack: &[u8]) -> Option<usize> {
+    haystack.starts_with(&[*self]).then_some(1)
+  }
+
+  fn match_suffix(&mut self, haystack: &[u8]) -> Option<usize> {
+    haystack.ends_with(&[*self]).then_some(1)
+  }
+
+  fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+    let start = memchr::memchr(*self, haystack)?;
+    Some(start..start + 1)
+  }
+
+  fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+    let start = memchr::memrchr(*self, haystack)?;
+    Some(start..start + 1)
+  }
+}
+
+impl Pattern<[u8]> for char {
+  fn match_prefix(&mut self, haystack: &[u8]) -> Option<usize> {
+    self
+      .encode_utf8(&mut [0; 4])
+      .as_bytes()
+      .match_prefix(haystack)
+  }
+
+  fn match_suffix(&mut self, haystack: &[u8]) -> Option<usize> {
+    self
+      .encode_utf8(&mut [0; 4])
+      .as_bytes()
+      .match_suffix(haystack)
+  }
+
+  fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+    self.encode_utf8(&mut [0; 4]).as_bytes().find_in(haystack)
+  }
+
+  fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+    self.encode_utf8(&mut [0; 4]).as_bytes().rfind_in(haystack)
+  }
+}
+
+impl<F: FnMut(u8) -> bool> Pattern<[u8]> for F {
+  fn match_prefix(&mut self, haystack: &[u8]) -> Option<usize> {
+    self(*haystack.first()?).then_some(1)
+  }
+
+  fn match_suffix(&mut self, haystack: &[u8]) -> Option<usize> {
+    self(*haystack.last()?).then_some(1)
+  }
+
+  fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+    let start = haystack.iter().position(|&b| self(b))?;
+    Some(start..start + 1)
+  }
+
+  fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+    let start = haystack.iter().rposition(|&b| self(b))?;
+    Some(start..start + 1)
+  }
+}
+
+macro_rules! byte_patterns {
+  ($($ty:ty => |$x:ident| $bytes:expr),* $(,)?) => {$(
+    impl Pattern<[u8]> for $ty {
+      fn match_prefix(&mut self, haystack: &[u8]) -> Option<usize> {
+        let $x = &*self;
+        let needle: &[u8] = $bytes;
+        haystack.starts_with(needle).then_some(needle.len())
+      }
+
+      fn match_suffix(&mut self, haystack: &[u8]) -> Option<usize> {
+        let $x = &*self;
+        let needle: &[u8] = $bytes;
+        haystack.ends_with(needle).then_some(needle.len())
+      }
+
+      fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+        let $x = &*self;
+        let needle: &[u8] = $bytes;
+        let start = memmem::find(haystack, needle)?;
+        Some(start..start + needle.len())
+      }
+
+      fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+        let $x = &*self;
+        let needle: &[u8] = $bytes;
+        let start = memmem::rfind(haystack, needle)?;
+        Some(start..start + needle.len())
+      }
+    }
+  )*};
+}
+
+byte_patterns! {
+  &[u8] => |x| x,
+  &Vec<u8> => |x| x,
+  YarnBox<'_, [u8]> => |x| x.as_slice(),
+  &YarnBox<'_, [u8]> => |x| x.as_slice(),
+  YarnRef<'_, [u8]> => |x| x.as_slice(),
+  &YarnRef<'_, [u8]> => |x| x.as_slice(),
+  &str => |x| x.as_bytes(),
+  &String => |x| x.as_bytes(),
+  YarnBox<'_, str> => |x| x.as_bytes(),
+  &YarnBox<'_, str> => |x| x.as_bytes(),
+  YarnRef<'_, str> => |x| x.as_bytes(),
+  &YarnRef<'_, str> => |x| x.as_bytes(),
+}
+
+impl<const N: usize> Pattern<[u8]> for &[u8; N] {
+  fn match_prefix(&mut self, haystack: &[u8]) -> Option<usize> {
+    self.as_slice().match_prefix(haystack)
+  }
+
+  fn match_suffix(&mut self, haystack: &[u8]) -> Option<usize> {
+    self.as_slice().match_suffix(haystack)
+  }
+
+  fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+    self.as_slice().find_in(haystack)
+  }
+
+  fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+    self.as_slice().rfind_in(haystack)
+  }
+}
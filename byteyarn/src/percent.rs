@@ -0,0 +1,209 @@
+//! Percent-encoding, as used in URLs.
+
+use core::fmt;
+
+use crate::ByteYarn;
+use crate::Yarn;
+
+#[cfg(doc)]
+use crate::*;
+
+/// A set of ASCII bytes to percent-encode, for use with
+/// [`Yarn::percent_encode()`].
+///
+/// Bytes outside of ASCII are always encoded. The sets provided as constants
+/// are those from the [WHATWG URL standard](https://url.spec.whatwg.org/#percent-encoded-bytes),
+/// and more can be built with [`EncodeSet::add()`].
+///
+/// ```
+/// # use byteyarn::*;
+/// const PLUS: EncodeSet = EncodeSet::CONTROLS.add(b'+').add(b' ');
+/// assert_eq!(Yarn::new("a+b c").percent_encode(PLUS), "a%2Bb%20c");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EncodeSet {
+  // One bit per ASCII byte.
+  bits: u128,
+}
+
+impl EncodeSet {
+  /// The C0 control characters and DEL.
+  pub const CONTROLS: Self = Self { bits: 0xffff_ffff | 1 << 0x7f };
+
+  /// Everything except ASCII letters and digits.
+  pub const NON_ALPHANUMERIC: Self = {
+    let mut set = Self { bits: !0 };
+    let mut b = 0;
+    while b < 0x80 {
+      if (b as u8).is_ascii_alphanumeric() {
+        set = set.remove(b as u8);
+      }
+      b += 1;
+    }
+    set
+  };
+
+  /// The bytes that must be encoded in a URL fragment.
+  pub const FRAGMENT: Self = Self::CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`');
+
+  /// The bytes that must be encoded in a URL query.
+  pub const QUERY: Self = Self::CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>');
+
+  /// The bytes that must be encoded in a URL path.
+  pub const PATH: Self = Self::QUERY.add(b'?').add(b'`').add(b'{').add(b'}');
+
+  /// The bytes that must be encoded in the userinfo of a URL.
+  pub const USERINFO: Self = Self::PATH
+    .add(b'/')
+    .add(b':')
+    .add(b';')
+    .add(b'=')
+    .add(b'@')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'|');
+
+  /// The bytes that must be encoded in a URL component, which is everything
+  /// except ASCII alphanumerics and `-._~!'()*`, like JavaScript's
+  /// `encodeURIComponent()`.
+  pub const COMPONENT: Self = Self::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*');
+
+  /// Returns this set with `byte` added to it.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `byte` is not ASCII.
+  pub const fn add(self, byte: u8) -> Self {
+    assert!(byte.is_ascii(), "EncodeSet can only contain ASCII bytes");
+    Self { bits: self.bits | 1 << byte }
+  }
+
+  /// Returns this set with `byte` removed from it.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `byte` is not ASCII.
+  pub const fn remove(self, byte: u8) -> Self {
+    assert!(byte.is_ascii(), "EncodeSet can only contain ASCII bytes");
+    Self { bits: self.bits & !(1 << byte) }
+  }
+
+  /// Returns whether `byte` should be encoded under this set.
+  pub const fn contains(self, byte: u8) -> bool {
+    !byte.is_ascii() || self.bits & 1 << byte != 0
+  }
+}
+
+/// An error returned by [`Yarn::percent_decode()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PercentError {
+  /// A `%` at the given byte offset was not followed by two hex digits.
+  InvalidEscape(usize),
+}
+
+impl fmt::Display for PercentError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::InvalidEscape(idx) => {
+        write!(f, "invalid percent-escape at offset {idx}")
+      }
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PercentError {}
+
+/// Percent-encodes every byte of `bytes` in `set`.
+pub(crate) fn encode(bytes: &[u8], set: EncodeSet) -> Yarn {
+  const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+  let mut yarn = Yarn::with_capacity(bytes.len());
+  for &b in bytes {
+    if set.contains(b) {
+      yarn.push('%');
+      yarn.push(DIGITS[(b >> 4) as usize] as char);
+      yarn.push(DIGITS[(b & 0xf) as usize] as char);
+    } else {
+      yarn.push(b as char);
+    }
+  }
+  yarn
+}
+
+/// Decodes every `%XY` escape in `bytes`.
+pub(crate) fn decode(bytes: &[u8]) -> Result<ByteYarn, PercentError> {
+  let digit = |b: u8| (b as char).to_digit(16);
+
+  let mut yarn = ByteYarn::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    if bytes[i] != b'%' {
+      // Copy everything up to the next escape at once.
+      let end =
+        memchr::memchr(b'%', &bytes[i..]).map_or(bytes.len(), |n| i + n);
+      yarn.extend([&bytes[i..end]]);
+      i = end;
+      continue;
+    }
+
+    let hi = bytes.get(i + 1).and_then(|&b| digit(b));
+    let lo = bytes.get(i + 2).and_then(|&b| digit(b));
+    let (Some(hi), Some(lo)) = (hi, lo) else {
+      return Err(PercentError::InvalidEscape(i));
+    };
+    yarn.extend([(hi << 4 | lo) as u8]);
+    i += 3;
+  }
+  Ok(yarn)
+}
+
+#[test]
+fn percent_round_trip() {
+  let text = Yarn::new("a b/c?d=é");
+  let encoded = text.percent_encode(EncodeSet::COMPONENT);
+  assert_eq!(encoded, "a%20b%2Fc%3Fd%3D%C3%A9");
+  assert_eq!(encoded.percent_decode().unwrap(), text.as_bytes());
+
+  let path = text.percent_encode(EncodeSet::PATH);
+  assert_eq!(path, "a%20b/c%3Fd=%C3%A9");
+  assert_eq!(path.percent_decode().unwrap().to_utf8().unwrap(), text);
+
+  let small = Yarn::new("%41%42%43").percent_decode().unwrap();
+  assert_eq!(small, b"ABC");
+  assert!(small.is_inlined());
+  assert!(Yarn::new("a~b")
+    .percent_encode(EncodeSet::COMPONENT)
+    .is_inlined());
+
+  let bytes = (0..=255).collect::<ByteYarn>();
+  let encoded = bytes.percent_encode(EncodeSet::NON_ALPHANUMERIC);
+  assert_eq!(encoded.len(), 62 + 194 * 3);
+  assert_eq!(encoded.percent_decode().unwrap(), bytes);
+
+  for (bad, at) in [("%", 0), ("a%4", 1), ("%4g", 0), ("ok%%41", 2)] {
+    let err = Yarn::new(bad).percent_decode();
+    assert_eq!(err, Err(PercentError::InvalidEscape(at)));
+  }
+}
@@ -0,0 +1,223 @@
+//! String interning.
+
+use core::borrow::Borrow;
+use core::cell::RefCell;
+use core::fmt;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::ptr::NonNull;
+use core::str;
+use std::collections::HashSet;
+
+use crate::raw::RawYarn;
+use crate::YarnBox;
+use crate::YarnRef;
+
+#[cfg(doc)]
+use crate::*;
+
+/// A pool of interned strings.
+///
+/// Interning a string returns a [`YarnRef`] that aliases a copy of it owned by
+/// the pool. Interning the same string twice returns a yarn pointing to the
+/// same copy, even for strings that would otherwise be inlined, so interned
+/// strings can be compared by address.
+///
+/// Requires the `std` feature.
+///
+/// ```
+/// # use byteyarn::*;
+/// use std::ptr;
+///
+/// let pool = YarnPool::new();
+/// let a = pool.intern("jellybeans");
+/// let b = pool.intern(yarn!("jelly{}", "beans"));
+/// let c = pool.intern("gumdrops");
+///
+/// assert!(ptr::eq(a.as_slice(), b.as_slice()));
+/// assert!(!ptr::eq(a.as_slice(), c.as_slice()));
+/// assert_eq!(pool.len(), 2);
+/// ```
+#[derive(Default)]
+pub struct YarnPool {
+  state: RefCell<State>,
+}
+
+#[derive(Default)]
+struct State {
+  strings: HashSet<Interned>,
+  // Each chunk is allocated with a fixed capacity, and never grown past it, so
+  // strings copied into it never move.
+  chunks: Vec<Vec<u8>>,
+}
+
+/// A canonical copy of an interned string, which is always `ALIASED` into one
+/// of the pool's chunks.
+#[derive(Copy, Clone)]
+struct Interned(RawYarn);
+
+impl Interned {
+  fn as_str(&self) -> &str {
+    unsafe {
+      // SAFETY: Interned yarns are never inlined, so the returned slice does
+      // not point into self, and they are only ever made from strings.
+      str::from_utf8_unchecked(self.0.as_slice())
+    }
+  }
+}
+
+impl Borrow<str> for Interned {
+  fn borrow(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl PartialEq for Interned {
+  fn eq(&self, other: &Self) -> bool {
+    self.as_str() == other.as_str()
+  }
+}
+
+impl Eq for Interned {}
+
+impl Hash for Interned {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.as_str().hash(state)
+  }
+}
+
+impl YarnPool {
+  /// The minimum size of a chunk of interned string data.
+  const CHUNK_SIZE: usize = 4096;
+
+  /// Returns a new, empty pool.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns the number of distinct strings in this pool.
+  pub fn len(&self) -> usize {
+    self.state.borrow().strings.len()
+  }
+
+  /// Returns whether this pool contains no strings.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Interns a string, returning a yarn pointing to this pool's copy of it.
+  ///
+  /// If an equal string has already been interned, this returns a yarn with
+  /// the same address as the one returned previously.
+  pub fn intern<'a>(
+    &self,
+    yarn: impl Into<YarnBox<'a, str>>,
+  ) -> YarnRef<'_, str> {
+    let yarn = yarn.into();
+    if yarn.is_empty() {
+      return unsafe {
+        // SAFETY: Empty yarns cannot be ALIASED, but a STATIC yarn of length
+        // zero may have any non-null pointer.
+        YarnRef::from_raw(RawYarn::from_ptr_len_tag(
+          NonNull::dangling().as_ptr(),
+          0,
+          RawYarn::STATIC,
+        ))
+      };
+    }
+
+    let mut state = self.state.borrow_mut();
+    if let Some(interned) = state.strings.get(yarn.as_str()) {
+      return unsafe { self.to_yarn(*interned) };
+    }
+
+    let bytes = yarn.as_bytes();
+    let needs_chunk = state
+      .chunks
+      .last()
+      .is_none_or(|c| c.capacity() - c.len() < bytes.len());
+    if needs_chunk {
+      let cap = bytes.len().max(Self::CHUNK_SIZE);
+      state.chunks.push(Vec::with_capacity(cap));
+    }
+
+    let chunk = state.chunks.last_mut().unwrap();
+    let start = chunk.len();
+    chunk.extend_from_slice(bytes);
+
+    let interned = unsafe {
+      // SAFETY: The chunk has enough capacity for bytes, so it did not
+      // reallocate, and will never reallocate or be freed until the pool is
+      // dropped. The copy is not empty, so it can be ALIASED.
+      Interned(RawYarn::from_ptr_len_tag(
+        chunk.as_ptr().add(start),
+        bytes.len(),
+        RawYarn::ALIASED,
+      ))
+    };
+    state.strings.insert(interned);
+
+    unsafe { self.to_yarn(interned) }
+  }
+
+  /// Looks up a string in this pool, without interning it.
+  ///
+  /// ```
+  /// # use byteyarn::*;
+  /// let pool = YarnPool::new();
+  /// let a = pool.intern("jellybeans");
+  ///
+  /// assert_eq!(pool.get("jellybeans"), Some(a));
+  /// assert_eq!(pool.get("gumdrops"), None);
+  /// ```
+  pub fn get(&self, s: &str) -> Option<YarnRef<'_, str>> {
+    let interned = *self.state.borrow().strings.get(s)?;
+    unsafe { Some(self.to_yarn(interned)) }
+  }
+
+  /// Converts an interned string into a yarn.
+  ///
+  /// # Safety
+  ///
+  /// `interned` must belong to this pool.
+  unsafe fn to_yarn(&self, interned: Interned) -> YarnRef<'_, str> {
+    // SAFETY: The returned yarn borrows self, so the chunk it points into
+    // will outlive it.
+    YarnRef::from_raw(interned.0)
+  }
+}
+
+impl fmt::Debug for YarnPool {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let state = self.state.borrow();
+    f.debug_set()
+      .entries(state.strings.iter().map(Interned::as_str))
+      .finish()
+  }
+}
+
+#[test]
+fn interned_strings_are_stable() {
+  let pool = YarnPool::new();
+  let words = (0..1000).map(|i| format!("word{i}")).collect::<Vec<_>>();
+
+  // Intern enough strings to need several chunks.
+  let first = words
+    .iter()
+    .map(|w| pool.intern(w.as_str()))
+    .collect::<Vec<_>>();
+  let again = words
+    .iter()
+    .map(|w| pool.intern(w.as_str()))
+    .collect::<Vec<_>>();
+  for ((a, b), w) in first.iter().zip(&again).zip(&words) {
+    assert!(a.same_allocation(*b));
+    assert_eq!(a, w);
+  }
+  assert_eq!(pool.len(), words.len());
+
+  let big = "x".repeat(YarnPool::CHUNK_SIZE * 2);
+  assert_eq!(pool.intern(big.as_str()), big);
+  assert_eq!(pool.intern(""), "");
+  assert_eq!(pool.intern("").as_ptr(), pool.intern(String::new()).as_ptr());
+}
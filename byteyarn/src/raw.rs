@@ -1,12 +1,13 @@
-use std::alloc;
-use std::fmt;
-use std::fmt::Write;
-use std::mem;
-use std::mem::ManuallyDrop;
-use std::mem::MaybeUninit;
-use std::num::NonZeroUsize;
-use std::ptr;
-use std::slice;
+use ::alloc::boxed::Box;
+use ::alloc::sync::Arc;
+use alloc::alloc;
+use core::mem;
+use core::mem::ManuallyDrop;
+use core::mem::MaybeUninit;
+use core::num::NonZeroUsize;
+use core::ops::Range;
+use core::ptr;
+use core::slice;
 
 /// The core implementation of yarns.
 ///
@@ -16,6 +17,8 @@ use std::slice;
 #[derive(Copy, Clone)]
 pub struct RawYarn {
   ptr: *const u8,
+  // This is always stored in little-endian order, so that the tag bits are in
+  // the last byte, which overlaps with `Small::len`.
   len: NonZeroUsize,
 }
 
@@ -52,6 +55,42 @@ fn has_niche() {
   assert_eq!(mem::size_of::<RawYarn>(), mem::size_of::<Option<RawYarn>>());
 }
 
+#[test]
+fn inlined_round_trip() {
+  let data = *b"0123456789abcdefghij";
+  for len in 0..=RawYarn::SSO_LEN {
+    let raw =
+      unsafe { RawYarn::from_slice_inlined_unchecked(data.as_ptr(), len) };
+    assert!(raw.is_small());
+    assert_eq!(raw.len(), len);
+    assert_eq!(raw.as_slice(), &data[..len]);
+  }
+}
+
+#[test]
+fn append_spills_once() {
+  let data = [b'a'; RawYarn::SSO_LEN - 1];
+  unsafe {
+    let mut raw =
+      RawYarn::copy_slice(alloc::Layout::for_value(&data), data.as_ptr());
+    assert!(raw.is_small());
+
+    // Crossing the SSO boundary with a multibyte character spills.
+    raw.append(1, "é".as_bytes());
+    assert!(raw.on_heap());
+    assert_eq!(raw.len(), RawYarn::SSO_LEN + 1);
+    assert!(raw.as_slice().ends_with("é".as_bytes()));
+
+    // Subsequent appends reuse the spare capacity.
+    let ptr = raw.as_ptr();
+    raw.append(1, b"!");
+    assert_eq!(raw.as_ptr(), ptr);
+    assert_eq!(raw.len(), RawYarn::SSO_LEN + 2);
+
+    raw.destroy_in(&Global, alloc::Layout::for_value(raw.as_slice()));
+  }
+}
+
 impl RawYarn {
   /// The number of bytes beyond the length byte that are usable for data.
   /// This is 7 on 32-bit and 15 on 64-bit.
@@ -75,6 +114,12 @@ impl RawYarn {
   pub const STATIC: u8 = 0b01;
   /// The tag for a yarn that points to a dynamic string slice, on the heap,
   /// that we uniquely own.
+  ///
+  /// The allocation begins with a header that records its capacity; the
+  /// pointer points just past it. See [`RawYarn::header_len()`].
+  ///
+  /// If the [`RawYarn::SHARED`] bit is set, the yarn instead points to the data
+  /// of an `Arc<[u8]>`, and holds one of its reference counts.
   pub const HEAP: u8 = 0b10;
   /// The tag for a yarn that points to a dynamic string slice we don't
   /// uniquely own.
@@ -90,6 +135,9 @@ impl RawYarn {
   const MASK8: usize = !0 << Self::SHIFT8;
   const MASK: usize = !0 << Self::SHIFT;
 
+  /// The bit just below the tag, which marks a `HEAP` yarn as shared.
+  const SHARED: usize = 1 << (Self::SHIFT - 1);
+
   /// Returns the kind of yarn this is (one of the constants above).
   #[inline(always)]
   pub const fn kind(&self) -> u8 {
@@ -106,7 +154,7 @@ impl RawYarn {
     // But LLVM apparently upgrades this to a word-aligned load (i.e. the code
     // below) regardless. :D
 
-    (self.len.get() >> Self::SHIFT) as u8
+    (usize::from_le(self.len.get()) >> Self::SHIFT) as u8
   }
 
   /// Creates a new, non-`SMALL` yarn with the given pointer, length, and tag.
@@ -116,8 +164,8 @@ impl RawYarn {
   /// `ptr` must be valid for reading `len` bytes.
   ///
   /// If tag is `STATIC`, then `ptr` must never be deallocated. If the tag is
-  /// `HEAP`, `ptr` must be free-able via dealloc with a (len, 1) layout and
-  /// valid for writing `len` bytes.
+  /// `HEAP`, `ptr` must point just past the header of an allocation made by
+  /// [`RawYarn::alloc_heap()`], and be valid for writing `len` bytes.
   #[inline(always)]
   pub const unsafe fn from_ptr_len_tag(
     ptr: *const u8,
@@ -125,8 +173,8 @@ impl RawYarn {
     tag: u8,
   ) -> Self {
     assert!(
-      len < usize::MAX / 4,
-      "yarns cannot be larger than a quarter of the address space"
+      len < usize::MAX / 8,
+      "yarns cannot be larger than an eighth of the address space"
     );
     debug_assert!(
       tag != 0 || len != 0,
@@ -136,7 +184,9 @@ impl RawYarn {
 
     Self {
       ptr,
-      len: NonZeroUsize::new_unchecked(len | (tag as usize) << Self::SHIFT),
+      len: NonZeroUsize::new_unchecked(
+        (len | (tag as usize) << Self::SHIFT).to_le(),
+      ),
     }
   }
 
@@ -172,7 +222,7 @@ impl RawYarn {
 
   /// Returns a reference to an empty `RawYarn` of any lifetime.
   #[inline]
-  pub fn empty<'a>() -> &'a RawYarn {
+  pub const fn empty<'a>() -> &'a RawYarn {
     static STORAGE: MaybeUninit<RawYarn> = MaybeUninit::new(RawYarn::new(b""));
     unsafe {
       // SAFETY: MaybeUninit::new() creates well-initialized memory.
@@ -180,6 +230,44 @@ impl RawYarn {
     }
   }
 
+  /// Returns a reference to an empty `RawYarn` whose data pointer is aligned
+  /// to `align`.
+  ///
+  /// Data that is more aligned than a pointer cannot be inlined, so this
+  /// returns a dangling `STATIC` yarn for such alignments instead.
+  #[inline]
+  pub const fn empty_aligned<'a>(align: usize) -> &'a RawYarn {
+    static DANGLING: [RawYarn; usize::BITS as usize] = {
+      let mut yarns = [RawYarn::new(b""); usize::BITS as usize];
+      let mut i = 0;
+      while i < yarns.len() {
+        yarns[i] = unsafe {
+          // SAFETY: A dangling, well-aligned pointer is valid for reading zero
+          // bytes, forever.
+          RawYarn::from_ptr_len_tag(
+            ptr::null::<u8>().wrapping_add(1 << i),
+            0,
+            RawYarn::STATIC,
+          )
+        };
+        i += 1;
+      }
+      yarns
+    };
+
+    if align <= mem::align_of::<Self>() {
+      return Self::empty();
+    }
+    &DANGLING[align.trailing_zeros() as usize]
+  }
+
+  /// Returns whether data with the given layout can be stored in a `SMALL`
+  /// yarn.
+  #[inline(always)]
+  const fn can_inline(layout: alloc::Layout) -> bool {
+    layout.size() <= Self::SSO_LEN && layout.align() <= mem::align_of::<Self>()
+  }
+
   /// Returns a `RawYarn` pointing to the given static string, without copying.
   #[inline]
   pub const fn new(s: &'static [u8]) -> Self {
@@ -201,7 +289,7 @@ impl RawYarn {
   pub const fn len(self) -> usize {
     match self.layout() {
       Layout::Small(s) => s.len as usize & !Self::MASK8,
-      Layout::Slice(s) => s.len & !Self::MASK,
+      Layout::Slice(s) => usize::from_le(s.len) & !(Self::MASK | Self::SHARED),
     }
   }
 
@@ -218,6 +306,20 @@ impl RawYarn {
     self.kind() == Self::SMALL
   }
 
+  /// Returns whether this `RawYarn` is a `HEAP` yarn that shares its buffer
+  /// with an `Arc<[u8]>`.
+  #[inline(always)]
+  pub const fn is_shared(self) -> bool {
+    self.on_heap() && usize::from_le(self.len.get()) & Self::SHARED != 0
+  }
+
+  /// Returns whether this `RawYarn`'s data can be mutated, i.e., whether it is
+  /// inlined or a `HEAP` yarn that is not shared.
+  #[inline(always)]
+  pub const fn is_unique(self) -> bool {
+    self.is_small() || (self.on_heap() && !self.is_shared())
+  }
+
   /// Returns whether this `RawYarn` is SSO.
   #[inline(always)]
   pub const fn is_immortal(self) -> bool {
@@ -229,15 +331,445 @@ impl RawYarn {
   /// # Safety
   ///
   /// This function must be called at most once, when the raw yarn is being
-  /// disposed of.
+  /// disposed of. `layout.align()` must be the alignment this yarn was
+  /// allocated with, and if it is unshared and on the heap, `alloc` must be
+  /// the allocator it was allocated with.
   #[inline(always)]
-  pub unsafe fn destroy(self, layout: alloc::Layout) {
+  pub unsafe fn destroy_in(self, alloc: &impl RawAlloc, layout: alloc::Layout) {
     if !self.on_heap() {
       return;
     }
+    if self.is_shared() {
+      self.release_shared();
+      return;
+    }
+
+    let align = layout.align();
+    alloc.dealloc(
+      self.heap_base(align),
+      Self::heap_layout(self.heap_capacity(align), align),
+    )
+  }
+
+  /// Returns a shared `HEAP` yarn that takes over the reference count held by
+  /// `arc`.
+  ///
+  /// If `arc` is small enough to be inlined, its contents are copied instead.
+  pub fn from_arc(arc: Arc<[u8]>) -> Self {
+    let layout = alloc::Layout::for_value(&*arc);
+    if let Some(inlined) = Self::from_slice_inlined(layout, arc.as_ptr()) {
+      return inlined;
+    }
+
+    let len = arc.len();
+    let ptr = Arc::into_raw(arc).cast::<u8>();
+    unsafe {
+      // SAFETY: ptr points to the data of an Arc, which we now hold a
+      // reference count for. The SHARED bit is below the tag bits, and is not
+      // otherwise used by from_ptr_len_tag, because len < usize::MAX / 8.
+      let raw = Self::from_ptr_len_tag(ptr, len, Self::HEAP);
+      Self {
+        ptr,
+        len: NonZeroUsize::new_unchecked(raw.len.get() | Self::SHARED.to_le()),
+      }
+    }
+  }
+
+  /// Returns a copy of a shared yarn, incrementing its reference count.
+  ///
+  /// # Safety
+  ///
+  /// `self` must be shared.
+  #[inline]
+  pub unsafe fn share(self) -> Self {
+    debug_assert!(self.is_shared());
+    Arc::increment_strong_count(self.as_arc_ptr());
+    self
+  }
+
+  /// Gives up the reference count held by this yarn, if it is shared.
+  ///
+  /// # Safety
+  ///
+  /// This must be called at most once for each reference count held.
+  #[inline]
+  unsafe fn release_shared(self) {
+    if self.is_shared() {
+      drop(Arc::from_raw(self.as_arc_ptr()));
+    }
+  }
+
+  /// Returns the pointer that `Arc::into_raw()` produced for a shared yarn.
+  const fn as_arc_ptr(self) -> *const [u8] {
+    ptr::slice_from_raw_parts(self.ptr, self.len())
+  }
+
+  /// Returns the number of bytes that precede the data of a `HEAP` yarn
+  /// within its allocation. This is where the capacity is stored.
+  ///
+  /// This is always a multiple of `align`, so that the data that follows is
+  /// correctly aligned.
+  #[inline(always)]
+  const fn header_len(align: usize) -> usize {
+    let len = mem::size_of::<usize>();
+    if align > len {
+      align
+    } else {
+      len
+    }
+  }
+
+  /// Returns the layout of the allocation backing a `HEAP` yarn with the
+  /// given capacity and alignment.
+  ///
+  /// Panics if such a layout would overflow.
+  #[inline]
+  fn heap_layout(cap: usize, align: usize) -> alloc::Layout {
+    cap
+      .checked_add(Self::header_len(align))
+      .filter(|&size| size < usize::MAX / 8)
+      .and_then(|size| alloc::Layout::from_size_align(size, align).ok())
+      .expect("capacity overflow")
+  }
+
+  /// Returns a pointer to the start of a `HEAP` yarn's allocation.
+  ///
+  /// # Safety
+  ///
+  /// `self` must be `HEAP` and not shared, and `align` must be the alignment it
+  /// was allocated with.
+  #[inline(always)]
+  unsafe fn heap_base(self, align: usize) -> *mut u8 {
+    debug_assert!(self.on_heap() && !self.is_shared());
+    self.ptr.sub(Self::header_len(align)).cast_mut()
+  }
+
+  /// Returns the capacity of a `HEAP` yarn, in bytes.
+  ///
+  /// # Safety
+  ///
+  /// `self` must be `HEAP` and not shared, and `align` must be the alignment it
+  /// was allocated with.
+  #[inline(always)]
+  unsafe fn heap_capacity(self, align: usize) -> usize {
+    // The header is only as aligned as the data, which may be less aligned
+    // than a usize.
+    self.heap_base(align).cast::<usize>().read_unaligned()
+  }
+
+  /// Allocates a new `HEAP` yarn with room for `cap` bytes, containing the
+  /// concatenation of `slices`.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be a power of two, and `slices` must yield at most `cap`
+  /// bytes.
+  unsafe fn alloc_heap<'a>(
+    align: usize,
+    cap: usize,
+    slices: impl IntoIterator<Item = &'a [u8]>,
+  ) -> Self {
+    Self::alloc_heap_in(&Global, align, cap, slices)
+  }
+
+  /// Like [`RawYarn::alloc_heap()`], but allocates with `alloc`.
+  ///
+  /// # Safety
+  ///
+  /// See [`RawYarn::alloc_heap()`].
+  unsafe fn alloc_heap_in<'a>(
+    alloc: &impl RawAlloc,
+    align: usize,
+    cap: usize,
+    slices: impl IntoIterator<Item = &'a [u8]>,
+  ) -> Self {
+    let layout = Self::heap_layout(cap, align);
+    let base = alloc.alloc(layout);
+    if base.is_null() {
+      alloc::handle_alloc_error(layout);
+    }
+    base.cast::<usize>().write_unaligned(cap);
+
+    let ptr = base.add(Self::header_len(align));
+    let mut len = 0;
+    for slice in slices {
+      debug_assert!(len + slice.len() <= cap);
+      ptr
+        .add(len)
+        .copy_from_nonoverlapping(slice.as_ptr(), slice.len());
+      len += slice.len();
+    }
+
+    Self::from_ptr_len_tag(ptr, len, Self::HEAP)
+  }
+
+  /// Resizes the allocation of a `HEAP` yarn to have the given capacity.
+  ///
+  /// # Safety
+  ///
+  /// `self` must be `HEAP` and not shared, `alloc` and `align` must be the
+  /// allocator and alignment it was allocated with, and `cap` must be at least
+  /// `self.len()`.
+  unsafe fn realloc_heap_in(
+    &mut self,
+    alloc: &impl RawAlloc,
+    align: usize,
+    cap: usize,
+  ) {
+    let len = self.len();
+    debug_assert!(len <= cap);
+
+    let old = Self::heap_layout(self.heap_capacity(align), align);
+    let new = Self::heap_layout(cap, align);
+    let base = alloc.realloc(self.heap_base(align), old, new.size());
+    if base.is_null() {
+      alloc::handle_alloc_error(new);
+    }
+    base.cast::<usize>().write_unaligned(cap);
+
+    *self = Self::from_ptr_len_tag(
+      base.add(Self::header_len(align)),
+      len,
+      Self::HEAP,
+    );
+  }
+
+  /// Returns a new, empty yarn that can hold `cap` bytes without
+  /// reallocating.
+  ///
+  /// This only allocates if `cap` is too large to be inlined.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be a power of two.
+  pub unsafe fn with_capacity(align: usize, cap: usize) -> Self {
+    Self::with_capacity_in(&Global, align, cap)
+  }
+
+  /// Like [`RawYarn::with_capacity()`], but allocates with `alloc`.
+  ///
+  /// # Safety
+  ///
+  /// See [`RawYarn::with_capacity()`].
+  pub unsafe fn with_capacity_in(
+    alloc: &impl RawAlloc,
+    align: usize,
+    cap: usize,
+  ) -> Self {
+    let layout = alloc::Layout::from_size_align_unchecked(cap, align);
+    if Self::can_inline(layout) {
+      return *Self::empty();
+    }
+
+    Self::alloc_heap_in(alloc, align, cap, [])
+  }
+
+  /// Returns the number of bytes this yarn can hold without reallocating.
+  ///
+  /// This is `SSO_LEN` for inlined yarns, and just the length for yarns that
+  /// do not uniquely own their buffer.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be the alignment of this yarn's data.
+  pub unsafe fn capacity(self, align: usize) -> usize {
+    match self.kind() {
+      Self::SMALL => Self::SSO_LEN,
+      Self::HEAP if !self.is_shared() => self.heap_capacity(align),
+      _ => self.len(),
+    }
+  }
+
+  /// Ensures that at least `additional` more bytes can be appended to this
+  /// yarn without reallocating.
+  ///
+  /// Yarns that do not uniquely own their buffer are copied onto the heap if
+  /// the requested capacity does not fit inline.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be the alignment of this yarn's data.
+  pub unsafe fn reserve(&mut self, align: usize, additional: usize) {
+    self.reserve_in(&Global, align, additional)
+  }
+
+  /// Like [`RawYarn::reserve()`], but for a yarn allocated with `alloc`.
+  ///
+  /// # Safety
+  ///
+  /// See [`RawYarn::reserve()`]. If this yarn is unshared and on the heap,
+  /// `alloc` must be the allocator it was allocated with.
+  pub unsafe fn reserve_in(
+    &mut self,
+    alloc: &impl RawAlloc,
+    align: usize,
+    additional: usize,
+  ) {
+    let len = self.len();
+    let needed = len.checked_add(additional).expect("capacity overflow");
+
+    if self.is_unique() && !self.is_small() {
+      let cap = self.heap_capacity(align);
+      if needed > cap {
+        self.realloc_heap_in(alloc, align, needed.max(cap.saturating_mul(2)));
+      }
+      return;
+    }
 
-    debug_assert!(layout.size() > 0);
-    alloc::dealloc(self.ptr as *mut u8, layout)
+    // Anything that fits inline can be appended without allocating.
+    let layout = alloc::Layout::from_size_align_unchecked(needed, align);
+    if Self::can_inline(layout) {
+      return;
+    }
+
+    let old = *self;
+    *self = Self::alloc_heap_in(alloc, align, needed, [old.as_slice()]);
+    old.release_shared();
+  }
+
+  /// Appends `data` to the end of this yarn.
+  ///
+  /// Unshared `HEAP` yarns are grown in place; any other yarn is copied into a
+  /// buffer it owns first, which is inlined if the result is small enough.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be the alignment of this yarn's data, and appending `data`
+  /// must produce a valid value of whatever type this yarn holds.
+  pub unsafe fn append(&mut self, align: usize, data: &[u8]) {
+    self.append_in(&Global, align, data)
+  }
+
+  /// Like [`RawYarn::append()`], but for a yarn allocated with `alloc`.
+  ///
+  /// # Safety
+  ///
+  /// See [`RawYarn::append()`]. If this yarn is unshared and on the heap,
+  /// `alloc` must be the allocator it was allocated with.
+  pub unsafe fn append_in(
+    &mut self,
+    alloc: &impl RawAlloc,
+    align: usize,
+    data: &[u8],
+  ) {
+    if data.is_empty() {
+      return;
+    }
+
+    let len = self.len();
+    let new_len = len.checked_add(data.len()).expect("capacity overflow");
+
+    if self.is_unique() && !self.is_small() {
+      self.reserve_in(alloc, align, data.len());
+      self
+        .as_mut_ptr()
+        .add(len)
+        .copy_from_nonoverlapping(data.as_ptr(), data.len());
+      *self = Self::from_ptr_len_tag(self.ptr, new_len, Self::HEAP);
+      return;
+    }
+
+    // Not uniquely owned (or inlined), so we need a fresh copy.
+    let old = *self;
+    *self = if new_len <= Self::SSO_LEN {
+      Self::concat_in(
+        alloc,
+        alloc::Layout::from_size_align_unchecked(new_len, align),
+        [old.as_slice(), data],
+      )
+    } else {
+      Self::alloc_heap_in(
+        alloc,
+        align,
+        new_len.max(len * 2),
+        [old.as_slice(), data],
+      )
+    };
+    old.release_shared();
+  }
+
+  /// Shortens this yarn to its first `len` bytes.
+  ///
+  /// Unshared `HEAP` yarns keep their allocation, so that it can be reused by
+  /// later appends. `STATIC` and `ALIASED` yarns become a shorter alias of the
+  /// same buffer, and are only copied once they are appended to. Shared yarns
+  /// are copied, since the rest of the `Arc` cannot be kept alive by a prefix.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be the alignment of this yarn's data, and `len` must be at
+  /// most `self.len()` and leave a valid value of whatever type this yarn
+  /// holds.
+  pub unsafe fn truncate(&mut self, align: usize, len: usize) {
+    self.truncate_in(&Global, align, len)
+  }
+
+  /// Like [`RawYarn::truncate()`], but for a yarn allocated with `alloc`.
+  ///
+  /// # Safety
+  ///
+  /// See [`RawYarn::truncate()`]. If this yarn is unshared and on the heap,
+  /// `alloc` must be the allocator it was allocated with.
+  pub unsafe fn truncate_in(
+    &mut self,
+    alloc: &impl RawAlloc,
+    align: usize,
+    len: usize,
+  ) {
+    debug_assert!(len <= self.len());
+    let layout = alloc::Layout::from_size_align_unchecked(len, align);
+
+    match self.kind() {
+      Self::HEAP if !self.is_shared() => {
+        *self = Self::from_ptr_len_tag(self.ptr, len, Self::HEAP);
+      }
+      Self::HEAP => {
+        let old = *self;
+        *self = Self::copy_slice_in(alloc, layout, old.ptr);
+        old.release_shared();
+      }
+      // Smaller data always fits inline, so this never allocates.
+      Self::SMALL => *self = Self::copy_slice_in(alloc, layout, self.as_ptr()),
+      _ => *self = self.slice(align, 0..len),
+    }
+  }
+
+  /// Shrinks the buffer of an unshared `HEAP` yarn to fit its data exactly.
+  ///
+  /// If the data fits inline, it is moved inline and the buffer is freed.
+  /// Any other yarn is left unchanged.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be the alignment of this yarn's data.
+  pub unsafe fn shrink_to_fit(&mut self, align: usize) {
+    self.shrink_to_fit_in(&Global, align)
+  }
+
+  /// Like [`RawYarn::shrink_to_fit()`], but for a yarn allocated with `alloc`.
+  ///
+  /// # Safety
+  ///
+  /// See [`RawYarn::shrink_to_fit()`]. If this yarn is unshared and on the
+  /// heap, `alloc` must be the allocator it was allocated with.
+  pub unsafe fn shrink_to_fit_in(
+    &mut self,
+    alloc: &impl RawAlloc,
+    align: usize,
+  ) {
+    if !self.on_heap() || self.is_shared() {
+      return;
+    }
+
+    let len = self.len();
+    let layout = alloc::Layout::from_size_align_unchecked(len, align);
+    if let Some(inlined) = Self::from_slice_inlined(layout, self.ptr) {
+      let old = *self;
+      *self = inlined;
+      old.destroy_in(alloc, layout);
+    } else if self.heap_capacity(align) > len {
+      self.realloc_heap_in(alloc, align, len);
+    }
   }
 
   /// Returns a pointer into the data for this raw yarn.
@@ -271,10 +803,10 @@ impl RawYarn {
   ///
   /// # Safety
   ///
-  /// This must only be called on `SMALL` or `HEAP` yarns.
+  /// This must only be called on `SMALL` or unshared `HEAP` yarns.
   #[inline(always)]
   pub unsafe fn as_mut_slice(&mut self) -> &mut [u8] {
-    debug_assert!(self.is_small() || self.on_heap());
+    debug_assert!(self.is_unique());
     unsafe {
       // SAFETY: the output lifetime ensures that `self` cannot move away.
       slice::from_raw_parts_mut(self.as_mut_ptr(), self.len())
@@ -285,12 +817,51 @@ impl RawYarn {
   ///
   /// # Safety
   ///
-  /// `align` must be a power of two.
+  /// `ptr` must be valid for reading `layout.size()` bytes.
   #[inline(always)]
   pub unsafe fn copy_slice(layout: alloc::Layout, ptr: *const u8) -> Self {
+    Self::copy_slice_in(&Global, layout, ptr)
+  }
+
+  /// Like [`RawYarn::copy_slice()`], but allocates with `alloc`.
+  ///
+  /// # Safety
+  ///
+  /// See [`RawYarn::copy_slice()`].
+  #[inline(always)]
+  pub unsafe fn copy_slice_in(
+    alloc: &impl RawAlloc,
+    layout: alloc::Layout,
+    ptr: *const u8,
+  ) -> Self {
     match Self::from_slice_inlined(layout, ptr) {
       Some(inl) => inl,
-      None => Self::from_heap(AlignedBox::new(layout, ptr)),
+      None => Self::alloc_heap_in(
+        alloc,
+        layout.align(),
+        layout.size(),
+        [slice::from_raw_parts(ptr, layout.size())],
+      ),
+    }
+  }
+
+  /// Returns a copy of this yarn, for cloning an owning yarn.
+  ///
+  /// Shared yarns get a new reference count, and unshared `HEAP` yarns are
+  /// copied, with `alloc`; anything else is copied bitwise.
+  ///
+  /// # Safety
+  ///
+  /// `layout` must be the layout of this yarn's data.
+  pub unsafe fn clone_in(
+    &self,
+    alloc: &impl RawAlloc,
+    layout: alloc::Layout,
+  ) -> Self {
+    match self.kind() {
+      Self::HEAP if self.is_shared() => self.share(),
+      Self::HEAP => Self::copy_slice_in(alloc, layout, self.ptr),
+      _ => *self,
     }
   }
 
@@ -311,6 +882,32 @@ impl RawYarn {
     Self::from_ptr_len_tag(ptr, layout.size(), Self::ALIASED)
   }
 
+  /// Returns a `RawYarn` for the bytes of this yarn in `range`.
+  ///
+  /// The result is inlined if it is small enough; otherwise, it points into
+  /// this yarn's buffer, remembering whether that buffer is static.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be the alignment of this yarn's data, and `range` must not
+  /// split any of its elements. The returned yarn must not outlive the buffer
+  /// this yarn points to.
+  pub unsafe fn slice(&self, align: usize, range: Range<usize>) -> Self {
+    let bytes = &self.as_slice()[range];
+    let layout = alloc::Layout::from_size_align_unchecked(bytes.len(), align);
+    if let Some(inlined) = Self::from_slice_inlined(layout, bytes.as_ptr()) {
+      return inlined;
+    }
+
+    // Inlined yarns can never produce a non-inlined slice, so this does not
+    // point into `self`.
+    let tag = match self.kind() {
+      Self::STATIC => Self::STATIC,
+      _ => Self::ALIASED,
+    };
+    Self::from_ptr_len_tag(bytes.as_ptr(), bytes.len(), tag)
+  }
+
   /// Returns a new `RawYarn` containing the contents of the given slice.
   ///
   /// # Safety
@@ -326,7 +923,7 @@ impl RawYarn {
       // SAFETY: This is a precondition for this function.
       // This allows the compiler to assume len <= Self::SSO_LEN for the rest
       // of the function body.
-      std::hint::unreachable_unchecked();
+      core::hint::unreachable_unchecked();
     }
 
     let tagged_len = (len as u8) | Self::SMALL << Self::SHIFT8;
@@ -335,7 +932,9 @@ impl RawYarn {
     if mem::size_of::<Self>() == 16 {
       // Do binary search on the length of the buffer to construct the shortest
       // instruction sequence for reading `len` little-endian bytes into
-      // `register`, with all higher bytes zeroed.
+      // `register`, with all higher bytes zeroed. The loads are converted from
+      // little-endian, which is a no-op on little-endian targets, so that the
+      // shifts below place each byte in the right position.
       //
       // Regardless of length, this costs three loads if len in 1..4, or two
       // loads otherwise.
@@ -344,16 +943,18 @@ impl RawYarn {
         // eight bytes, which possibly overlap, and then ors them together.
         //
         // This reads between 9 and 15 distinct bytes, total.
-        let x0 = ptr.cast::<u64>().read_unaligned() as u128;
-        let x1 = ptr.add(len - 8).cast::<u64>().read_unaligned() as u128;
+        let x0 = u64::from_le(ptr.cast::<u64>().read_unaligned()) as u128;
+        let x1 =
+          u64::from_le(ptr.add(len - 8).cast::<u64>().read_unaligned()) as u128;
         x0 | (x1 << ((len - 8) * 8))
       } else if len > 3 {
         // SAFETY: This reads the low four bytes of the buffer and the high
         // four bytes, which possibly overlap, and then ors them together.
         //
         // This reads between 4 and 8 distinct bytes, total.
-        let x0 = ptr.cast::<u32>().read_unaligned() as u128;
-        let x1 = ptr.add(len - 4).cast::<u32>().read_unaligned() as u128;
+        let x0 = u32::from_le(ptr.cast::<u32>().read_unaligned()) as u128;
+        let x1 =
+          u32::from_le(ptr.add(len - 4).cast::<u32>().read_unaligned()) as u128;
         x0 | (x1 << ((len - 4) * 8))
       } else if len > 0 {
         // SAFETY: This code runs when len is 1, 2, or 3, in which case these
@@ -373,9 +974,10 @@ impl RawYarn {
         0
       };
 
-      // SAFETY: size_of<u128> == size_of<Small>.
+      // SAFETY: size_of<u128> == size_of<Small>, and Small has alignment 1.
       // Unfortunately, transmute_copy() is not const as of writing.
-      let mut small = (&register as *const u128).cast::<Small>().read();
+      let bytes = register.to_le_bytes();
+      let mut small = (&bytes as *const [u8; 16]).cast::<Small>().read();
       small.len = tagged_len;
 
       return mem::transmute::<Small, RawYarn>(small);
@@ -401,18 +1003,18 @@ impl RawYarn {
 
   /// Returns a new `RawYarn` containing the contents of the given slice.
   ///
-  /// This function will always return an inlined string.
+  /// This function will always return an inlined string, or an empty one.
+  /// Returns `None` if the slice is too large, or too aligned, to be inlined.
   #[inline]
   pub const fn from_slice_inlined(
     layout: alloc::Layout,
     ptr: *const u8,
   ) -> Option<Self> {
-    assert!(
-      layout.align() <= mem::align_of::<Self>(),
-      "cannot store types with alignment greater than a pointer in a Yarn"
-    );
+    if layout.size() == 0 {
+      return Some(*Self::empty_aligned(layout.align()));
+    }
 
-    if layout.size() > Self::SSO_LEN {
+    if !Self::can_inline(layout) {
       return None;
     }
 
@@ -449,13 +1051,30 @@ impl RawYarn {
   ///
   /// # Safety
   ///
-  /// `total_len < Self::SSO_LEN`.
+  /// `iter` must yield exactly `layout.size()` bytes.
   pub unsafe fn concat<'a>(
     layout: alloc::Layout,
     iter: impl IntoIterator<Item = &'a [u8]>,
   ) -> Self {
-    if layout.size() > Self::SSO_LEN {
-      return Self::from_heap(AlignedBox::concat(layout, iter));
+    Self::concat_in(&Global, layout, iter)
+  }
+
+  /// Like [`RawYarn::concat()`], but allocates with `alloc`.
+  ///
+  /// # Safety
+  ///
+  /// See [`RawYarn::concat()`].
+  pub unsafe fn concat_in<'a>(
+    alloc: &impl RawAlloc,
+    layout: alloc::Layout,
+    iter: impl IntoIterator<Item = &'a [u8]>,
+  ) -> Self {
+    if layout.size() == 0 {
+      return *Self::empty_aligned(layout.align());
+    }
+
+    if !Self::can_inline(layout) {
+      return Self::alloc_heap_in(alloc, layout.align(), layout.size(), iter);
     }
 
     let mut cursor = 0;
@@ -468,6 +1087,43 @@ impl RawYarn {
     Self::from_slice_inlined(layout, data[..cursor].as_ptr()).unwrap_unchecked()
   }
 
+  /// Returns a `RawYarn` consisting of `data` repeated `n` times.
+  ///
+  /// Does not allocate if the result can be inlined; otherwise, performs
+  /// exactly one allocation. Panics if the length of the result overflows.
+  ///
+  /// # Safety
+  ///
+  /// `align` must be a power of two.
+  pub unsafe fn repeat(align: usize, data: &[u8], n: usize) -> Self {
+    let len = data.len().checked_mul(n).expect("capacity overflow");
+    if len == 0 {
+      return *Self::empty_aligned(align);
+    }
+
+    let layout = alloc::Layout::from_size_align_unchecked(len, align);
+    if Self::can_inline(layout) {
+      let mut buf = [0; Self::SSO_LEN];
+      for chunk in buf[..len].chunks_exact_mut(data.len()) {
+        chunk.copy_from_slice(data);
+      }
+      return Self::from_slice_inlined_unchecked(buf.as_ptr(), len);
+    }
+
+    let mut yarn = Self::alloc_heap(align, len, [data]);
+    let ptr = yarn.as_mut_ptr();
+
+    // Fill the rest of the buffer by repeatedly doubling what's already there.
+    let mut filled = data.len();
+    while filled < len {
+      let count = filled.min(len - filled);
+      ptr.add(filled).copy_from_nonoverlapping(ptr, count);
+      filled += count;
+    }
+
+    Self::from_ptr_len_tag(ptr, len, Self::HEAP)
+  }
+
   /// Returns a `RawYarn` by taking ownership of the given allocation.
   #[inline]
   pub fn from_heap(s: AlignedBox) -> Self {
@@ -477,63 +1133,165 @@ impl RawYarn {
       return inline;
     }
 
+    let layout = s.layout();
+    let align = layout.align();
     let (ptr, len) = s.into_raw_parts();
     unsafe {
-      // SAFETY: s is a heap allocation of the appropriate layout for HEAP,
-      // which we own uniquely because we dismantled it from a box.
+      // SAFETY: s is a non-empty allocation with the given layout, which we
+      // own uniquely because we dismantled it from a box. We grow it to make
+      // room for the header, and then shift the data past it.
+      let new = Self::heap_layout(len, align);
+      let base = alloc::realloc(ptr, layout, new.size());
+      if base.is_null() {
+        alloc::handle_alloc_error(new);
+      }
+
+      let ptr = base.add(Self::header_len(align));
+      ptr::copy(base, ptr, len);
+      base.cast::<usize>().write_unaligned(len);
+
       Self::from_ptr_len_tag(ptr, len, Self::HEAP)
     }
   }
 
-  /// Builds a new yarn from the given formatting arguments, without allocating
-  /// in the trival and small cases.
-  pub fn from_fmt_args(args: fmt::Arguments) -> Self {
-    if let Some(constant) = args.as_str() {
-      return Self::new(constant.as_bytes());
+  /// Converts a `HEAP` yarn back into an allocation that contains exactly its
+  /// data, the inverse of [`RawYarn::from_heap()`].
+  ///
+  /// # Safety
+  ///
+  /// `self` must be `HEAP` and not shared, with at least one byte of data, and
+  /// `align` must be the alignment it was allocated with.
+  pub unsafe fn into_heap(self, align: usize) -> AlignedBox {
+    let len = self.len();
+    debug_assert!(len > 0);
+
+    let base = self.heap_base(align);
+    let layout = Self::heap_layout(self.heap_capacity(align), align);
+    ptr::copy(self.ptr, base, len);
+
+    let ptr = alloc::realloc(base, layout, len);
+    if ptr.is_null() {
+      alloc::handle_alloc_error(alloc::Layout::from_size_align_unchecked(
+        len, align,
+      ));
     }
 
-    enum Buf {
-      Sso(usize, [u8; RawYarn::SSO_LEN]),
-      Vec(Vec<u8>),
+    AlignedBox {
+      data: Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)),
+      align,
     }
-    impl fmt::Write for Buf {
-      fn write_str(&mut self, s: &str) -> fmt::Result {
-        match self {
-          Self::Sso(len, bytes) => {
-            let new_len = *len + s.len();
-            if new_len > RawYarn::SSO_LEN {
-              let mut vec = Vec::from(&bytes[..*len]);
-              vec.extend_from_slice(s.as_bytes());
+  }
 
-              *self = Self::Vec(vec);
-            } else {
-              let _ = &bytes[*len..new_len].copy_from_slice(s.as_bytes());
-              *len = new_len;
-            }
-          }
-          Self::Vec(vec) => vec.extend_from_slice(s.as_bytes()),
-        }
+  /// Returns the size of the allocation backing a `HEAP` yarn, in bytes,
+  /// including its header.
+  ///
+  /// # Safety
+  ///
+  /// `self` must be `HEAP` and not shared, and `align` must be the alignment it
+  /// was allocated with.
+  pub unsafe fn heap_size(self, align: usize) -> usize {
+    self.heap_capacity(align) + Self::header_len(align)
+  }
 
-        Ok(())
-      }
-    }
+  /// Converts a `HEAP` yarn into the start of its allocation, with its data
+  /// moved to the front, without reallocating.
+  ///
+  /// The allocation has the layout `(self.heap_size(align), align)`, so the
+  /// returned pointer and length may be used as the parts of a vector with a
+  /// capacity of that many bytes.
+  ///
+  /// # Safety
+  ///
+  /// `self` must be `HEAP` and not shared, and `align` must be the alignment it
+  /// was allocated with.
+  pub unsafe fn into_heap_front(self, align: usize) -> *mut u8 {
+    let base = self.heap_base(align);
+    ptr::copy(self.ptr, base, self.len());
+    base
+  }
+}
 
-    let mut w = Buf::Sso(0, [0; RawYarn::SSO_LEN]);
-    let _ = w.write_fmt(args);
-    match w {
-      Buf::Sso(len, bytes) => {
-        let chunk = &bytes[..len];
-        Self::from_slice_inlined(
-          alloc::Layout::for_value(chunk),
-          chunk.as_ptr(),
-        )
-        .unwrap()
-      }
-      Buf::Vec(vec) => Self::from_heap(unsafe {
-        // SAFETY: 1 == 2^0.
-        AlignedBox::from_vec(1, vec)
-      }),
-    }
+/// An allocator that `HEAP` yarns can be allocated with.
+///
+/// With the `allocator_api` feature, this is implemented for every
+/// `Allocator`; otherwise, it is only implemented for [`Global`].
+///
+/// # Safety
+///
+/// Each function must behave like the function of the same name in the
+/// `alloc::alloc` module.
+pub unsafe trait RawAlloc {
+  /// Allocates memory with the given layout, which must not be zero-sized.
+  unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8;
+
+  /// Frees memory allocated by this allocator with the given layout.
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout);
+
+  /// Resizes memory allocated by this allocator with the given layout.
+  unsafe fn realloc(
+    &self,
+    ptr: *mut u8,
+    layout: alloc::Layout,
+    new_size: usize,
+  ) -> *mut u8;
+}
+
+#[cfg(feature = "allocator_api")]
+pub use alloc::Allocator;
+#[cfg(feature = "allocator_api")]
+pub use alloc::Global;
+
+/// The global allocator.
+#[cfg(not(feature = "allocator_api"))]
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Global;
+
+#[cfg(not(feature = "allocator_api"))]
+unsafe impl RawAlloc for Global {
+  unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
+    alloc::alloc(layout)
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
+    alloc::dealloc(ptr, layout)
+  }
+
+  unsafe fn realloc(
+    &self,
+    ptr: *mut u8,
+    layout: alloc::Layout,
+    new_size: usize,
+  ) -> *mut u8 {
+    alloc::realloc(ptr, layout, new_size)
+  }
+}
+
+#[cfg(feature = "allocator_api")]
+unsafe impl<A: alloc::Allocator> RawAlloc for A {
+  unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
+    self
+      .allocate(layout)
+      .map_or(ptr::null_mut(), |p| p.as_ptr().cast())
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
+    self.deallocate(ptr::NonNull::new_unchecked(ptr), layout)
+  }
+
+  unsafe fn realloc(
+    &self,
+    ptr: *mut u8,
+    layout: alloc::Layout,
+    new_size: usize,
+  ) -> *mut u8 {
+    let ptr = ptr::NonNull::new_unchecked(ptr);
+    let new =
+      alloc::Layout::from_size_align_unchecked(new_size, layout.align());
+    let result = match new_size >= layout.size() {
+      true => self.grow(ptr, layout, new),
+      false => self.shrink(ptr, layout, new),
+    };
+    result.map_or(ptr::null_mut(), |p| p.as_ptr().cast())
   }
 }
 
@@ -557,46 +1315,6 @@ impl<T: ?Sized> From<Box<T>> for AlignedBox {
 }
 
 impl AlignedBox {
-  // SAFETY: `data` must have the given layout.
-  unsafe fn new(layout: alloc::Layout, data: *const u8) -> Self {
-    Self::concat(layout, [slice::from_raw_parts(data, layout.size())])
-  }
-
-  // SAFETY: `align` must be a power of two.
-  unsafe fn from_vec(align: usize, data: Vec<u8>) -> Self {
-    if align == 1 {
-      return Self { data: data.into(), align };
-    }
-
-    Self::new(
-      alloc::Layout::from_size_align_unchecked(data.len(), align),
-      data.as_ptr(),
-    )
-  }
-
-  // SAFETY: `data` must yield enough data to actually fill out `layout`.
-  unsafe fn concat<'a>(
-    layout: alloc::Layout,
-    slices: impl IntoIterator<Item = &'a [u8]>,
-  ) -> Self {
-    let mut ptr = alloc::alloc(layout);
-    if ptr.is_null() {
-      alloc::handle_alloc_error(layout);
-    }
-
-    for slice in slices {
-      ptr.copy_from_nonoverlapping(slice.as_ptr(), slice.len());
-      ptr = ptr.add(slice.len());
-    }
-    ptr = ptr.sub(layout.size());
-
-    let ptr = ptr::slice_from_raw_parts_mut(ptr, layout.size());
-    Self {
-      data: Box::from_raw(ptr),
-      align: layout.align(),
-    }
-  }
-
   fn layout(&self) -> alloc::Layout {
     unsafe {
       // SAFETY: `self.align` is a power of 2.
@@ -608,7 +1326,7 @@ impl AlignedBox {
     self.data.as_ref()
   }
 
-  fn into_raw_parts(self) -> (*mut u8, usize) {
+  pub fn into_raw_parts(self) -> (*mut u8, usize) {
     let len = self.data.len();
     let ptr = ManuallyDrop::new(self).data.as_mut_ptr();
     (ptr, len)
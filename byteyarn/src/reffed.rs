@@ -1,15 +1,35 @@
-use std::cmp::Ordering;
-use std::fmt;
-use std::fmt::Write;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::marker::PhantomData;
-use std::mem;
-use std::ops::Deref;
-use std::str;
-use std::str::Utf8Error;
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::ffi::NulError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::ffi::CStr;
+use core::fmt;
+use core::fmt::Write;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::iter;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::Deref;
+use core::ops::Range;
+use core::slice;
+use core::str;
+use core::str::FromStr;
+use core::str::Utf8Error;
 
 use crate::raw::RawYarn;
+use crate::utf8::Escape;
+use crate::CharsLossy;
+use crate::EncodeSet;
+use crate::Lines;
+use crate::MatchIndices;
+use crate::Matches;
+use crate::ParseError;
+use crate::Pattern;
+use crate::PercentError;
+use crate::Split;
 use crate::Utf8Chunks;
 use crate::YarnBox;
 
@@ -64,7 +84,9 @@ where
     unsafe {
       // SAFETY: YarnRef is a transparent wrapper over RawYarn; even though
       // YarnRef has a destructor, this is fine.
-      mem::transmute::<&'b RawYarn, &'b Self>(RawYarn::empty())
+      mem::transmute::<&'b RawYarn, &'b Self>(RawYarn::empty_aligned(
+        mem::align_of::<Buf::Element>(),
+      ))
     }
   }
 
@@ -97,7 +119,8 @@ where
   /// function, since all `YarnRef`-constructing functions will automatically
   /// inline any small strings passed to them.
   ///
-  /// Note that the maximum inlined size is architecture-dependent.
+  /// Note that the maximum inlined size is architecture-dependent; it is
+  /// given by [`YarnRef::inlined_len()`].
   ///
   /// ```
   /// # use byteyarn::*;
@@ -122,6 +145,13 @@ where
     }
   }
 
+  /// Returns the maximum length, in bytes, of a yarn that can be inlined.
+  ///
+  /// See [`YarnBox::inlined_len()`].
+  pub const fn inlined_len() -> usize {
+    RawYarn::SSO_LEN
+  }
+
   /// Returns a yarn containing a single UTF-8-encoded Unicode scalar.
   /// This function does not allocate: every `char` fits in an inlined yarn.
   ///
@@ -138,6 +168,17 @@ where
     }
   }
 
+  /// Returns a yarn containing a single UTF-8-encoded Unicode scalar, given
+  /// as a `u32`.
+  ///
+  /// See [`Yarn::from_u32()`].
+  pub const fn from_u32(c: u32) -> Option<Self> {
+    match char::from_u32(c) {
+      Some(c) => Some(Self::from_char(c)),
+      None => None,
+    }
+  }
+
   /// Checks whether this yarn is empty.
   pub const fn is_empty(self) -> bool {
     self.len() == 0
@@ -148,6 +189,28 @@ where
     self.raw.len()
   }
 
+  /// Returns whether this yarn and `that` point to the exact same bytes of the
+  /// same buffer.
+  ///
+  /// See [`YarnBox::same_allocation()`].
+  pub fn same_allocation(self, that: YarnRef<Buf>) -> bool {
+    !self.raw.is_small()
+      && !that.raw.is_small()
+      && self.as_bytes().as_ptr() == that.as_bytes().as_ptr()
+      && self.len() == that.len()
+  }
+
+  /// Returns a value whose `Debug` implementation shows how this yarn is
+  /// stored, along with its contents.
+  ///
+  /// See [`YarnBox::debug_storage()`].
+  pub fn debug_storage(self) -> StorageDebug<'a, Buf> {
+    unsafe {
+      // SAFETY: self.raw is valid for 'a.
+      StorageDebug::new(self.raw)
+    }
+  }
+
   /// Converts this yarn into a slice.
   pub const fn as_slice(&self) -> &Buf {
     unsafe { buf_trait::as_buf(self.as_bytes()) }
@@ -170,6 +233,13 @@ where
     }
   }
 
+  /// Returns a copy of this yarn that owns its data, with any lifetime.
+  ///
+  /// See [`YarnBox::to_owned_yarn()`].
+  pub fn to_owned_yarn<'b>(self) -> YarnBox<'b, Buf> {
+    self.to_box().to_owned_yarn()
+  }
+
   /// Converts this yarn into a boxed slice by copying it.
   pub fn to_boxed_bytes(self) -> Box<[u8]> {
     self.to_box().into_bytes().into_box()
@@ -216,6 +286,27 @@ where
     }
   }
 
+  /// Returns the byte offset of the first match of `pat` in this yarn.
+  ///
+  /// See [`Yarn::find()`].
+  pub fn find(&self, mut pat: impl Pattern<Buf>) -> Option<usize> {
+    Some(pat.find_in(self.as_slice())?.start)
+  }
+
+  /// Returns the byte offset of the last match of `pat` in this yarn.
+  ///
+  /// See [`Yarn::rfind()`].
+  pub fn rfind(&self, mut pat: impl Pattern<Buf>) -> Option<usize> {
+    Some(pat.rfind_in(self.as_slice())?.start)
+  }
+
+  /// Returns whether `pat` matches anywhere in this yarn.
+  ///
+  /// See [`Yarn::contains()`].
+  pub fn contains(&self, pat: impl Pattern<Buf>) -> bool {
+    self.find(pat).is_some()
+  }
+
   /// Tries to inline this yarn, if it's small enough.
   ///
   /// This operation has no directly visible side effects, and is only intended
@@ -227,6 +318,37 @@ where
     }
   }
 
+  /// Returns the sub-yarn of this yarn that `sub` refers to.
+  ///
+  /// The result is inlined if it is small enough (which it always is if this
+  /// yarn is inlined), so it never points into `self`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `sub` is not a subslice of `self.as_slice()`.
+  pub(crate) fn subyarn(&self, sub: &Buf) -> YarnRef<'a, Buf> {
+    let bytes = self.as_bytes().as_ptr_range();
+    let start = sub as *const Buf as *const u8 as usize;
+    let end = start + sub.byte_len();
+    assert!(
+      bytes.start as usize <= start && end <= bytes.end as usize,
+      "subyarn() argument is not a subslice"
+    );
+
+    let start = start - bytes.start as usize;
+    unsafe {
+      // SAFETY: sub is a subslice, so the range does not split any elements.
+      // The result is either inlined, or points to the same buffer as self,
+      // which lives for 'a.
+      YarnRef::from_raw(
+        self.raw.slice(
+          mem::align_of::<Buf::Element>(),
+          start..start + sub.byte_len(),
+        ),
+      )
+    }
+  }
+
   /// Returns an iterator over the UTF-8 (or otherwise) chunks in this string.
   ///
   /// This iterator is also used for the `Debug` and `Display` formatter
@@ -251,6 +373,20 @@ where
   pub fn utf8_chunks(&self) -> Utf8Chunks {
     Utf8Chunks::new(self.as_bytes())
   }
+
+  /// Returns a copy of this yarn with its characters escaped.
+  ///
+  /// See [`Yarn::escape_default()`].
+  pub fn escape_default(&self) -> YarnBox<'static, str> {
+    YarnBox::from_fmt(format_args!("{}", Escape::default(self.as_bytes())))
+  }
+
+  /// Returns a copy of this yarn with its characters escaped.
+  ///
+  /// See [`Yarn::escape_debug()`].
+  pub fn escape_debug(&self) -> YarnBox<'static, str> {
+    YarnBox::from_fmt(format_args!("{}", Escape::debug(self.as_bytes())))
+  }
 }
 
 impl<Buf> YarnRef<'static, Buf>
@@ -262,6 +398,8 @@ where
   /// static string, meaning that it can be dynamically upcast back to a
   /// `'static` lifetime.
   ///
+  /// Like [`YarnBox::from_static()`], short slices are inlined instead.
+  ///
   /// This function will *not* be found by `From` impls.
   pub const fn from_static(buf: &'static Buf) -> Self {
     let raw = RawYarn::new(buf_trait::as_bytes(buf));
@@ -278,6 +416,97 @@ impl<'a> YarnRef<'a, [u8]> {
     unsafe { Self::from_raw(raw) }
   }
 
+  /// Returns a yarn pointing to the bytes of a C string, without copying.
+  ///
+  /// See [`ByteYarn::from_cstr()`].
+  pub const fn from_cstr(c: &'a CStr) -> Self {
+    Self::new(c.to_bytes())
+  }
+
+  /// Returns a yarn pointing to the bytes of a static C string, without
+  /// copying.
+  ///
+  /// See [`ByteYarn::from_static_cstr()`].
+  pub const fn from_static_cstr(c: &'static CStr) -> Self {
+    YarnRef::from_static(c.to_bytes())
+  }
+
+  /// Copies this yarn into a new C string.
+  ///
+  /// See [`ByteYarn::to_cstring()`].
+  pub fn to_cstring(&self) -> Result<CString, NulError> {
+    CString::new(self.as_bytes())
+  }
+
+  /// Returns a hash of this yarn's contents that is the same on every
+  /// platform.
+  ///
+  /// See [`ByteYarn::stable_hash()`].
+  #[cfg(feature = "fnv")]
+  pub fn stable_hash(&self) -> u64 {
+    crate::fnv::hash(self.as_bytes())
+  }
+
+  /// Returns an iterator over the characters of this yarn, treating it as
+  /// UTF-8.
+  ///
+  /// See [`ByteYarn::chars_lossy()`].
+  pub fn chars_lossy(&self) -> CharsLossy<'_> {
+    CharsLossy::new(self.as_bytes())
+  }
+
+  /// Returns an iterator over the bytes of this yarn.
+  ///
+  /// See [`ByteYarn::bytes()`].
+  pub fn bytes(&self) -> iter::Copied<slice::Iter<'_, u8>> {
+    self.as_slice().iter().copied()
+  }
+
+  /// Returns an iterator over the bytes of this yarn.
+  ///
+  /// See [`ByteYarn::iter()`].
+  pub fn iter(&self) -> iter::Copied<slice::Iter<'_, u8>> {
+    self.bytes()
+  }
+
+  /// Returns an iterator over the bytes of this yarn, back to front.
+  ///
+  /// See [`ByteYarn::rbytes()`].
+  pub fn rbytes(&self) -> iter::Rev<iter::Copied<slice::Iter<'_, u8>>> {
+    self.bytes().rev()
+  }
+
+  /// Returns a copy of this yarn with its bytes in reverse order.
+  ///
+  /// See [`ByteYarn::reverse_bytes()`].
+  pub fn reverse_bytes(&self) -> YarnBox<'static, [u8]> {
+    let mut yarn = YarnBox::<[u8]>::with_capacity(self.len());
+    yarn.extend(self.rbytes());
+    yarn
+  }
+
+  /// Splits this yarn into two at byte offset `mid`.
+  ///
+  /// See [`ByteYarn::split_at()`].
+  pub fn split_at(&self, mid: usize) -> (YarnRef<'a, [u8]>, YarnRef<'a, [u8]>) {
+    let (a, b) = self.as_slice().split_at(mid);
+    (self.subyarn(a), self.subyarn(b))
+  }
+
+  /// Checks whether this yarn is equal to `that`, ignoring ASCII case.
+  ///
+  /// See [`ByteYarn::eq_ignore_ascii_case()`].
+  pub fn eq_ignore_ascii_case(&self, that: impl AsRef<[u8]>) -> bool {
+    self.as_bytes().eq_ignore_ascii_case(that.as_ref())
+  }
+
+  /// Compares this yarn with `that` lexicographically, ignoring ASCII case.
+  ///
+  /// See [`ByteYarn::cmp_ignore_ascii_case()`].
+  pub fn cmp_ignore_ascii_case(&self, that: impl AsRef<[u8]>) -> Ordering {
+    crate::caseless::cmp(self.as_bytes(), that.as_ref())
+  }
+
   /// Tries to convert this yarn into a UTF-8 yarn via [`str::from_utf8()`].
   ///
   /// ```
@@ -291,14 +520,421 @@ impl<'a> YarnRef<'a, [u8]> {
     str::from_utf8(self.as_bytes())?;
     unsafe { Ok(YarnRef::from_raw(self.raw)) }
   }
+
+  /// Parses this yarn into another type via [`FromStr`], if it is valid UTF-8.
+  ///
+  /// See [`ByteYarn::parse()`].
+  pub fn parse<T: FromStr>(&self) -> Result<T, ParseError<T::Err>> {
+    let s = str::from_utf8(self.as_bytes()).map_err(ParseError::Utf8)?;
+    s.parse().map_err(ParseError::Parse)
+  }
+
+  /// Encodes this yarn as base64 in the standard alphabet, with padding.
+  ///
+  /// See [`ByteYarn::to_base64()`].
+  #[cfg(feature = "base64")]
+  pub fn to_base64(&self) -> YarnBox<'static, str> {
+    crate::base64::encode(self.as_bytes(), crate::base64::STANDARD)
+  }
+
+  /// Encodes this yarn as base64 in the URL-safe alphabet, without padding.
+  ///
+  /// See [`ByteYarn::to_base64_url()`].
+  #[cfg(feature = "base64")]
+  pub fn to_base64_url(&self) -> YarnBox<'static, str> {
+    crate::base64::encode(self.as_bytes(), crate::base64::URL_SAFE)
+  }
+
+  /// Percent-encodes every byte of this yarn that is in `set`, as `%XY`.
+  ///
+  /// See [`ByteYarn::percent_encode()`].
+  pub fn percent_encode(&self, set: EncodeSet) -> YarnBox<'static, str> {
+    crate::percent::encode(self.as_bytes(), set)
+  }
+
+  /// Decodes every `%XY` percent-escape in this yarn.
+  ///
+  /// See [`ByteYarn::percent_decode()`].
+  pub fn percent_decode(&self) -> Result<YarnBox<'static, [u8]>, PercentError> {
+    crate::percent::decode(self.as_bytes())
+  }
+
+  /// Encodes this yarn as lowercase hex digits, two per byte.
+  ///
+  /// See [`ByteYarn::to_hex()`].
+  pub fn to_hex(&self) -> YarnBox<'static, str> {
+    crate::hex::encode(self.as_bytes(), false)
+  }
+
+  /// Encodes this yarn as uppercase hex digits, two per byte.
+  ///
+  /// See [`ByteYarn::to_hex_upper()`].
+  pub fn to_hex_upper(&self) -> YarnBox<'static, str> {
+    crate::hex::encode(self.as_bytes(), true)
+  }
 }
 
-impl YarnRef<'_, str> {
+impl<'a> YarnRef<'a, str> {
   /// Converts this yarn into a string slice.
   pub fn as_str(&self) -> &str {
     self.as_slice()
   }
 
+  /// Copies this yarn into a new C string.
+  ///
+  /// See [`Yarn::to_cstring()`].
+  pub fn to_cstring(&self) -> Result<CString, NulError> {
+    CString::new(self.as_bytes())
+  }
+
+  /// Returns a hash of this yarn's contents that is the same on every
+  /// platform.
+  ///
+  /// See [`Yarn::stable_hash()`].
+  #[cfg(feature = "fnv")]
+  pub fn stable_hash(&self) -> u64 {
+    crate::fnv::hash(self.as_bytes())
+  }
+
+  /// Returns an iterator over the extended grapheme clusters of this yarn.
+  ///
+  /// See [`Yarn::graphemes()`].
+  #[cfg(feature = "unicode")]
+  pub fn graphemes(&self) -> crate::Graphemes<'a> {
+    crate::Graphemes::new(*self)
+  }
+
+  /// Returns an iterator over the extended grapheme clusters of this yarn,
+  /// along with their byte offsets.
+  ///
+  /// See [`Yarn::grapheme_indices()`].
+  #[cfg(feature = "unicode")]
+  pub fn grapheme_indices(&self) -> crate::GraphemeIndices<'a> {
+    crate::GraphemeIndices::new(*self)
+  }
+
+  /// Percent-encodes every byte of this yarn that is in `set`, as `%XY`.
+  ///
+  /// See [`Yarn::percent_encode()`].
+  pub fn percent_encode(&self, set: EncodeSet) -> YarnBox<'static, str> {
+    crate::percent::encode(self.as_bytes(), set)
+  }
+
+  /// Decodes every `%XY` percent-escape in this yarn.
+  ///
+  /// See [`Yarn::percent_decode()`].
+  pub fn percent_decode(&self) -> Result<YarnBox<'static, [u8]>, PercentError> {
+    crate::percent::decode(self.as_bytes())
+  }
+
+  /// Escapes this yarn as the contents of a JSON string literal.
+  ///
+  /// See [`Yarn::json_escape()`].
+  pub fn json_escape(&self) -> YarnBox<'static, str> {
+    crate::json::escape(self.as_str(), false)
+  }
+
+  /// Escapes this yarn as the contents of a pure-ASCII JSON string literal.
+  ///
+  /// See [`Yarn::json_escape_ascii()`].
+  pub fn json_escape_ascii(&self) -> YarnBox<'static, str> {
+    crate::json::escape(self.as_str(), true)
+  }
+
+  /// Returns this yarn padded at the start to `width` characters.
+  ///
+  /// See [`Yarn::pad_start()`].
+  pub fn pad_start(&self, width: usize, fill: char) -> YarnBox<'a, str> {
+    self.pad(width, fill, |pad| pad).unwrap_or(self.to_box())
+  }
+
+  /// Returns this yarn padded at the end to `width` characters.
+  ///
+  /// See [`Yarn::pad_end()`].
+  pub fn pad_end(&self, width: usize, fill: char) -> YarnBox<'a, str> {
+    self.pad(width, fill, |_| 0).unwrap_or(self.to_box())
+  }
+
+  /// Returns this yarn padded on both sides to `width` characters.
+  ///
+  /// See [`Yarn::center()`].
+  pub fn center(&self, width: usize, fill: char) -> YarnBox<'a, str> {
+    self
+      .pad(width, fill, |pad| pad / 2)
+      .unwrap_or(self.to_box())
+  }
+
+  /// Pads this yarn with `fill` to `width` characters, putting `before(pad)`
+  /// of the `pad` fill characters before it and the rest after it.
+  ///
+  /// Returns `None` if this yarn is already at least `width` characters wide.
+  pub(crate) fn pad(
+    &self,
+    width: usize,
+    fill: char,
+    before: impl FnOnce(usize) -> usize,
+  ) -> Option<YarnBox<'static, str>> {
+    let pad = width.checked_sub(self.as_str().chars().count())?;
+    if pad == 0 {
+      return None;
+    }
+    let before = before(pad);
+
+    let cap = self.len() + pad * fill.len_utf8();
+    let mut yarn = YarnBox::<str>::with_capacity(cap);
+    yarn.extend(iter::repeat_n(fill, before));
+    yarn.push_str(self.as_str());
+    yarn.extend(iter::repeat_n(fill, pad - before));
+    Some(yarn)
+  }
+
+  /// Returns an iterator over the characters of this yarn, back to front.
+  ///
+  /// See [`Yarn::rchars()`].
+  pub fn rchars(&self) -> iter::Rev<str::Chars<'_>> {
+    self.as_str().chars().rev()
+  }
+
+  /// Returns a copy of this yarn with its characters in reverse order.
+  ///
+  /// See [`Yarn::reverse_chars()`].
+  pub fn reverse_chars(&self) -> YarnBox<'static, str> {
+    let mut yarn = YarnBox::<str>::with_capacity(self.len());
+    yarn.extend(self.rchars());
+    yarn
+  }
+
+  /// Checks whether this yarn is equal to `that`, ignoring ASCII case.
+  ///
+  /// See [`Yarn::eq_ignore_ascii_case()`].
+  pub fn eq_ignore_ascii_case(&self, that: impl AsRef<str>) -> bool {
+    self
+      .as_bytes()
+      .eq_ignore_ascii_case(that.as_ref().as_bytes())
+  }
+
+  /// Compares this yarn with `that` lexicographically, ignoring ASCII case.
+  ///
+  /// See [`Yarn::cmp_ignore_ascii_case()`].
+  pub fn cmp_ignore_ascii_case(&self, that: impl AsRef<str>) -> Ordering {
+    crate::caseless::cmp(self.as_bytes(), that.as_ref().as_bytes())
+  }
+
+  /// Parses this yarn into another type via [`str::parse()`].
+  ///
+  /// See [`Yarn::parse()`].
+  pub fn parse<T: FromStr>(&self) -> Result<T, T::Err> {
+    self.as_str().parse()
+  }
+
+  /// Returns this yarn with leading and trailing whitespace removed.
+  ///
+  /// See [`Yarn::trim()`].
+  pub fn trim(&self) -> YarnRef<'a, str> {
+    self.subyarn(self.as_str().trim())
+  }
+
+  /// Returns this yarn with leading whitespace removed.
+  ///
+  /// See [`Yarn::trim_start()`].
+  pub fn trim_start(&self) -> YarnRef<'a, str> {
+    self.subyarn(self.as_str().trim_start())
+  }
+
+  /// Returns this yarn with trailing whitespace removed.
+  ///
+  /// See [`Yarn::trim_end()`].
+  pub fn trim_end(&self) -> YarnRef<'a, str> {
+    self.subyarn(self.as_str().trim_end())
+  }
+
+  /// Returns this yarn with all leading and trailing copies of `c` removed.
+  ///
+  /// See [`Yarn::trim_matches()`].
+  pub fn trim_matches(&self, c: char) -> YarnRef<'a, str> {
+    self.subyarn(self.as_str().trim_matches(c))
+  }
+
+  /// Returns this yarn with `prefix` removed, if it starts with it.
+  ///
+  /// See [`Yarn::strip_prefix()`].
+  pub fn strip_prefix(
+    &self,
+    mut prefix: impl Pattern,
+  ) -> Option<YarnRef<'a, str>> {
+    let len = prefix.match_prefix(self.as_str())?;
+    Some(self.subyarn(&self.as_str()[len..]))
+  }
+
+  /// Returns this yarn with `suffix` removed, if it ends with it.
+  ///
+  /// See [`Yarn::strip_suffix()`].
+  pub fn strip_suffix(
+    &self,
+    mut suffix: impl Pattern,
+  ) -> Option<YarnRef<'a, str>> {
+    let len = suffix.match_suffix(self.as_str())?;
+    Some(self.subyarn(&self.as_str()[..self.len() - len]))
+  }
+
+  /// Returns an iterator over the pieces of this yarn separated by `pat`.
+  ///
+  /// See [`Yarn::split()`].
+  pub fn split<P: Pattern>(&self, pat: P) -> Split<'a, P> {
+    Split::new(*self, pat, usize::MAX)
+  }
+
+  /// Returns an iterator over the non-overlapping matches of `pat` in this
+  /// yarn.
+  ///
+  /// See [`Yarn::matches()`].
+  pub fn matches<P: Pattern>(&self, pat: P) -> Matches<'a, P> {
+    Matches::new(*self, pat)
+  }
+
+  /// Returns an iterator over the non-overlapping matches of `pat` in this
+  /// yarn, along with their byte offsets.
+  ///
+  /// See [`Yarn::match_indices()`].
+  pub fn match_indices<P: Pattern>(&self, pat: P) -> MatchIndices<'a, P> {
+    MatchIndices::new(*self, pat)
+  }
+
+  /// Returns an iterator over at most `n` pieces of this yarn separated by
+  /// `pat`.
+  ///
+  /// See [`Yarn::splitn()`].
+  pub fn splitn<P: Pattern>(&self, n: usize, pat: P) -> Split<'a, P> {
+    Split::new(*self, pat, n)
+  }
+
+  /// Returns an iterator over the lines of this yarn.
+  ///
+  /// See [`Yarn::lines()`].
+  pub fn lines(&self) -> Lines<'a> {
+    Lines::new(*self)
+  }
+
+  /// Splits this yarn into two at byte offset `mid`.
+  ///
+  /// See [`Yarn::split_at()`].
+  pub fn split_at(&self, mid: usize) -> (YarnRef<'a, str>, YarnRef<'a, str>) {
+    let (a, b) = self.as_str().split_at(mid);
+    (self.subyarn(a), self.subyarn(b))
+  }
+
+  /// Splits this yarn around the first match of `pat`.
+  ///
+  /// See [`Yarn::split_once()`].
+  pub fn split_once(
+    &self,
+    mut pat: impl Pattern,
+  ) -> Option<(YarnRef<'a, str>, YarnRef<'a, str>)> {
+    let found = pat.find_in(self.as_str())?;
+    Some(self.split_around(found))
+  }
+
+  /// Splits this yarn around the last match of `pat`.
+  ///
+  /// See [`Yarn::rsplit_once()`].
+  pub fn rsplit_once(
+    &self,
+    mut pat: impl Pattern,
+  ) -> Option<(YarnRef<'a, str>, YarnRef<'a, str>)> {
+    let found = pat.rfind_in(self.as_str())?;
+    Some(self.split_around(found))
+  }
+
+  /// Returns the sub-yarns before and after `range`.
+  fn split_around(
+    &self,
+    range: Range<usize>,
+  ) -> (YarnRef<'a, str>, YarnRef<'a, str>) {
+    let text = self.as_str();
+    (self.subyarn(&text[..range.start]), self.subyarn(&text[range.end..]))
+  }
+
+  /// Replaces all matches of `from` with `to`.
+  ///
+  /// See [`Yarn::replace()`].
+  pub fn replace<'b>(
+    &self,
+    from: impl Pattern,
+    to: impl Into<YarnBox<'b, str>>,
+  ) -> YarnBox<'a, str> {
+    self.replacen(from, to, usize::MAX)
+  }
+
+  /// Replaces the first `n` matches of `from` with `to`.
+  ///
+  /// See [`Yarn::replacen()`].
+  pub fn replacen<'b>(
+    &self,
+    mut from: impl Pattern,
+    to: impl Into<YarnBox<'b, str>>,
+    mut n: usize,
+  ) -> YarnBox<'a, str> {
+    let text = self.as_str();
+    let to = to.into();
+
+    // The output is only materialized once we find a match that would
+    // actually change the yarn; until then, `copied` stays at zero.
+    let mut out = None::<YarnBox<'a, str>>;
+    let mut copied = 0;
+    let mut search = 0;
+    while n > 0 && search <= text.len() {
+      let Some(found) = from.find_in(&text[search..]) else { break };
+      let (start, end) = (search + found.start, search + found.end);
+      n -= 1;
+
+      search = end;
+      if start == end {
+        search += text[end..].chars().next().map_or(1, char::len_utf8);
+      }
+
+      if out.is_none() && text[start..end] == *to {
+        continue;
+      }
+
+      // Growing from an empty yarn means the result stays inlined if it
+      // fits.
+      let out = out.get_or_insert_with(YarnBox::default);
+      out.push_str(&text[copied..start]);
+      out.push_str(&to);
+      copied = end;
+    }
+
+    match out {
+      Some(mut out) => {
+        out.push_str(&text[copied..]);
+        out
+      }
+      None => self.to_box(),
+    }
+  }
+
+  /// Returns an iterator over the characters of this yarn.
+  ///
+  /// See [`Yarn::chars()`].
+  pub fn chars(&self) -> str::Chars<'_> {
+    self.as_str().chars()
+  }
+
+  /// Returns an iterator over the characters of this yarn, and their byte
+  /// offsets.
+  ///
+  /// See [`Yarn::char_indices()`].
+  pub fn char_indices(&self) -> str::CharIndices<'_> {
+    self.as_str().char_indices()
+  }
+
+  /// Returns an iterator over the UTF-16 encoding of this yarn.
+  ///
+  /// See [`Yarn::encode_utf16()`].
+  pub fn encode_utf16(&self) -> str::EncodeUtf16<'_> {
+    self.as_str().encode_utf16()
+  }
+
   /// Converts this yarn into a boxed slice by copying it.
   pub fn to_boxed_str(self) -> Box<str> {
     self.to_box().into_boxed_str()
@@ -336,18 +972,52 @@ where
 
 impl<Buf: crate::Buf + ?Sized> fmt::Debug for YarnRef<'_, Buf> {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "\"")?;
-    for chunk in self.utf8_chunks() {
-      match chunk {
-        Ok(utf8) => write!(f, "{}", utf8.escape_debug())?,
-        Err(bytes) => {
-          for b in bytes {
-            write!(f, "\\x{:02X}", b)?;
-          }
-        }
-      }
+    write!(f, "\"{}\"", Escape::debug(self.as_bytes()))
+  }
+}
+
+/// Formats a yarn along with how it is stored.
+///
+/// This prints the kind of storage (`Small`, `Static`, `Heap`, or `Aliased`),
+/// the length and capacity in bytes, whether a heap buffer is shared, and the
+/// contents.
+///
+/// See [`YarnBox::debug_storage()`].
+#[derive(Copy, Clone)]
+pub struct StorageDebug<'a, Buf: crate::Buf + ?Sized> {
+  raw: RawYarn,
+  _ph: PhantomData<&'a Buf>,
+}
+
+impl<Buf: crate::Buf + ?Sized> StorageDebug<'_, Buf> {
+  /// # Safety
+  ///
+  /// `raw` must be a valid yarn of `Buf` for the lifetime of this value.
+  pub(crate) unsafe fn new(raw: RawYarn) -> Self {
+    Self { raw, _ph: PhantomData }
+  }
+}
+
+impl<Buf: crate::Buf + ?Sized> fmt::Debug for StorageDebug<'_, Buf> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let kind = match self.raw.kind() {
+      RawYarn::SMALL => "Small",
+      RawYarn::STATIC => "Static",
+      RawYarn::HEAP => "Heap",
+      _ => "Aliased",
+    };
+
+    // SAFETY: This is the alignment this yarn was allocated with.
+    let align = mem::align_of::<Buf::Element>();
+    let capacity = unsafe { self.raw.capacity(align) };
+
+    let mut f = f.debug_struct(kind);
+    f.field("len", &self.raw.len()).field("capacity", &capacity);
+    if self.raw.on_heap() {
+      f.field("shared", &self.raw.is_shared());
     }
-    write!(f, "\"")
+    f.field("data", &format_args!("\"{}\"", Escape::debug(self.raw.as_slice())))
+      .finish()
   }
 }
 
@@ -370,6 +1040,15 @@ where
   Slice: AsRef<Buf> + ?Sized,
 {
   fn eq(&self, that: &Slice) -> bool {
+    #[cfg(feature = "simd")]
+    if mem::size_of::<Buf::Element>() == 1 {
+      // Byte-sized elements are equal exactly when their bytes are.
+      return crate::simd::eq(
+        self.as_slice().as_bytes(),
+        that.as_ref().as_bytes(),
+      );
+    }
+
     self.as_slice() == that.as_ref()
   }
 }
@@ -409,3 +1088,113 @@ impl<Buf: crate::Buf + ?Sized> Default for &YarnRef<'_, Buf> {
     YarnRef::empty()
   }
 }
+
+#[test]
+fn subyarns_never_alias_inlined_data() {
+  use crate::Yarn;
+
+  let small = Yarn::copy("  smol  ");
+  assert!(small.trim().raw.is_small());
+
+  let big = Yarn::from_string("  a very long string on the heap  ".into());
+  let trimmed = big.trim();
+  assert_eq!(trimmed.raw.kind(), RawYarn::ALIASED);
+  assert_eq!(trimmed.as_ptr(), big[2..].as_ptr());
+}
+
+#[test]
+fn split_once_aliases() {
+  use crate::Yarn;
+
+  let yarn = Yarn::from(
+    "a_long_configuration_key = a_value_that_is_too_long".to_string(),
+  );
+  let (key, value) = yarn.split_once(" = ").unwrap();
+  assert_eq!(key, "a_long_configuration_key");
+  assert_eq!(value, "a_value_that_is_too_long");
+  assert_eq!(key.as_ptr(), yarn.as_ptr());
+  assert_eq!(value.as_ptr(), yarn[27..].as_ptr());
+
+  let (key, value) = yarn.rsplit_once(Yarn::new("_")).unwrap();
+  assert_eq!(key, "a_long_configuration_key = a_value_that_is_too");
+  assert_eq!(value, "long");
+}
+
+#[test]
+fn split_at_aliases() {
+  use crate::ByteYarn;
+  use crate::Yarn;
+
+  let yarn = Yarn::from("a string that is too long to inline: 🐈".to_string());
+  let (head, tail) = yarn.split_at(36);
+  assert_eq!(head, "a string that is too long to inline:");
+  assert_eq!(head.raw.kind(), RawYarn::ALIASED);
+  assert_eq!(head.as_ptr(), yarn.as_ptr());
+  assert_eq!(tail, " 🐈");
+  assert!(tail.raw.is_small());
+
+  let small = ByteYarn::copy(b"\xffsmol");
+  let (head, tail) = small.split_at(1);
+  assert_eq!([head, tail], [&b"\xff"[..], b"smol"]);
+  assert!(head.raw.is_small() && tail.raw.is_small());
+
+  let (all, empty) = small.split_at(small.len());
+  assert_eq!([all, empty], [&small[..], b""]);
+}
+
+#[test]
+fn to_owned_yarn_detaches() {
+  use crate::Yarn;
+
+  let text = "a static string that is too long to inline";
+  let owned: Yarn = YarnRef::from_static(text).to_owned_yarn();
+  assert!(owned.is_heap());
+  assert_ne!(owned.as_ptr(), text.as_ptr());
+
+  // Shared heap yarns are copied instead of having their count bumped.
+  let arc = std::sync::Arc::<str>::from(text);
+  let shared = Yarn::from_arc(arc.clone());
+  let copy = shared.to_owned_yarn();
+  assert_eq!(std::sync::Arc::strong_count(&arc), 2);
+  assert!(copy.is_heap());
+  assert_ne!(copy.as_ptr(), shared.as_ptr());
+  assert_eq!(copy, text);
+
+  let small: Yarn = YarnRef::from_static("smol").to_owned_yarn();
+  assert!(small.is_inlined());
+}
+
+#[test]
+fn debug_storage_kinds() {
+  use crate::Yarn;
+
+  let text = "a static string\n, too long to inline";
+  let lit = Yarn::from_static(text);
+  assert_eq!(
+    format!("{:?}", lit.debug_storage()),
+    r#"Static { len: 36, capacity: 36, data: "a static string\n, too long to inline" }"#,
+  );
+
+  let owned = text.to_string();
+  assert_eq!(
+    format!("{:?}", YarnRef::new(owned.as_str()).debug_storage()),
+    r#"Aliased { len: 36, capacity: 36, data: "a static string\n, too long to inline" }"#,
+  );
+
+  let shared = Yarn::from_arc(text.into());
+  assert_eq!(
+    format!("{:?}", shared.debug_storage()),
+    r#"Heap { len: 36, capacity: 36, shared: true, data: "a static string\n, too long to inline" }"#,
+  );
+
+  // A heap yarn only looks aliased through a reference.
+  assert!(
+    format!("{:?}", shared.as_ref().debug_storage()).starts_with("Aliased")
+  );
+}
+
+#[test]
+#[should_panic]
+fn split_at_char_boundary() {
+  crate::Yarn::new("🐈").split_at(1);
+}
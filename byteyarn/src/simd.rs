@@ -0,0 +1,102 @@
+//! Vectorized byte comparison, for the `simd` feature.
+
+/// Returns whether `a` and `b` contain the same bytes.
+///
+/// Short slices, such as the contents of inlined yarns, are compared with a
+/// pair of overlapping word loads; longer slices are compared 32 bytes at a
+/// time, with one overlapping 16-byte comparison for the tail.
+pub fn eq(a: &[u8], b: &[u8]) -> bool {
+  let len = a.len();
+  if len != b.len() {
+    return false;
+  }
+  if a.as_ptr() == b.as_ptr() {
+    return true;
+  }
+
+  let (a, b) = (a.as_ptr(), b.as_ptr());
+  unsafe {
+    // SAFETY: Both slices are `len` bytes long, and every offset read from
+    // below is at most `len` minus the size of the load.
+    match len {
+      0..=3 => (0..len).all(|i| *a.add(i) == *b.add(i)),
+      4..=8 => {
+        load::<u32>(a, 0) == load::<u32>(b, 0)
+          && load::<u32>(a, len - 4) == load::<u32>(b, len - 4)
+      }
+      9..=16 => {
+        load::<u64>(a, 0) == load::<u64>(b, 0)
+          && load::<u64>(a, len - 8) == load::<u64>(b, len - 8)
+      }
+      _ => {
+        let mut i = 0;
+        while i + 32 <= len {
+          // Deliberately not short-circuiting, so that both halves are
+          // compared at once.
+          if !(eq16(a.add(i), b.add(i)) & eq16(a.add(i + 16), b.add(i + 16))) {
+            return false;
+          }
+          i += 32;
+        }
+        if i + 16 <= len {
+          if !eq16(a.add(i), b.add(i)) {
+            return false;
+          }
+          i += 16;
+        }
+        i == len || eq16(a.add(len - 16), b.add(len - 16))
+      }
+    }
+  }
+}
+
+/// Performs an unaligned load of a `T` at `ptr + offset`.
+///
+/// # Safety
+///
+/// `ptr + offset` must be valid for reading a `T`.
+#[inline(always)]
+unsafe fn load<T: Copy>(ptr: *const u8, offset: usize) -> T {
+  ptr.add(offset).cast::<T>().read_unaligned()
+}
+
+/// Compares 16 bytes at `a` and `b`.
+///
+/// # Safety
+///
+/// `a` and `b` must be valid for reading 16 bytes.
+#[inline(always)]
+unsafe fn eq16(a: *const u8, b: *const u8) -> bool {
+  #[cfg(target_arch = "x86_64")]
+  {
+    use core::arch::x86_64::*;
+
+    // SAFETY: SSE2 is always available on x86_64.
+    let a = _mm_loadu_si128(a.cast());
+    let b = _mm_loadu_si128(b.cast());
+    _mm_movemask_epi8(_mm_cmpeq_epi8(a, b)) == 0xffff
+  }
+
+  #[cfg(not(target_arch = "x86_64"))]
+  {
+    load::<u128>(a, 0) == load::<u128>(b, 0)
+  }
+}
+
+#[test]
+fn eq_agrees_with_slices() {
+  let a = (0..100u8).collect::<Vec<_>>();
+  for len in 0..a.len() {
+    let mut b = a[..len].to_vec();
+    assert!(eq(&a[..len], &b));
+    assert!(!eq(&a[..len], &a[..len.saturating_sub(1)]) || len == 0);
+
+    // A difference at any position must be caught, including in the tail
+    // that does not fill a whole vector.
+    for i in 0..len {
+      b[i] ^= 0x80;
+      assert!(!eq(&a[..len], &b), "len = {len}, i = {i}");
+      b[i] ^= 0x80;
+    }
+  }
+}
@@ -1,10 +1,49 @@
 //! UTF-8 utilities not provided by the standard library.
 
-use std::str;
+use core::fmt;
+use core::str;
 
 #[cfg(doc)]
 use crate::*;
 
+/// Formats a byte buffer with its characters escaped, like
+/// [`str::escape_default()`] or [`str::escape_debug()`], and any bytes that are
+/// not valid UTF-8 escaped as `\xNN`.
+#[derive(Copy, Clone)]
+pub(crate) struct Escape<'a> {
+  buf: &'a [u8],
+  debug: bool,
+}
+
+impl<'a> Escape<'a> {
+  /// Escapes like [`str::escape_default()`].
+  pub(crate) fn default(buf: &'a [u8]) -> Self {
+    Self { buf, debug: false }
+  }
+
+  /// Escapes like [`str::escape_debug()`].
+  pub(crate) fn debug(buf: &'a [u8]) -> Self {
+    Self { buf, debug: true }
+  }
+}
+
+impl fmt::Display for Escape<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for chunk in Utf8Chunks::new(self.buf) {
+      match chunk {
+        Ok(utf8) if self.debug => write!(f, "{}", utf8.escape_debug())?,
+        Ok(utf8) => write!(f, "{}", utf8.escape_default())?,
+        Err(bytes) => {
+          for b in bytes {
+            write!(f, "\\x{:02X}", b)?;
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
 /// An iterator over UTF-8 chunks in a byte buffer.
 ///
 /// Any time non-UTF-8 bytes are encountered, they are returned as `Err`s
@@ -90,6 +129,46 @@ impl<'a> Iterator for Utf8Chunks<'a> {
   }
 }
 
+/// An iterator over the characters in a byte buffer, which replaces invalid
+/// UTF-8 with [`char::REPLACEMENT_CHARACTER`].
+///
+/// Each run of invalid bytes yielded by [`Utf8Chunks`] is replaced with a
+/// single replacement character, like in the `Display` implementation of
+/// yarns.
+///
+/// See [`ByteYarn::chars_lossy()`].
+#[derive(Clone)]
+pub struct CharsLossy<'a> {
+  chunks: Utf8Chunks<'a>,
+  chars: str::Chars<'a>,
+}
+
+impl<'a> CharsLossy<'a> {
+  pub(crate) fn new(buf: &'a [u8]) -> Self {
+    Self {
+      chunks: Utf8Chunks::new(buf),
+      chars: "".chars(),
+    }
+  }
+}
+
+impl Iterator for CharsLossy<'_> {
+  type Item = char;
+
+  fn next(&mut self) -> Option<char> {
+    loop {
+      if let Some(c) = self.chars.next() {
+        return Some(c);
+      }
+
+      match self.chunks.next()? {
+        Ok(utf8) => self.chars = utf8.chars(),
+        Err(_) => return Some(char::REPLACEMENT_CHARACTER),
+      }
+    }
+  }
+}
+
 /// `const`-enabled UTF-8 encoding.
 ///
 /// Returns the encoded bytes in a static array, and the number of those bytes
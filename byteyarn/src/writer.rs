@@ -0,0 +1,120 @@
+//! Incremental building of yarns with `write!()`.
+
+use core::fmt;
+
+use crate::YarnBox;
+
+#[cfg(doc)]
+use crate::*;
+
+/// A builder that accumulates formatted output into a yarn.
+///
+/// A `YarnWriter<str>` implements [`fmt::Write`], and a `YarnWriter<[u8]>`
+/// additionally implements [`std::io::Write`] (with the `std` feature). Output
+/// is built up inline, and only spills onto the heap once it no longer fits.
+///
+/// ```
+/// # use byteyarn::*;
+/// use std::fmt::Write;
+///
+/// let mut w = YarnWriter::<str>::new();
+/// write!(w, "{}+{}", 1, 2).unwrap();
+/// assert!(w.as_yarn().is_inlined());
+///
+/// write!(w, "={}, and this no longer fits inline", 1 + 2).unwrap();
+/// assert_eq!(w.into_yarn(), "1+2=3, and this no longer fits inline");
+/// ```
+pub struct YarnWriter<Buf: crate::Buf + ?Sized + 'static = str> {
+  yarn: YarnBox<'static, Buf>,
+}
+
+impl<Buf: crate::Buf + ?Sized> YarnWriter<Buf> {
+  /// Returns a new, empty writer.
+  pub fn new() -> Self {
+    Self { yarn: YarnBox::default() }
+  }
+
+  /// Returns a new, empty writer that can hold at least `cap` bytes without
+  /// reallocating.
+  ///
+  /// See [`YarnBox::with_capacity()`].
+  pub fn with_capacity(cap: usize) -> Self {
+    Self { yarn: YarnBox::with_capacity(cap) }
+  }
+
+  /// Returns the yarn written so far.
+  pub fn as_yarn(&self) -> &YarnBox<'static, Buf> {
+    &self.yarn
+  }
+
+  /// Consumes this writer, returning the yarn written to it.
+  pub fn into_yarn(self) -> YarnBox<'static, Buf> {
+    self.yarn
+  }
+}
+
+impl<Buf: crate::Buf + ?Sized> Default for YarnWriter<Buf> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl fmt::Write for YarnWriter<str> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    self.yarn.push_str(s);
+    Ok(())
+  }
+}
+
+impl fmt::Write for YarnWriter<[u8]> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    self.yarn.extend([s.as_bytes()]);
+    Ok(())
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for YarnWriter<[u8]> {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    self.yarn.extend([buf]);
+    Ok(buf.len())
+  }
+
+  fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+    self.yarn.extend([buf]);
+    Ok(())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
+}
+
+impl<Buf> fmt::Debug for YarnWriter<Buf>
+where
+  Buf: crate::Buf + ?Sized,
+  YarnBox<'static, Buf>: fmt::Debug,
+{
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Debug::fmt(&self.yarn, f)
+  }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn io_writer() {
+  use std::io::Write;
+
+  let mut w = YarnWriter::<[u8]>::new();
+  w.write_all(b"\xff\x00").unwrap();
+  write!(w, "{}", 42).unwrap();
+  assert!(w.as_yarn().is_inlined());
+  assert_eq!(w.as_yarn(), b"\xff\x0042");
+
+  for _ in 0..8 {
+    w.write_all(b"spill").unwrap();
+  }
+  let yarn = w.into_yarn();
+  assert!(yarn.is_heap());
+  assert_eq!(yarn.len(), 44);
+}
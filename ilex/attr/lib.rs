@@ -4,7 +4,7 @@ use proc_macro::TokenStream;
 
 // This helper exists only to make the #[spec] field attributes inert.
 #[doc(hidden)]
-#[proc_macro_derive(derive_hack, attributes(named, rule))]
+#[proc_macro_derive(derive_hack, attributes(named, priority, rule))]
 pub fn derive(_: TokenStream) -> TokenStream {
   TokenStream::new()
 }
@@ -29,7 +29,7 @@ proc2decl::bridge! {
   /// ```
   ///
   /// The type of each field must be a [`Lexeme`] with a [`Rule`] type as its
-  /// parameter. There are two special attributes that can follow.
+  /// parameter. There are three special attributes that can follow.
   ///
   /// - `#[named]` makes the rule into a *named* rule. This name can be used by
   ///   diagnostics, and corresponds to calling `Spec::named_rule()`.
@@ -41,9 +41,13 @@ proc2decl::bridge! {
   ///   `nullptr: Lexeme<Keyword>`, assuming Rust itself doesn't already use that
   ///   keyword.
   ///
+  /// - `#[priority]` sets the rule's priority, which must be an `i32`, and
+  ///   corresponds to calling `SpecBuilder::set_priority()`.
+  ///
   /// Note that *order matters* for the fields: when breaking a tie between two
-  /// potential tokens of the same length, the first one in the struct will win.
-  /// In practice, this means you should put keywords before identifiers.
+  /// potential tokens of the same length, the first one in the struct will win,
+  /// unless another one has a higher `#[priority]`. In practice, this means you
+  /// should put keywords before identifiers.
   ///
   /// Additionally, the following functions will be defined for the `MySpec` type.
   ///
@@ -1,5 +1,6 @@
 use std::fs;
 use std::sync::Arc;
+use std::sync::OnceLock;
 use std::sync::RwLock;
 
 use camino::Utf8Path;
@@ -11,6 +12,8 @@ use crate::report;
 use crate::report::Fatal;
 use crate::report::Report;
 
+#[cfg(doc)]
+use crate::ColumnUnit;
 #[cfg(doc)]
 use crate::Span;
 
@@ -26,13 +29,17 @@ pub struct Context {
 #[derive(Default)]
 pub struct State {
   // Each file is laid out as the length of the text, followed by the text data,
-  // followed by the path.
+  // followed by the path. The line table is boxed so that references to it
+  // remain valid as more files are added.
   //
   // TODO(mcyoung): Be smarter about this and use something something concurrent
   // vector? We don't need to have all this stuff behind a lock I think.
-  files: Vec<(usize, String)>,
+  files: Vec<(usize, String, Box<LineTable>)>,
 }
 
+/// Byte offsets of the start of each line in a file, computed on demand.
+pub(super) type LineTable = OnceLock<Vec<u32>>;
+
 unsafe impl Send for Context {}
 unsafe impl Sync for Context {}
 
@@ -94,7 +101,7 @@ impl Context {
 
     let idx = {
       let mut state = self.state.write().unwrap();
-      state.files.push((len, text));
+      state.files.push((len, text, Default::default()));
       state.files.len() - 1
     };
 
@@ -123,6 +130,48 @@ impl Context {
     Ok(self.new_file(path, text))
   }
 
+  /// Adds a new file to this source context by transcoding `text` from
+  /// Latin-1 (that is, ISO 8859-1) to UTF-8.
+  ///
+  /// The file is lexed as UTF-8 like any other; there is no byte-oriented
+  /// mode. Every byte is a valid Latin-1 character, so this cannot fail, and no
+  /// information is lost: each character in the file's text is the code point
+  /// with the same value as the corresponding input byte.
+  ///
+  /// Spans are still byte offsets into the decoded UTF-8 text, not into
+  /// `text`: every byte `0x80` or above decodes to a two-byte character, so
+  /// each one before a span shifts it one byte further along. The offset in
+  /// `text` of a span offset `n` is the number of characters before it, that
+  /// is, `file.text(..n).chars().count()`; likewise, a [`ColumnUnit::Chars`]
+  /// column is a byte column in `text`.
+  ///
+  /// ```
+  /// let ctx = ilex::Context::new();
+  /// let file = ctx.new_file_from_latin1("menu.txt", b"caf\xe9 cr\xe8me");
+  /// assert_eq!(file.text(..), "café crème");
+  ///
+  /// let bytes = file.text(..).chars().map(|c| c as u8).collect::<Vec<_>>();
+  /// assert_eq!(bytes, b"caf\xe9 cr\xe8me");
+  ///
+  /// // `crème` is at 5..10 in the input, but at 6..12 in the file.
+  /// let span = file.span(6..12);
+  /// assert_eq!(span.text(), "crème");
+  /// let to_input = |n| file.text(..n).chars().count();
+  /// assert_eq!(to_input(span.start())..to_input(span.end()), 5..10);
+  /// ```
+  pub fn new_file_from_latin1<'a>(
+    &self,
+    path: impl Into<&'a Utf8Path>,
+    text: impl AsRef<[u8]>,
+  ) -> File {
+    let text = text
+      .as_ref()
+      .iter()
+      .map(|&b| char::from(b))
+      .collect::<String>();
+    self.new_file(path, text)
+  }
+
   /// Adds a new file to this source context by opening `name` and reading it
   /// from the file system.
   pub fn open_file<'a>(
@@ -146,14 +195,14 @@ impl Context {
   /// Gets the `idx`th file in this source context.
   pub fn file(&self, idx: usize) -> Option<File> {
     let state = self.state.read().unwrap();
-    let (len, text) = state.files.get(idx)?;
-    let text = unsafe {
-      // SAFETY: The pointer to the file's text is immutable and pointer-stable,
-      // so we can safely extend its lifetime here.
-      &*(text.as_str() as *const str)
+    let (len, text, lines) = state.files.get(idx)?;
+    let (text, lines) = unsafe {
+      // SAFETY: The pointers to the file's text and line table are immutable
+      // and pointer-stable, so we can safely extend their lifetimes here.
+      (&*(text.as_str() as *const str), &*(&**lines as *const LineTable))
     };
 
-    Some(File { len: *len, text, ctx: self, idx })
+    Some(File { len: *len, text, lines, ctx: self, idx })
   }
 
   /// Gets the number of files currently tracked by this source context.
@@ -3,6 +3,7 @@
 use std::cell::RefCell;
 use std::fmt;
 use std::fmt::Write;
+use std::iter;
 use std::ops::Bound;
 use std::ops::Index;
 use std::ops::RangeBounds;
@@ -25,6 +26,7 @@ pub use context::Context;
 pub struct File<'ctx> {
   len: usize,
   text: &'ctx str,
+  lines: &'ctx context::LineTable,
   ctx: &'ctx Context,
   idx: usize,
 }
@@ -77,6 +79,54 @@ impl<'ctx> File<'ctx> {
     Span::new(self, range)
   }
 
+  /// Converts a byte offset into this file into a line and column.
+  ///
+  /// The first call to this function for a file builds a table of line
+  /// starts, so later lookups only take logarithmic time.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `offset` is greater than the length of the file, or if it does
+  /// not lie on a `char` boundary.
+  ///
+  /// ```
+  /// # use ilex::ColumnUnit;
+  /// let ctx = ilex::Context::new();
+  /// let file = ctx.new_file("poem.txt", "roses are red\nviolets are 🔵");
+  ///
+  /// let loc = file.location(25, ColumnUnit::Chars);
+  /// assert_eq!((loc.line, loc.column), (2, 12));
+  ///
+  /// let loc = file.location(file.len(), ColumnUnit::Utf16);
+  /// assert_eq!((loc.line, loc.column), (2, 15));
+  /// ```
+  pub fn location(self, offset: usize, unit: ColumnUnit) -> Location {
+    let text = self.text(..);
+    assert!(
+      text.is_char_boundary(offset),
+      "offset is not a char boundary: {offset}"
+    );
+
+    let lines = self.lines.get_or_init(|| {
+      let newlines = text.match_indices('\n').map(|(i, _)| i as u32 + 1);
+      iter::once(0).chain(newlines).collect()
+    });
+
+    let line = lines.partition_point(|&start| start as usize <= offset);
+    let line_text = &text[lines[line - 1] as usize..offset];
+    let column = match unit {
+      ColumnUnit::Bytes => line_text.len(),
+      ColumnUnit::Chars => line_text.chars().count(),
+      ColumnUnit::Utf16 => line_text.chars().map(char::len_utf16).sum(),
+    };
+
+    Location {
+      line: line as u32,
+      column: column as u32 + 1,
+      byte_offset: offset,
+    }
+  }
+
   pub(crate) fn idx(self) -> usize {
     self.idx
   }
@@ -97,6 +147,33 @@ impl PartialEq for File<'_> {
   }
 }
 
+/// A human-readable position within a [`File`].
+///
+/// See [`File::location()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+  /// The line number, starting at 1.
+  pub line: u32,
+  /// The column number, starting at 1, in the [`ColumnUnit`] that was asked
+  /// for.
+  pub column: u32,
+  /// The byte offset into the file.
+  pub byte_offset: usize,
+}
+
+/// What to count when computing a [`Location`]'s column.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColumnUnit {
+  /// Count UTF-8 bytes.
+  Bytes,
+  /// Count Unicode scalar values.
+  #[default]
+  Chars,
+  /// Count UTF-16 code units, as the Language Server Protocol does by
+  /// default.
+  Utf16,
+}
+
 /// A range within a [`File`].
 ///
 /// Full span information (such as comments) is not necessary for diagnostics,
@@ -111,6 +188,7 @@ pub struct Span<'ctx> {
 
 // A compressed version of a span that only remembers the start/end.
 #[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span2(u32, u32);
 
 impl Span2 {
@@ -120,7 +198,7 @@ impl Span2 {
 }
 
 // A compressed version of a span that remembers the start, end, and file.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Span3(u32, u32, u32);
 
 impl Span3 {
@@ -194,6 +272,20 @@ impl<'ctx> Span<'ctx> {
     self.end as usize
   }
 
+  /// Returns the line and column of the start of this span.
+  ///
+  /// See [`File::location()`].
+  pub fn start_location(self, unit: ColumnUnit) -> Location {
+    self.file.location(self.start(), unit)
+  }
+
+  /// Returns the line and column of the end of this span.
+  ///
+  /// See [`File::location()`].
+  pub fn end_location(self, unit: ColumnUnit) -> Location {
+    self.file.location(self.end(), unit)
+  }
+
   /// Returns whether this span has zero length.
   pub fn is_empty(self) -> bool {
     self.len() == 0
@@ -7,6 +7,7 @@
 
 use std::cmp::Ordering;
 use std::fmt;
+use std::iter;
 use std::mem;
 use std::ops;
 use std::str::FromStr;
@@ -461,9 +462,12 @@ impl fmt::Debug for Exotic {
 
 impl Digital<'_> {
   #[track_caller]
+  /// Parses this token as a float. If `report` is `None`, no diagnostics are
+  /// generated, and the caller is responsible for checking that the token has
+  /// no extra digit blocks or exponents.
   pub(crate) fn parse_fp<Fp: Parse>(
     self,
-    report: &Report,
+    report: Option<&Report>,
     exact: bool,
   ) -> Result<Fp, Exotic> {
     let rule = self.rule().unwrap();
@@ -471,32 +475,35 @@ impl Digital<'_> {
     let mut digits = self.digit_blocks();
     let int = digits.next();
     let frac = digits.next();
-    for extra in digits {
-      report.builtins(self.spec()).unexpected(
-        "extra digits",
-        self.lexeme(),
-        extra,
-      );
-    }
-
     let mut exps = self.exponents();
     let exp = exps.next();
-    if let Some(exp) = exp {
-      for extra in exp.digit_blocks().skip(1) {
+
+    if let Some(report) = report {
+      for extra in digits {
         report.builtins(self.spec()).unexpected(
           "extra digits",
           self.lexeme(),
           extra,
         );
       }
-    }
 
-    for extra in exps {
-      report.builtins(self.spec()).unexpected(
-        "extra exponent",
-        self.lexeme(),
-        extra,
-      );
+      if let Some(exp) = exp {
+        for extra in exp.digit_blocks().skip(1) {
+          report.builtins(self.spec()).unexpected(
+            "extra digits",
+            self.lexeme(),
+            extra,
+          );
+        }
+      }
+
+      for extra in exps {
+        report.builtins(self.spec()).unexpected(
+          "extra exponent",
+          self.lexeme(),
+          extra,
+        );
+      }
     }
 
     if self.radix() != 10 && !self.radix().is_power_of_two() {
@@ -506,6 +513,18 @@ impl Digital<'_> {
       )));
     };
 
+    // The decimal case hands the text to rustc_apfloat, which does not know
+    // about custom digits.
+    let has_alphabet = iter::once(self)
+      .chain(exp)
+      .any(|d| d.digit_rule().alphabet.is_some());
+    if has_alphabet && !self.radix().is_power_of_two() {
+      return Err(Exotic(
+        "ilex does not support parsing decimal floats with custom digit alphabets"
+          .into(),
+      ));
+    }
+
     // The power of two case is simple enough that we can do it ourselves.
     // Adapted from the algorithm in rustc_apfloat.
     let result = if self.radix().is_power_of_two() {
@@ -519,20 +538,9 @@ impl Digital<'_> {
       let mut int_digits = 0i64;
       let mut frac_digits = 0i64;
       for (span, digits) in [(int, &mut int_digits), (frac, &mut frac_digits)] {
-        let Some(mut text) = span.map(|s| s.text()) else {
-          continue;
-        };
-        while let Some(c) = text.chars().next() {
-          if let Some(suf) = text.strip_prefix(rule.separator.as_str()) {
-            text = suf;
-            continue;
-          }
-
-          let digit = c
-            .to_digit(self.radix() as u32)
-            .unwrap_or_else(|| bug!("bad digit slipped past the lexer"))
-            as u128;
-          text = &text[1..];
+        let Some(span) = span else { continue };
+        for digit in self.digit_values(span) {
+          let digit = digit as u128;
           if digit > 0 {
             saw_sig_digit = true;
           }
@@ -552,18 +560,8 @@ impl Digital<'_> {
       }
 
       if let Some(exp) = exp {
-        let mut text = exp.digit_blocks().next().unwrap().text();
-        while let Some(c) = text.chars().next() {
-          if let Some(suf) = text.strip_prefix(rule.separator.as_str()) {
-            text = suf;
-            continue;
-          }
-
-          let digit = c.to_digit(exp.radix() as u32).unwrap_or_else(|| {
-            bug!("bad digit slipped past the lexer: {:?}", c)
-          }) as u128;
-          text = &text[1..];
-
+        let block = exp.digit_blocks().next().unwrap();
+        for digit in exp.digit_values(block) {
           e = e
             .saturating_mul(exp.radix() as i64)
             .saturating_add(digit as i64);
@@ -699,9 +697,12 @@ impl Digital<'_> {
       }
     };
 
-    if exact && result.status.contains(Status::INEXACT) {
+    if let Some(report) =
+      report.filter(|_| exact && result.status.contains(Status::INEXACT))
+    {
       report
         .error("value cannot be represented as an IEEE754 number exactly")
+        .code("E0014")
         .saying(self, "this number would be rounded");
     }
 
@@ -39,6 +39,7 @@ use crate::report::Report;
 /// # let report = ctx.new_report_with(report::Options {
 /// #   color: true,
 /// #   show_report_locations: false,
+/// #   ..Default::default()
 /// # });
 ///
 ///   let opts = ice::Options {
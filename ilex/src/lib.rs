@@ -29,9 +29,10 @@
 //!
 //! - **Unicode support.** This means that e.g. `エルフーン` is an identifier
 //!   by default. ASCII-only filters exist for backwards compatibility with old
-//!   stuff. `ilex` will only support UTF-8-encoded input files, and always uses
-//!   the Unicode definition of whitespace for delimiting tokens, not just
-//!   ASCII whitespace (`" \t\n\t"`).
+//!   stuff. `ilex` will only support UTF-8-encoded input files (Latin-1 files
+//!   can be losslessly transcoded with [`Context::new_file_from_latin1()`]),
+//!   and always uses the Unicode definition of whitespace for delimiting
+//!   tokens, not just ASCII whitespace (`" \t\n\t"`).
 //!
 //! - **Diagnostics and spans.** The lexer should be able to generate pretty good
 //!   diagnostics, and this API is exposed for tools built on top of the lexer
@@ -269,6 +270,7 @@ pub use {
   crate::{
     file::Context,
     file::File,
+    file::{ColumnUnit, Location},
     file::{Span, Spanned},
     report::{Fatal, Report},
     rule::Rule,
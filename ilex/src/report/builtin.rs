@@ -12,6 +12,7 @@ use crate::f;
 use crate::file::Spanned;
 use crate::plural;
 use crate::report::Diagnostic;
+use crate::report::Level;
 use crate::report::Report;
 use crate::rule;
 use crate::spec::Lexeme;
@@ -21,6 +22,33 @@ use crate::token;
 /// A wrapper over [`Report`] for generating diagnostics.
 ///
 /// See [`Report::builtins()`].
+///
+/// Every diagnostic generated through this type, and by the lexer itself,
+/// carries a stable [code][Diagnostic::code]:
+///
+/// | Code    | Diagnostic                                               |
+/// | ------- | -------------------------------------------------------- |
+/// | `E0001` | [`Builtins::unexpected()`]                               |
+/// | `E0002` | unrecognized characters                                  |
+/// | `E0003` | extraneous characters after a token                      |
+/// | `E0004` | [`Builtins::expected()`]                                 |
+/// | `E0005` | unopened delimiter                                       |
+/// | `E0006` | unclosed delimiter                                       |
+/// | `E0007` | non-ASCII characters in an ASCII-only identifier (lint)  |
+/// | `E0008` | identifier too short                                     |
+/// | `E0009` | dedent to an unknown indentation level                   |
+/// | `E0010` | mixed tabs and spaces in indentation                     |
+/// | `E0011` | [`Builtins::invalid_escape()`]                           |
+/// | `E0012` | [`Builtins::literal_out_of_range()`]                     |
+/// | `E0013` | too few decimal points in a number                       |
+/// | `E0014` | floating-point number that cannot be represented exactly |
+/// | `E0015` | digit separator in an unusual position (lint)            |
+/// | `E0016` | identifier that mixes scripts (lint)                     |
+/// | `E0017` | identifier that looks like an ASCII identifier (lint)    |
+///
+/// Diagnostics marked as lints can be configured with
+/// [`Report::set_level()`]; by default, they are errors, except for `E0016`
+/// and `E0017`, which are warnings.
 pub struct Builtins<'a> {
   pub(super) report: &'a Report,
   pub(super) spec: &'a Spec,
@@ -45,6 +73,7 @@ impl Builtins<'_> {
         unexpected_in.into().for_user_diagnostic(self.spec),
       ))
       .at(at)
+      .code("E0001")
       .reported_at(Location::caller());
 
     non_printable_note(found, diagnostic)
@@ -62,6 +91,7 @@ impl Builtins<'_> {
       .report
       .error(f!("unrecognized character{}", plural(found.chars().count())))
       .at(at)
+      .code("E0002")
       .reported_at(Location::caller());
 
     non_printable_note(found.into(), diagnostic)
@@ -89,6 +119,7 @@ impl Builtins<'_> {
           .span(at.start().saturating_sub(1)..at.start().saturating_add(1)),
         "maybe you meant to include a space here",
       )
+      .code("E0003")
       .reported_at(Location::caller());
 
     non_printable_note(found.into(), diagnostic)
@@ -115,6 +146,7 @@ impl Builtins<'_> {
         found.for_user_diagnostic(self.spec)
       ))
       .saying(at, f!("expected {alts}"))
+      .code("E0004")
       .reported_at(Location::caller());
 
     non_printable_note(found, diagnostic)
@@ -130,11 +162,14 @@ impl Builtins<'_> {
     at: impl Spanned<'s>,
   ) -> Diagnostic {
     let found = found.into();
+    let at = at.span();
 
     let diagnostic = self
       .report
       .error(f!("unexpected closing {}", found.for_user_diagnostic(self.spec)))
       .saying(at, f!("expected to be opened by `{expected}`"))
+      .suggest(at, "")
+      .code("E0005")
       .reported_at(Location::caller());
 
     non_printable_note(found, diagnostic)
@@ -152,6 +187,14 @@ impl Builtins<'_> {
     at: impl Spanned<'s2>,
   ) -> Diagnostic {
     let found = found.into();
+    let at = at.span();
+
+    // If we hit the end of something, insert the closer right after the last
+    // non-whitespace character, rather than on a line of its own.
+    let mut insert_at = at.start();
+    if at.is_empty() {
+      insert_at = at.file().text(..insert_at).trim_end().len();
+    }
 
     let diagnostic = self
       .report
@@ -160,30 +203,126 @@ impl Builtins<'_> {
         found.for_user_diagnostic(self.spec)
       ))
       .saying(at, f!("expected `{expected}` here"))
-      .remark(open, "previously opened here")
+      .suggest(at.file().span(insert_at..insert_at), expected)
+      .label(open, "previously opened here")
+      .code("E0006")
       .reported_at(Location::caller());
 
     non_printable_note(found, diagnostic)
   }
 
-  /// Generates an "unclosed delimiter" diagnostic, for when a delimiter is
-  /// not closed before expected.
+  /// Generates a "non-ASCII characters in identifier" lint.
   #[track_caller]
-  pub(crate) fn non_ascii_in_ident<'a, 's>(
+  pub(crate) fn non_ascii_in_ident<'s>(
     &self,
-    expected: impl Into<Expected<'a>>,
+    lexeme: Lexeme<rule::Any>,
     at: impl Spanned<'s>,
   ) -> Diagnostic {
     self
-      .report
-      .error(f!(
-        "unexpected non-ASCII characters in {}",
-        expected.into().for_user_diagnostic(self.spec)
-      ))
+      .lint(
+        "E0007",
+        lexeme,
+        Level::Deny,
+        f!(
+          "unexpected non-ASCII characters in {}",
+          Expected::from(lexeme).for_user_diagnostic(self.spec)
+        ),
+      )
+      .at(at)
+      .reported_at(Location::caller())
+  }
+
+  /// Generates a "digit separator in unusual position" lint.
+  #[track_caller]
+  pub(crate) fn misplaced_separator<'s>(
+    &self,
+    lexeme: Lexeme<rule::Any>,
+    at: impl Spanned<'s>,
+  ) -> Diagnostic {
+    self
+      .lint(
+        "E0015",
+        lexeme,
+        Level::Deny,
+        f!(
+          "unexpected digit separator in {}",
+          Expected::from(lexeme).for_user_diagnostic(self.spec)
+        ),
+      )
+      .at(at)
+      .reported_at(Location::caller())
+  }
+
+  /// Generates a "mixed scripts in identifier" lint.
+  #[cfg(feature = "confusables")]
+  #[track_caller]
+  pub(crate) fn mixed_script_ident<'s>(
+    &self,
+    lexeme: Lexeme<rule::Any>,
+    at: impl Spanned<'s>,
+    found: impl fmt::Display,
+    expected: impl fmt::Display,
+  ) -> Diagnostic {
+    let at = at.span();
+    self
+      .lint(
+        "E0016",
+        lexeme,
+        Level::Warn,
+        f!(
+          "mixed scripts in {}",
+          Expected::from(lexeme).for_user_diagnostic(self.spec)
+        ),
+      )
+      .saying(at, f!("{found} character{}", plural(at.text().chars().count())))
+      .note(f!("the characters before it are {expected}"))
+      .reported_at(Location::caller())
+  }
+
+  /// Generates a "confusable identifier" lint.
+  #[cfg(feature = "confusables")]
+  #[track_caller]
+  pub(crate) fn confusable_ident<'s>(
+    &self,
+    lexeme: Lexeme<rule::Any>,
+    at: impl Spanned<'s>,
+    lookalike: &str,
+  ) -> Diagnostic {
+    let at = at.span();
+    self
+      .lint(
+        "E0017",
+        lexeme,
+        Level::Warn,
+        f!(
+          "{} `{}` could be confused with `{lookalike}`",
+          Expected::from(lexeme).for_user_diagnostic(self.spec),
+          at.text(),
+        ),
+      )
       .at(at)
+      .note("every character in it resembles an ASCII character")
       .reported_at(Location::caller())
   }
 
+  /// Starts a diagnostic for a lint, whose severity depends on the level it
+  /// is configured at. Allowed lints produce a speculative diagnostic, which
+  /// is discarded unless committed.
+  fn lint(
+    &self,
+    code: &str,
+    lexeme: Lexeme<rule::Any>,
+    default: Level,
+    message: impl fmt::Display,
+  ) -> Diagnostic {
+    let diagnostic = match self.report.level_of(code, lexeme, default) {
+      Level::Allow => self.report.warn(message).speculate(),
+      Level::Warn => self.report.warn(message),
+      Level::Deny => self.report.error(message),
+    };
+    diagnostic.code(code)
+  }
+
   #[track_caller]
   pub(crate) fn ident_too_small<'s>(
     &self,
@@ -199,6 +338,7 @@ impl Builtins<'_> {
         if actual == 0 { yarn!("none") } else { yarn!("only {actual}") }
       ))
       .saying(at, f!("expected at least {min_len} here"))
+      .code("E0008")
       .reported_at(Location::caller());
 
     if actual == 0 {
@@ -208,6 +348,35 @@ impl Builtins<'_> {
     }
   }
 
+  #[track_caller]
+  pub(crate) fn inconsistent_dedent<'s1, 's2>(
+    &self,
+    at: impl Spanned<'s1>,
+    outer: Option<impl Spanned<'s2>>,
+  ) -> Diagnostic {
+    let diagnostic = self
+      .report
+      .error("dedent does not match any outer indentation level")
+      .saying(at, "dedented to here");
+
+    match outer {
+      Some(outer) => diagnostic.remark(outer, "nearest outer level is here"),
+      None => diagnostic,
+    }
+    .code("E0009")
+    .reported_at(Location::caller())
+  }
+
+  #[track_caller]
+  pub(crate) fn mixed_tabs<'s>(&self, at: impl Spanned<'s>) -> Diagnostic {
+    self
+      .report
+      .error("inconsistent use of tabs and spaces in indentation")
+      .saying(at, "this indentation depends on the width of a tab")
+      .code("E0010")
+      .reported_at(Location::caller())
+  }
+
   /// Generates an "invalid escape sequence" diagnostic.
   #[track_caller]
   pub fn invalid_escape<'s>(
@@ -220,6 +389,7 @@ impl Builtins<'_> {
       .report
       .error(f!("found an invalid escape sequence: `{at}`"))
       .saying(at, why)
+      .code("E0011")
       .reported_at(Location::caller())
   }
 
@@ -255,6 +425,7 @@ impl Builtins<'_> {
         if is_exc { "<" } else { "" },
         if is_inc { "=" } else { "" },
       ))
+      .code("E0012")
       .reported_at(Location::caller())
   }
 }
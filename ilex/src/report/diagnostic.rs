@@ -3,6 +3,7 @@ use std::mem;
 use std::panic;
 
 use crate::file;
+use crate::file::Span;
 use crate::file::Spanned;
 use crate::report::Report;
 
@@ -23,6 +24,9 @@ use crate::report::Report;
 /// the scope, or until [`Diagnostic::commit()`] is called. Once a diagnostic
 /// is added to a report, it cannot be modified.
 ///
+/// Diagnostics that have already been added to a report can be inspected via
+/// [`Report::diagnostics()`].
+///
 /// See e.g. [`Report::error()`].
 pub struct Diagnostic {
   pub(super) report: Report,
@@ -32,11 +36,44 @@ pub struct Diagnostic {
 
 pub use annotate_snippets::AnnotationType as Kind;
 
+/// The severity of a [`Diagnostic`], or of one of its notes.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Severity {
+  /// An error, such as one created with [`Report::error()`].
+  Error,
+  /// A warning, such as one created with [`Report::warn()`].
+  Warning,
+  /// A note, such as one created with [`Report::note()`].
+  Note,
+  /// A help tip, such as one added with [`Diagnostic::help()`].
+  Help,
+}
+
+impl From<Kind> for Severity {
+  fn from(kind: Kind) -> Self {
+    match kind {
+      Kind::Error => Self::Error,
+      Kind::Warning => Self::Warning,
+      Kind::Info | Kind::Note => Self::Note,
+      Kind::Help => Self::Help,
+    }
+  }
+}
+
+/// The code, message, and primary span of a diagnostic.
+pub type DedupKey = (Option<String>, String, Option<file::Span3>);
+
+#[derive(Clone)]
 pub struct Info {
   pub kind: Kind,
   pub message: String,
+  pub code: Option<String>,
   pub snippets: Vec<Vec<(file::Span3, String, Kind)>>,
+  pub labels: Vec<(file::Span3, String)>,
   pub notes: Vec<(String, Kind)>,
+  pub suggestions: Vec<(file::Span3, String)>,
+  /// How many identical diagnostics this one stands for, when deduplicating.
+  pub count: usize,
   pub reported_at: Option<&'static panic::Location<'static>>,
 }
 
@@ -45,16 +82,50 @@ impl Diagnostic {
     Diagnostic {
       report,
       speculative: false,
-      info: Info {
-        message,
-        kind,
-        snippets: Vec::new(),
-        notes: Vec::new(),
-        reported_at: None,
-      },
+      info: Info::new(kind, message),
     }
   }
 
+  /// Returns this diagnostic's severity.
+  pub fn severity(&self) -> Severity {
+    self.info.kind.into()
+  }
+
+  /// Returns this diagnostic's top-level message.
+  pub fn message(&self) -> &str {
+    &self.info.message
+  }
+
+  /// Returns the span this diagnostic was first reported at, if any.
+  pub fn primary_span(&self) -> Option<Span> {
+    Some(self.info.primary()?.get(&self.report.ctx))
+  }
+
+  /// Returns every span this diagnostic points to other than the
+  /// [primary one][Self::primary_span], along with the message attached to it,
+  /// which may be empty.
+  ///
+  /// This includes spans added with [`Diagnostic::saying()`],
+  /// [`Diagnostic::remark()`] and [`Diagnostic::label()`], in the order they
+  /// are rendered in.
+  pub fn labels(&self) -> impl Iterator<Item = (Span, &str)> + '_ {
+    self
+      .info
+      .all_snippets()
+      .into_iter()
+      .flatten()
+      .skip(1)
+      .map(|(span, text, _)| (span.get(&self.report.ctx), text))
+  }
+
+  /// Returns the notes and help tips appended to the bottom of this diagnostic.
+  pub fn notes(&self) -> impl Iterator<Item = (Severity, String)> + '_ {
+    self.info.notes.iter().map(|(note, kind)| {
+      // Undo the zero-width space hack in Diagnostic::note().
+      ((*kind).into(), note.replace("_\u{200b}_", "__"))
+    })
+  }
+
   /// Marks this diagnostic as "speculative", meaning that it will not be
   /// applied until [`Diagnostic::commit()`] is called.
   pub fn speculate(mut self) -> Self {
@@ -69,6 +140,18 @@ impl Diagnostic {
     drop(self);
   }
 
+  /// Sets this diagnostic's code, such as `E0012`.
+  ///
+  /// Codes are rendered next to the diagnostic's severity, e.g.
+  /// `error[E0012]: ...`, and give tools a stable way to refer to a kind of
+  /// diagnostic. The diagnostics generated by [`Builtins`] all have codes.
+  ///
+  /// [`Builtins`]: crate::report::Builtins
+  pub fn code(mut self, code: impl fmt::Display) -> Self {
+    self.info.code = Some(code.to_string());
+    self
+  }
+
   /// Adds a new relevant snippet at the given location.
   pub fn at<'s>(self, span: impl Spanned<'s>) -> Self {
     self.saying(span, "")
@@ -93,6 +176,23 @@ impl Diagnostic {
     self.snippet(span, message, Some(Kind::Help))
   }
 
+  /// Adds a labeled secondary span to this diagnostic.
+  ///
+  /// Unlike [`Diagnostic::remark()`], labels are always shown alongside the
+  /// diagnostic's first snippet, ordered by position rather than by the order
+  /// they were added in. Each label gets its own underline and message.
+  pub fn label<'s>(
+    mut self,
+    span: impl Spanned<'s>,
+    message: impl fmt::Display,
+  ) -> Self {
+    self
+      .info
+      .labels
+      .push((span.span().span3(), message.to_string()));
+    self
+  }
+
   fn snippet<'s>(
     mut self,
     span: impl Spanned<'s>,
@@ -141,6 +241,25 @@ impl Diagnostic {
     self
   }
 
+  /// Suggests a fix for this diagnostic: replacing the text at `span` with
+  /// `replacement`.
+  ///
+  /// An empty `span` suggests an insertion, and an empty `replacement`
+  /// suggests a deletion. Each call adds a separate, alternative suggestion;
+  /// they are rendered as help notes, and exposed as edits in machine-readable
+  /// output.
+  pub fn suggest<'s>(
+    mut self,
+    span: impl Spanned<'s>,
+    replacement: impl fmt::Display,
+  ) -> Self {
+    self
+      .info
+      .suggestions
+      .push((span.span().span3(), replacement.to_string()));
+    self
+  }
+
   /// Updates the "reported at" information for this diagnostic.
   ///
   /// This information is only intended to be used for tool developers to
@@ -153,18 +272,75 @@ impl Diagnostic {
   }
 }
 
+impl Info {
+  pub fn new(kind: Kind, message: String) -> Self {
+    Info {
+      message,
+      kind,
+      code: None,
+      snippets: Vec::new(),
+      labels: Vec::new(),
+      notes: Vec::new(),
+      suggestions: Vec::new(),
+      count: 1,
+      reported_at: None,
+    }
+  }
+
+  /// Returns what identical diagnostics have in common, for deduplicating
+  /// them.
+  pub fn dedup_key(&self) -> DedupKey {
+    (self.code.clone(), self.message.clone(), self.primary())
+  }
+
+  /// Returns the span this diagnostic was first reported at, if any.
+  pub fn primary(&self) -> Option<file::Span3> {
+    self
+      .snippets
+      .iter()
+      .flatten()
+      .next()
+      .map(|(span, _, _)| *span)
+  }
+
+  /// Returns this diagnostic's snippets, with its labels merged into the first
+  /// one, sorted by position.
+  pub fn all_snippets(&self) -> Vec<Vec<(file::Span3, &str, Kind)>> {
+    let mut snippets = self
+      .snippets
+      .iter()
+      .map(|snips| {
+        snips
+          .iter()
+          .map(|(span, text, kind)| (*span, text.as_str(), *kind))
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+
+    if !self.labels.is_empty() {
+      let mut labels = self
+        .labels
+        .iter()
+        .map(|(span, text)| (*span, text.as_str(), Kind::Help))
+        .collect::<Vec<_>>();
+      labels.sort_by_key(|&(span, _, _)| span);
+
+      match snippets.first_mut() {
+        Some(first) => first.extend(labels),
+        None => snippets.push(labels),
+      }
+    }
+
+    snippets
+  }
+}
+
 impl Drop for Diagnostic {
   fn drop(&mut self) {
     if !self.speculative {
       self.report.state.insert_diagnostic(mem::replace(
         &mut self.info,
-        Info {
-          message: "".to_string(),
-          kind: Kind::Error,
-          snippets: Vec::new(),
-          notes: Vec::new(),
-          reported_at: None,
-        },
+        Info::new(Kind::Error, String::new()),
       ));
     }
   }
@@ -14,19 +14,25 @@ use std::io;
 use std::panic;
 use std::panic::Location;
 use std::process;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use crate::file::Context;
+use crate::rule;
+use crate::spec::Lexeme;
 use crate::spec::Spec;
 
 mod builtin;
 mod diagnostic;
 mod render;
+#[cfg(feature = "serde")]
+mod serialize;
 
 pub use builtin::Builtins;
 pub use builtin::Expected;
 pub use diagnostic::Diagnostic;
 use diagnostic::Kind;
+pub use diagnostic::Severity;
 
 #[cfg(doc)]
 use crate::Span;
@@ -49,6 +55,24 @@ pub struct Options {
   /// code it was reported. `ilex` makes a best-case effort to ensure this
   /// location is in *your* code.
   pub show_report_locations: bool,
+  /// How many lines of source to show before and after each annotated span.
+  ///
+  /// When this is zero, lines that do not contain an annotation are elided
+  /// with a `...` where possible, such as in the middle of multi-line spans.
+  /// Otherwise, multi-line spans are shown in full, and only lines far from
+  /// any span are skipped.
+  pub context_lines: usize,
+  /// Whether to merge diagnostics with the same code, message, and primary
+  /// span into one, which notes how many times it was reported.
+  pub deduplicate: bool,
+  /// Whether to sort diagnostics by the file and offset of their primary span,
+  /// rather than rendering them in the order they were reported. Diagnostics
+  /// without a span come last.
+  pub sort_by_position: bool,
+  /// How many columns a tab takes up when rendered. Tabs in source code are
+  /// replaced with this many spaces, so that underlines line up with the text
+  /// they point to.
+  pub tab_width: usize,
 }
 
 impl Default for Options {
@@ -56,10 +80,59 @@ impl Default for Options {
     Self {
       color: true,
       show_report_locations: cfg!(debug_assertions),
+      context_lines: 0,
+      deduplicate: false,
+      sort_by_position: false,
+      tab_width: 4,
     }
   }
 }
 
+/// How a [`Report`] should treat diagnostics for a particular [`Lint`].
+///
+/// See [`Report::set_level()`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Level {
+  /// Discard the diagnostic.
+  Allow,
+  /// Report the diagnostic as a warning.
+  Warn,
+  /// Report the diagnostic as an error.
+  Deny,
+}
+
+/// A group of diagnostics whose [`Level`] can be configured.
+///
+/// Only some [builtin diagnostics][Builtins] are lints; these are marked as such
+/// in the table of diagnostic codes. A lint can be configured either by its
+/// code, or by the lexeme it was reported for, in which case it applies to
+/// every lint for that lexeme.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Lint {
+  /// Every diagnostic with the given code.
+  Code(String),
+  /// Every lint reported for the given lexeme.
+  Lexeme(Lexeme<rule::Any>),
+}
+
+impl From<&str> for Lint {
+  fn from(code: &str) -> Self {
+    Self::Code(code.to_string())
+  }
+}
+
+impl From<String> for Lint {
+  fn from(code: String) -> Self {
+    Self::Code(code)
+  }
+}
+
+impl<R> From<Lexeme<R>> for Lint {
+  fn from(lexeme: Lexeme<R>) -> Self {
+    Self::Lexeme(lexeme.any())
+  }
+}
+
 impl Report {
   pub(crate) fn copy(&self) -> Report {
     Self {
@@ -107,6 +180,79 @@ impl Report {
     Diagnostic::new(self.copy(), kind, message).reported_at(Location::caller())
   }
 
+  /// Sets the level for a lint, overriding its default.
+  ///
+  /// If a lint's level is configured both for its lexeme and its code, the
+  /// level for the lexeme wins.
+  ///
+  /// ```
+  /// # use ilex::report::Level;
+  /// let ctx = ilex::Context::new();
+  /// let report = ctx.new_report();
+  ///
+  /// // Allow non-ASCII characters in ASCII-only identifiers, but warn about it.
+  /// report.set_level("E0007", Level::Warn);
+  /// ```
+  pub fn set_level(&self, lint: impl Into<Lint>, level: Level) {
+    let mut levels = self.state.levels.lock().unwrap();
+    levels.insert(lint.into(), level);
+  }
+
+  /// Turns every warning added to this report from now on into an error.
+  ///
+  /// This includes both lints set to [`Level::Warn`] and diagnostics created
+  /// with [`Report::warn()`].
+  pub fn deny_warnings(&self) {
+    self.state.deny_warnings.store(true, Ordering::SeqCst);
+  }
+
+  /// Limits how many errors this report will hold.
+  ///
+  /// Once `n` errors have been added, any further errors are discarded, and a
+  /// final note is added saying so. The report still counts as having errors,
+  /// so operations like lexing still fail, but they will run to completion.
+  ///
+  /// ```
+  /// let ctx = ilex::Context::new();
+  /// let report = ctx.new_report();
+  /// report.max_errors(2);
+  ///
+  /// for i in 0..10 {
+  ///   report.error(format!("error #{i}"));
+  /// }
+  ///
+  /// let messages = report
+  ///   .diagnostics()
+  ///   .iter()
+  ///   .map(|d| d.message().to_string())
+  ///   .collect::<Vec<_>>();
+  /// assert_eq!(messages, ["error #0", "error #1", "too many errors, stopping"]);
+  /// ```
+  pub fn max_errors(&self, n: usize) {
+    self.state.max_errors.store(n, Ordering::SeqCst);
+  }
+
+  /// Returns whether this report has started discarding errors because it has
+  /// hit its [limit][Report::max_errors].
+  pub(crate) fn too_many_errors(&self) -> bool {
+    self.state.too_many_errors()
+  }
+
+  /// Returns the configured level for a lint.
+  pub(crate) fn level_of<R>(
+    &self,
+    code: &str,
+    lexeme: Lexeme<R>,
+    default: Level,
+  ) -> Level {
+    let levels = self.state.levels.lock().unwrap();
+    levels
+      .get(&Lint::from(lexeme))
+      .or_else(|| levels.get(&Lint::from(code)))
+      .copied()
+      .unwrap_or(default)
+  }
+
   /// Returns a [`Fatal`] regardless of whether this report contains any errors.
   pub fn fatal<T>(&self) -> Result<T, Fatal> {
     Err(Fatal(self.copy()))
@@ -136,6 +282,45 @@ impl Report {
     self.state.collate()
   }
 
+  /// Returns a snapshot of every diagnostic added to this report so far, in
+  /// the order they would be rendered in.
+  ///
+  /// The returned diagnostics are [speculative][Diagnostic::speculate], so
+  /// dropping them does not add them to the report a second time.
+  ///
+  /// ```
+  /// # use ilex::report::Severity;
+  /// let ctx = ilex::Context::new();
+  /// let report = ctx.new_report();
+  /// let file = ctx.new_file("x.txt", "hello world");
+  ///
+  /// report.error("bad word")
+  ///   .saying(file.span(6..11), "this one")
+  ///   .note("try another word");
+  ///
+  /// let diagnostics = report.diagnostics();
+  /// assert_eq!(diagnostics.len(), 1);
+  /// assert_eq!(diagnostics[0].severity(), Severity::Error);
+  /// assert_eq!(diagnostics[0].message(), "bad word");
+  /// assert_eq!(diagnostics[0].primary_span().unwrap().text(), "world");
+  /// assert_eq!(
+  ///   diagnostics[0].notes().collect::<Vec<_>>(),
+  ///   [(Severity::Note, "try another word".to_string())],
+  /// );
+  /// ```
+  pub fn diagnostics(&self) -> Vec<Diagnostic> {
+    self.state.collate();
+    let diagnostics = self.state.sorted_diagnostics.lock().unwrap();
+    diagnostics
+      .iter()
+      .map(|info| Diagnostic {
+        report: self.copy(),
+        info: info.clone(),
+        speculative: true,
+      })
+      .collect()
+  }
+
   /// Writes out the contents of this diagnostic to `sink`.
   pub fn write_out(&self, sink: impl io::Write) -> io::Result<()> {
     render::finish(self, sink)
@@ -1,8 +1,14 @@
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::io;
+use std::iter;
 use std::mem;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Mutex;
 
@@ -16,15 +22,23 @@ use annotate_snippets::Snippet;
 use annotate_snippets::SourceAnnotation;
 
 use crate::report::diagnostic;
+use crate::report::diagnostic::DedupKey;
 use crate::report::diagnostic::Info;
 use crate::report::diagnostic::Kind;
+use crate::report::Level;
+use crate::report::Lint;
 use crate::report::Options;
 use crate::report::Report;
 
 pub struct State {
   pub opts: Options,
+  pub levels: Mutex<HashMap<Lint, Level>>,
+  pub deny_warnings: AtomicBool,
+  pub max_errors: AtomicUsize,
   has_error: AtomicBool,
-  sorted_diagnostics: Mutex<Vec<diagnostic::Info>>,
+  errors: AtomicUsize,
+  counted_errors: Mutex<HashSet<DedupKey>>,
+  pub sorted_diagnostics: Mutex<Vec<diagnostic::Info>>,
   recent_diagnostics: Mutex<Vec<(u64, diagnostic::Info)>>,
 }
 
@@ -32,7 +46,12 @@ impl State {
   pub fn new(opts: Options) -> Self {
     Self {
       opts,
+      levels: Default::default(),
+      deny_warnings: AtomicBool::new(false),
+      max_errors: AtomicUsize::new(usize::MAX),
       has_error: AtomicBool::new(false),
+      errors: AtomicUsize::new(0),
+      counted_errors: Default::default(),
       sorted_diagnostics: Default::default(),
       recent_diagnostics: Default::default(),
     }
@@ -42,6 +61,12 @@ impl State {
     self.has_error.load(Ordering::SeqCst)
   }
 
+  /// Returns whether errors have started being discarded because there are
+  /// too many of them.
+  pub fn too_many_errors(&self) -> bool {
+    self.errors.load(Ordering::SeqCst) > self.max_errors.load(Ordering::SeqCst)
+  }
+
   /// Collates all of the "unsorted diagnostics" into the "sorted diagnostics",
   /// sorting them by thread id. This ensures that all diagnostics coming from
   /// a particular thread are together.
@@ -51,11 +76,61 @@ impl State {
 
     recent.sort_by_key(|&(id, _)| id);
     sorted.extend(recent.drain(..).map(|(_, i)| i));
+
+    if self.opts.deduplicate {
+      let mut seen = HashMap::<_, usize>::new();
+      let mut deduped = Vec::<Info>::new();
+      for info in sorted.drain(..) {
+        match seen.entry(info.dedup_key()) {
+          Entry::Occupied(e) => deduped[*e.get()].count += info.count,
+          Entry::Vacant(e) => {
+            e.insert(deduped.len());
+            deduped.push(info);
+          }
+        }
+      }
+      *sorted = deduped;
+    }
+
+    if self.opts.sort_by_position {
+      // This sort is stable, so diagnostics at the same position stay in the
+      // order they were reported in.
+      sorted.sort_by_key(|info| {
+        let primary = info.primary();
+        (primary.is_none(), primary)
+      });
+    }
   }
 
-  pub fn insert_diagnostic(&self, info: Info) {
+  pub fn insert_diagnostic(&self, mut info: Info) {
+    if info.kind == Kind::Warning && self.deny_warnings.load(Ordering::SeqCst) {
+      info.kind = Kind::Error;
+    }
+
     if info.kind == Kind::Error {
       self.has_error.store(true, Ordering::SeqCst);
+
+      // When deduplicating, a repeat of an error that has already been counted
+      // is merged into it when rendering, so it does not count towards the
+      // limit.
+      let mut counted = self.counted_errors.lock().unwrap();
+      let key = self.opts.deduplicate.then(|| info.dedup_key());
+      if !key.as_ref().is_some_and(|key| counted.contains(key)) {
+        let errors = self.errors.fetch_add(1, Ordering::SeqCst);
+        let max = self.max_errors.load(Ordering::SeqCst);
+        if errors > max {
+          return;
+        }
+        if errors == max {
+          // Replace the first error over the limit with a note explaining why
+          // the rest are missing, reported where that error was.
+          let reported_at = info.reported_at;
+          info = Info::new(Kind::Note, "too many errors, stopping".into());
+          info.reported_at = reported_at;
+        } else {
+          counted.extend(key);
+        }
+      }
     }
 
     static COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -122,7 +197,7 @@ pub fn render_fmt(
 
     let mut snippet = Snippet {
       title: Some(Annotation {
-        id: None,
+        id: e.code.as_deref(),
         label: Some(&e.message),
         annotation_type: e.kind,
       }),
@@ -130,7 +205,8 @@ pub fn render_fmt(
       slices: Vec::new(),
     };
 
-    for snips in &e.snippets {
+    let snippets = e.all_snippets();
+    for snips in &snippets {
       let mut cur_file = None;
       let mut cur_slice = None::<Slice>;
       let mut has_eof = false;
@@ -197,8 +273,20 @@ pub fn render_fmt(
       }
     }
 
+    if opts.context_lines > 0 {
+      snippet.slices = mem::take(&mut snippet.slices)
+        .into_iter()
+        .flat_map(|slice| with_context(slice, opts.context_lines))
+        .collect();
+    }
+
     // Crop the starts of each slice to only incorporate the annotations.
     for slice in &mut snippet.slices {
+      if !slice.fold {
+        // Already cropped by with_context().
+        continue;
+      }
+
       let earliest_start = slice
         .annotations
         .iter()
@@ -222,6 +310,27 @@ pub fn render_fmt(
       }
     }
 
+    // annotate-snippets measures annotations in chars, and works out how wide
+    // each one is when drawing carets, except for tabs, which it cannot know
+    // the width of. So, expand tabs into spaces, and convert byte offsets into
+    // char offsets.
+    let sources = snippet
+      .slices
+      .iter()
+      .map(|slice| expand_tabs(slice.source, opts.tab_width))
+      .collect::<Vec<_>>();
+    for (slice, source) in snippet.slices.iter_mut().zip(&sources) {
+      let col = |byte: usize| {
+        let prefix = &slice.source[..byte];
+        let tabs = prefix.matches('\t').count();
+        prefix.chars().count() - tabs + tabs * opts.tab_width
+      };
+      for a in &mut slice.annotations {
+        a.range = (col(a.range.0), col(a.range.1));
+      }
+      slice.source = source;
+    }
+
     for (note, kind) in &e.notes {
       snippet.footer.push(Annotation {
         id: None,
@@ -230,6 +339,41 @@ pub fn render_fmt(
       });
     }
 
+    let suggestions = e
+      .suggestions
+      .iter()
+      .map(|(span, replacement)| {
+        let span = span.get(&report.ctx);
+        let help = if span.is_empty() {
+          format!("insert `{replacement}`")
+        } else if replacement.is_empty() {
+          format!("remove `{}`", span.text())
+        } else {
+          format!("replace `{}` with `{replacement}`", span.text())
+        };
+
+        // HACK: See Diagnostic::note().
+        help.replace("__", "_\u{200b}_")
+      })
+      .collect::<Vec<_>>();
+    for help in &suggestions {
+      snippet.footer.push(Annotation {
+        id: None,
+        label: Some(help),
+        annotation_type: AnnotationType::Help,
+      });
+    }
+
+    let repeats;
+    if e.count > 1 {
+      repeats = format!("reported {} times", e.count);
+      snippet.footer.push(Annotation {
+        id: None,
+        label: Some(&repeats),
+        annotation_type: AnnotationType::Note,
+      });
+    }
+
     let footer;
     if opts.show_report_locations {
       footer = format!("reported at: {}", e.reported_at.unwrap());
@@ -264,3 +408,68 @@ pub fn render_fmt(
 
   Ok(())
 }
+
+/// Replaces every tab in `source` with `tab_width` spaces.
+fn expand_tabs(source: &str, tab_width: usize) -> Cow<str> {
+  if !source.contains('\t') {
+    return Cow::Borrowed(source);
+  }
+  Cow::Owned(source.replace('\t', &" ".repeat(tab_width)))
+}
+
+/// Splits `slice` into one slice per group of nearby annotations, each of which
+/// includes `context` lines of source before and after it.
+///
+/// The returned slices are not folded, since folding would discard the context
+/// lines; instead, lines far away from any annotation are skipped between
+/// slices.
+fn with_context(slice: Slice, context: usize) -> Vec<Slice> {
+  let src = slice.source;
+
+  // The byte offset at which each line starts.
+  let lines = iter::once(0)
+    .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+    .filter(|&i| i < src.len())
+    .collect::<Vec<_>>();
+  let line_of = |offset: usize| lines.partition_point(|&i| i <= offset) - 1;
+
+  let mut annotations = slice.annotations;
+  annotations.sort_by_key(|a| a.range.0);
+
+  // Group together annotations whose context overlaps, or would be adjacent.
+  let mut groups = Vec::<(usize, usize, Vec<SourceAnnotation>)>::new();
+  for a in annotations {
+    let first = line_of(a.range.0).saturating_sub(context);
+    let last = (line_of(a.range.1 - 1) + context).min(lines.len() - 1);
+    match groups.last_mut() {
+      Some((_, end, group)) if first <= *end + 1 => {
+        *end = (*end).max(last);
+        group.push(a);
+      }
+      _ => groups.push((first, last, vec![a])),
+    }
+  }
+
+  groups
+    .into_iter()
+    .enumerate()
+    .map(|(i, (first, last, mut annotations))| {
+      let start = lines[first];
+      let end = lines.get(last + 1).copied().unwrap_or(src.len());
+      for a in &mut annotations {
+        a.range.0 -= start;
+        a.range.1 -= start;
+      }
+
+      Slice {
+        source: &src[start..end],
+        line_start: slice.line_start + first,
+        // Only the first group gets a file name; the rest are presented as
+        // a continuation of it.
+        origin: slice.origin.filter(|_| i == 0),
+        annotations,
+        fold: false,
+      }
+    })
+    .collect()
+}
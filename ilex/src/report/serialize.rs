@@ -0,0 +1,396 @@
+//! `serde` support for [`Report`]s, including SARIF output.
+
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::file::ColumnUnit;
+use crate::file::Location;
+use crate::file::Span;
+use crate::file::Span3;
+use crate::report::diagnostic::Info;
+use crate::report::diagnostic::Kind;
+use crate::report::Report;
+
+/// The serialized form of a single diagnostic.
+#[derive(Serialize)]
+struct Wire<'a> {
+  severity: &'static str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  code: Option<&'a str>,
+  message: &'a str,
+  /// The first span the diagnostic was reported at, if any.
+  primary: Option<WireSpan<'a>>,
+  /// Every other span, in the order they were added.
+  related: Vec<WireSpan<'a>>,
+  notes: Vec<WireNote>,
+  /// Alternative fixes for the diagnostic.
+  suggestions: Vec<WireEdit<'a>>,
+  /// How many identical diagnostics were merged into this one.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  count: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  reported_at: Option<String>,
+}
+
+#[derive(Serialize)]
+struct WireSpan<'a> {
+  file: &'a str,
+  range: [usize; 2],
+  start: WireLocation,
+  end: WireLocation,
+  severity: &'static str,
+  label: &'a str,
+}
+
+#[derive(Serialize)]
+struct WireEdit<'a> {
+  file: &'a str,
+  range: [usize; 2],
+  start: WireLocation,
+  end: WireLocation,
+  replacement: &'a str,
+}
+
+#[derive(Serialize)]
+struct WireLocation {
+  line: u32,
+  column: u32,
+}
+
+#[derive(Serialize)]
+struct WireNote {
+  severity: &'static str,
+  message: String,
+}
+
+impl<'a> Wire<'a> {
+  fn new(report: &'a Report, info: &'a Info) -> Self {
+    let mut spans = info
+      .all_snippets()
+      .into_iter()
+      .flatten()
+      .map(|(span, label, kind)| WireSpan::new(report, span, label, kind));
+
+    Wire {
+      severity: severity(info.kind),
+      code: info.code.as_deref(),
+      message: &info.message,
+      primary: spans.next(),
+      related: spans.collect(),
+      notes: info
+        .notes
+        .iter()
+        .map(|(note, kind)| WireNote {
+          severity: severity(*kind),
+          // Undo the zero-width space hack in Diagnostic::note(); it only
+          // exists for the benefit of the terminal renderer.
+          message: note.replace("_\u{200b}_", "__"),
+        })
+        .collect(),
+      suggestions: info
+        .suggestions
+        .iter()
+        .map(|(span, replacement)| {
+          let span = WireSpan::new(report, *span, "", Kind::Help);
+          WireEdit {
+            file: span.file,
+            range: span.range,
+            start: span.start,
+            end: span.end,
+            replacement,
+          }
+        })
+        .collect(),
+      count: Some(info.count).filter(|&n| n > 1),
+      reported_at: info.reported_at.map(ToString::to_string),
+    }
+  }
+}
+
+impl<'a> WireSpan<'a> {
+  fn new(report: &'a Report, span: Span3, label: &'a str, kind: Kind) -> Self {
+    let span = span.get(&report.ctx);
+    let loc =
+      |loc: Location| WireLocation { line: loc.line, column: loc.column };
+
+    WireSpan {
+      file: span.file().path().as_str(),
+      range: [span.start(), span.end()],
+      start: loc(span.start_location(ColumnUnit::Chars)),
+      end: loc(span.end_location(ColumnUnit::Chars)),
+      severity: severity(kind),
+      label,
+    }
+  }
+}
+
+fn severity(kind: Kind) -> &'static str {
+  match kind {
+    Kind::Error => "error",
+    Kind::Warning => "warning",
+    Kind::Info => "info",
+    Kind::Note => "note",
+    Kind::Help => "help",
+  }
+}
+
+/// Serializes every diagnostic in the report, in the order they would be
+/// rendered, as a sequence.
+impl Serialize for Report {
+  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    self.state.collate();
+    let diagnostics = self.state.sorted_diagnostics.lock().unwrap();
+    s.collect_seq(diagnostics.iter().map(|info| Wire::new(self, info)))
+  }
+}
+
+/// A SARIF 2.1.0 log with a single run.
+///
+/// See <https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html>.
+#[derive(Serialize)]
+struct Sarif<'a> {
+  #[serde(rename = "$schema")]
+  schema: &'static str,
+  version: &'static str,
+  runs: [SarifRun<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRun<'a> {
+  tool: SarifTool,
+  column_kind: &'static str,
+  results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+  driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+  name: &'static str,
+  version: &'static str,
+  information_uri: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  rule_id: Option<&'a str>,
+  level: &'static str,
+  message: SarifMessage,
+  locations: Vec<SarifLocation<'a>>,
+  related_locations: Vec<SarifLocation<'a>>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  fixes: Vec<SarifFix<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifFix<'a> {
+  artifact_changes: [SarifArtifactChange<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactChange<'a> {
+  artifact_location: SarifArtifactLocation<'a>,
+  replacements: [SarifReplacement<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifReplacement<'a> {
+  deleted_region: SarifRegion,
+  inserted_content: SarifContent<'a>,
+}
+
+#[derive(Serialize)]
+struct SarifContent<'a> {
+  text: &'a str,
+}
+
+#[derive(Serialize)]
+struct SarifMessage {
+  text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation<'a> {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  id: Option<usize>,
+  physical_location: SarifPhysicalLocation<'a>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  message: Option<SarifMessage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation<'a> {
+  artifact_location: SarifArtifactLocation<'a>,
+  region: SarifRegion,
+}
+
+#[derive(Serialize)]
+struct SarifArtifactLocation<'a> {
+  uri: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+  start_line: u32,
+  start_column: u32,
+  end_line: u32,
+  end_column: u32,
+  byte_offset: usize,
+  byte_length: usize,
+}
+
+impl SarifRegion {
+  fn new(span: Span) -> Self {
+    let start = span.start_location(ColumnUnit::Utf16);
+    let end = span.end_location(ColumnUnit::Utf16);
+    SarifRegion {
+      start_line: start.line,
+      start_column: start.column,
+      end_line: end.line,
+      end_column: end.column,
+      byte_offset: span.start(),
+      byte_length: span.len(),
+    }
+  }
+}
+
+impl<'a> SarifResult<'a> {
+  fn new(report: &'a Report, info: &'a Info) -> Self {
+    // SARIF has no notion of notes, so they are appended to the message, the
+    // way the terminal renderer displays them.
+    let mut text = info.message.clone();
+    for (note, kind) in &info.notes {
+      text.push('\n');
+      text.push_str(severity(*kind));
+      text.push_str(": ");
+      text.push_str(&note.replace("_\u{200b}_", "__"));
+    }
+
+    let mut locations =
+      info.all_snippets().into_iter().flatten().enumerate().map(
+        |(i, (span, label, _))| {
+          let span = span.get(&report.ctx);
+          SarifLocation {
+            id: Some(i).filter(|&i| i > 0),
+            physical_location: SarifPhysicalLocation {
+              artifact_location: SarifArtifactLocation {
+                uri: span.file().path().as_str(),
+              },
+              region: SarifRegion::new(span),
+            },
+            message: (!label.is_empty())
+              .then(|| SarifMessage { text: label.to_string() }),
+          }
+        },
+      );
+
+    SarifResult {
+      rule_id: info.code.as_deref(),
+      level: match info.kind {
+        Kind::Error => "error",
+        Kind::Warning => "warning",
+        _ => "note",
+      },
+      message: SarifMessage { text },
+      locations: locations.next().into_iter().collect(),
+      related_locations: locations.collect(),
+      fixes: info
+        .suggestions
+        .iter()
+        .map(|(span, replacement)| {
+          let span = span.get(&report.ctx);
+          SarifFix {
+            artifact_changes: [SarifArtifactChange {
+              artifact_location: SarifArtifactLocation {
+                uri: span.file().path().as_str(),
+              },
+              replacements: [SarifReplacement {
+                deleted_region: SarifRegion::new(span),
+                inserted_content: SarifContent { text: replacement },
+              }],
+            }],
+          }
+        })
+        .collect(),
+    }
+  }
+}
+
+impl Report {
+  /// Renders this report's diagnostics as a JSON array, for consumption by
+  /// other tools.
+  ///
+  /// Each diagnostic records its severity, code (if any), message, notes,
+  /// spans, and suggested edits. The first span a diagnostic was reported at
+  /// is its `primary` span; the rest, followed by its
+  /// [labels][crate::report::Diagnostic::label], are `related`. Spans are given
+  /// both as byte ranges and as line/column pairs, with columns counted in
+  /// `char`s.
+  ///
+  /// ```
+  /// let ctx = ilex::Context::new();
+  /// let report = ctx.new_report_with(ilex::report::Options {
+  ///   show_report_locations: false,
+  ///   ..Default::default()
+  /// });
+  /// let file = ctx.new_file("x.txt", "hello world");
+  ///
+  /// report.error("bad word").saying(file.span(6..11), "this one");
+  ///
+  /// assert_eq!(
+  ///   report.to_json(),
+  ///   r#"[{"severity":"error","message":"bad word","primary":{"file":"x.txt","range":[6,11],"start":{"line":1,"column":7},"end":{"line":1,"column":12},"severity":"error","label":"this one"},"related":[],"notes":[],"suggestions":[]}]"#,
+  /// );
+  /// ```
+  pub fn to_json(&self) -> String {
+    serde_json::to_string(self).unwrap()
+  }
+
+  /// Renders this report's diagnostics as a [SARIF 2.1.0][sarif] log, which
+  /// code review tools can use to annotate changes.
+  ///
+  /// Each diagnostic becomes a `result`, whose `ruleId` is the diagnostic's
+  /// [code][crate::report::Diagnostic::code], if it has one. Its first span is the result's
+  /// location, and any other spans and labels become `relatedLocations`.
+  /// Suggestions become `fixes`. Columns are counted in UTF-16 code units,
+  /// SARIF's default.
+  ///
+  /// [sarif]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+  pub fn to_sarif(&self) -> String {
+    self.state.collate();
+    let diagnostics = self.state.sorted_diagnostics.lock().unwrap();
+    let sarif = Sarif {
+      schema: "https://json.schemastore.org/sarif-2.1.0.json",
+      version: "2.1.0",
+      runs: [SarifRun {
+        tool: SarifTool {
+          driver: SarifDriver {
+            name: "ilex",
+            version: env!("CARGO_PKG_VERSION"),
+            information_uri: env!("CARGO_PKG_HOMEPAGE"),
+          },
+        },
+        column_kind: "utf16CodeUnits",
+        results: diagnostics
+          .iter()
+          .map(|info| SarifResult::new(self, info))
+          .collect(),
+      }],
+    };
+
+    serde_json::to_string(&sarif).unwrap()
+  }
+}
@@ -2,7 +2,6 @@
 
 use std::collections::HashMap;
 use std::iter;
-use std::sync::OnceLock;
 
 use byteyarn::Yarn;
 use regex_automata::hybrid;
@@ -19,14 +18,16 @@ use regex_syntax::hir::Hir;
 use regex_syntax::hir::Repetition;
 
 use crate::rt::lexer::Lexer;
-use crate::rt::unicode;
 use crate::rule::Affixes;
 use crate::rule::Any;
+use crate::rule::Bracket;
 use crate::rule::BracketKind;
+use crate::rule::Case;
 use crate::rule::Digital;
 use crate::rule::Digits;
 use crate::rule::Ident;
 use crate::spec::Lexeme;
+use crate::spec::XidRanges;
 
 /// A compiled DFA for a spec.
 ///
@@ -120,13 +121,17 @@ impl Dfa {
   }
 }
 
-pub fn compile(rules: &[Any]) -> Dfa {
+pub fn compile(rules: &[Any], xid: XidRanges<&[(char, char)]>) -> Dfa {
   let mut patterns = Vec::new();
   let mut closers = Vec::new();
 
+  let xid = Xid {
+    start: class_from_table(xid.start),
+    cont: class_from_table(xid.cont),
+  };
   for (lexeme, rule) in rules.iter().enumerate() {
     let lexeme = Lexeme::new(lexeme as i32);
-    let rule = compile_rule(rule);
+    let rule = compile_rule(rule, &xid);
     patterns.push(rule.pat);
     if let Some(close) = rule.close {
       closers.push((lexeme, close));
@@ -161,9 +166,15 @@ struct Rule {
   close: Option<Hir>,
 }
 
-fn compile_rule(rule: &Any) -> Rule {
+/// Classes for the characters that can start and continue an identifier.
+struct Xid {
+  start: Hir,
+  cont: Hir,
+}
+
+fn compile_rule(rule: &Any, xid: &Xid) -> Rule {
   let (pat, close) = match rule {
-    Any::Keyword(rule) => (lit(&rule.value), None),
+    Any::Keyword(rule) => (lit_in(&rule.value, rule.case), None),
 
     Any::LineEnd(rule) if rule.cancel.is_empty() => (lit(&"\n".into()), None),
 
@@ -171,21 +182,26 @@ fn compile_rule(rule: &Any) -> Rule {
       (Hir::alternation(vec![lit(&rule.cancel), lit(&"\n".into())]), None)
     }
 
+    Any::Regex(rule) => (rule.hir.clone(), None),
+
+    // These are synthesized by the lexer, and never matched directly.
+    Any::Indent(_) | Any::Dedent(_) => (Hir::fail(), None),
+
     Any::Comment(rule) => {
       // We can just throw the bracket in, regardless of whether it's a line
       // comment. Because of how the outer lexer loop works, we will run the DFA
       // on a string with leading whitespace, so the ("//", "\n") pair from a
       // line comment won't generate weird diagnostics when it hits a newline.
-      let (open, close) = compile_bracket(&rule.bracket.kind);
+      let (open, close) = compile_bracket(&rule.bracket, xid);
       (open, Some(close))
     }
 
     Any::Bracket(rule) => {
-      let (open, close) = compile_bracket(&rule.kind);
+      let (open, close) = compile_bracket(rule, xid);
       (open, Some(close))
     }
 
-    Any::Ident(rule) => (compile_ident(rule, true), None),
+    Any::Ident(rule) => (compile_ident(rule, true, xid), None),
     Any::Digital(rule) => {
       let signs = Hir::alternation(
         rule
@@ -197,7 +213,7 @@ fn compile_rule(rule: &Any) -> Rule {
           .collect(),
       );
       let (pre, suf) = compile_affixes(&rule.affixes);
-      let mant = compile_digits(rule, &rule.mant);
+      let mant = compile_digits(rule, &rule.mant, xid);
       let exps = Hir::alternation(
         rule
           .exps
@@ -212,7 +228,7 @@ fn compile_rule(rule: &Any) -> Rule {
                 .collect(),
             );
 
-            Hir::concat(vec![lit(y), signs, compile_digits(rule, digits)])
+            Hir::concat(vec![lit(y), signs, compile_digits(rule, digits, xid)])
           })
           .collect(),
       );
@@ -222,7 +238,7 @@ fn compile_rule(rule: &Any) -> Rule {
     }
     Any::Quoted(rule) => {
       let (pre, suf) = compile_affixes(&rule.affixes);
-      let (open, close) = compile_bracket(&rule.bracket.kind);
+      let (open, close) = compile_bracket(&rule.bracket, xid);
 
       (Hir::concat(vec![pre, open]), Some(Hir::concat(vec![close, suf])))
     }
@@ -231,8 +247,9 @@ fn compile_rule(rule: &Any) -> Rule {
   Rule { pat, close }
 }
 
-fn compile_bracket(kind: &BracketKind) -> (Hir, Hir) {
-  match kind {
+fn compile_bracket(bracket: &Bracket, xid: &Xid) -> (Hir, Hir) {
+  let lit = |y: &Yarn| lit_in(y, bracket.case);
+  match &bracket.kind {
     BracketKind::Paired(open, close) => (lit(open), lit(close)),
     BracketKind::RustLike {
       repeating,
@@ -247,22 +264,39 @@ fn compile_bracket(kind: &BracketKind) -> (Hir, Hir) {
       open: (o1, o2),
       close: (c1, c2),
     } => {
-      let ident = compile_ident(ident_rule, false);
+      let ident = compile_ident(ident_rule, false, xid);
       (
         Hir::concat(vec![lit(o1), ident.clone(), lit(o2)]),
         Hir::concat(vec![lit(c1), ident, lit(c2)]),
       )
     }
+    // The closer must be alone on its line, which the DFA cannot see, so it
+    // is only ever matched by the lexer itself.
+    BracketKind::Heredoc { introducer, ident_rule, .. } => (
+      Hir::concat(vec![lit(introducer), compile_ident(ident_rule, false, xid)]),
+      Hir::fail(),
+    ),
   }
 }
 
-fn compile_digits(rule: &Digital, digits: &Digits) -> Hir {
+fn compile_digits(rule: &Digital, digits: &Digits, xid: &Xid) -> Hir {
+  // Custom alphabets may contain non-XID characters, such as `-` in base64url.
+  let alphabet = digits.alphabet.as_deref().unwrap_or("");
+  let alphabet = Hir::alternation(
+    alphabet
+      .chars()
+      .map(|c| Hir::literal(Yarn::from(c).into_boxed_bytes()))
+      .collect(),
+  );
+
   let start = Hir::alternation(vec![
-    xid_continue(), // Any XID can be a digit! We validate that it is a
+    xid.cont.clone(), // Any XID can be a digit! We validate that it is a
     // valid number only after we consume the whole token.
+    alphabet.clone(),
     lit(&rule.separator),
   ]);
-  let cont = Hir::alternation(vec![xid_continue(), lit(&rule.separator)]);
+  let cont =
+    Hir::alternation(vec![xid.cont.clone(), alphabet, lit(&rule.separator)]);
 
   // We use a lazy here, since we want an exponent prefix to take precedence.
   let block = Hir::concat(vec![start, lazy(cont, 0)]);
@@ -278,7 +312,7 @@ fn compile_digits(rule: &Digital, digits: &Digits) -> Hir {
   ])
 }
 
-fn compile_ident(rule: &Ident, top_level: bool) -> Hir {
+fn compile_ident(rule: &Ident, top_level: bool, xid: &Xid) -> Hir {
   let (pre, suf) = compile_affixes(&rule.affixes);
 
   let str2alt = |s: &str| -> Hir {
@@ -291,11 +325,12 @@ fn compile_ident(rule: &Ident, top_level: bool) -> Hir {
 
   // NOTE: We do not look at is_ascii here, for diagnostic reasons: we want
   // to match as many non-ASCII XID characters and then diagnose all of them.
-  let start = Hir::alternation(vec![str2alt(&rule.extra_starts), xid_start()]);
+  let start =
+    Hir::alternation(vec![str2alt(&rule.extra_starts), xid.start.clone()]);
   let cont = Hir::alternation(vec![
     str2alt(&rule.extra_starts),
     str2alt(&rule.extra_continues),
-    xid_continue(),
+    xid.cont.clone(),
   ]);
 
   let ident =
@@ -310,7 +345,7 @@ fn compile_ident(rule: &Ident, top_level: bool) -> Hir {
         .prefixes()
         .iter()
         .filter(|y| !y.is_empty())
-        .map(lit)
+        .map(|y| lit_in(y, rule.affixes.case))
         .collect(),
     );
     let suf = Hir::alternation(
@@ -319,7 +354,7 @@ fn compile_ident(rule: &Ident, top_level: bool) -> Hir {
         .suffixes()
         .iter()
         .filter(|y| !y.is_empty())
-        .map(lit)
+        .map(|y| lit_in(y, rule.affixes.case))
         .collect(),
     );
     Hir::alternation(vec![
@@ -334,6 +369,7 @@ fn compile_ident(rule: &Ident, top_level: bool) -> Hir {
 }
 
 fn compile_affixes(rule: &Affixes) -> (Hir, Hir) {
+  let lit = |y: &Yarn| lit_in(y, rule.case);
   let prefixes = Hir::alternation(rule.prefixes().iter().map(lit).collect());
   let suffixes = Hir::alternation(rule.suffixes().iter().map(lit).collect());
 
@@ -344,6 +380,32 @@ fn lit(y: &Yarn) -> Hir {
   Hir::literal(y.clone().into_boxed_bytes())
 }
 
+/// Like [`lit()`], but matches `y` according to `case`.
+fn lit_in(y: &Yarn, case: Case) -> Hir {
+  if case == Case::Sensitive {
+    return lit(y);
+  }
+
+  Hir::concat(
+    y.chars()
+      .map(|c| {
+        let mut class =
+          ClassUnicode::new(iter::once(ClassUnicodeRange::new(c, c)));
+        match case {
+          Case::Sensitive => {}
+          Case::Ascii => {
+            for c in [c.to_ascii_lowercase(), c.to_ascii_uppercase()] {
+              class.push(ClassUnicodeRange::new(c, c));
+            }
+          }
+          Case::Unicode => class.case_fold_simple(),
+        }
+        Hir::class(Class::Unicode(class))
+      })
+      .collect(),
+  )
+}
+
 fn greedy(hir: Hir, min: u32) -> Hir {
   Hir::repetition(Repetition {
     min,
@@ -362,20 +424,6 @@ fn lazy(hir: Hir, min: u32) -> Hir {
   })
 }
 
-fn xid_continue() -> Hir {
-  static CLASS: OnceLock<Hir> = OnceLock::new();
-  CLASS
-    .get_or_init(|| class_from_table(unicode::XID_CONTINUE))
-    .clone()
-}
-
-fn xid_start() -> Hir {
-  static CLASS: OnceLock<Hir> = OnceLock::new();
-  CLASS
-    .get_or_init(|| class_from_table(unicode::XID_START))
-    .clone()
-}
-
 fn class_from_table(ranges: &[(char, char)]) -> Hir {
   Hir::class(Class::Unicode(ClassUnicode::new(
     ranges
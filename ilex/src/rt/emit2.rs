@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use std::iter;
 use std::ptr;
 
@@ -17,13 +18,13 @@ use crate::rule;
 use crate::rule::Affixes;
 use crate::rule::Any;
 use crate::rule::BracketKind;
+use crate::rule::Case;
 use crate::rule::Comment;
 use crate::rule::Quoted;
 use crate::spec::Lexeme;
 use crate::spec::Spec;
 
 use super::dfa::Lexeme2;
-use super::unicode::is_xid;
 
 pub fn emit(lexer: &mut Lexer) {
   // Start by searching for the longest matches using the DFA.
@@ -49,11 +50,15 @@ pub fn emit(lexer: &mut Lexer) {
   // - Valid number of digit blocks; only the max is checked in the DFA.
   //
   // Once we filter out based on that, we break ties by picking the one with
-  // the smallest lexeme index; bracket opens the corresponding bracket close,
-  // so that if '|', '|' is a type of bracket, || will parse correctly.
+  // the highest priority, and then the one with the smallest lexeme index;
+  // bracket opens the corresponding bracket close, so that if '|', '|' is a
+  // type of bracket, || will parse correctly.
   //
-  // TODO(mcyoung): Document first-wins semantics?
-  match_.candidates.sort_unstable();
+  // Every candidate has the same length, so priority never allows a shorter
+  // token to beat a longer one.
+  match_
+    .candidates
+    .sort_unstable_by_key(|&c| (Reverse(lexer.spec().priority(c.lexeme)), c));
 
   // Find the first candidate that has no errors. If we can't find one, we'll
   // assume the first candidate on the list is a good enough choice for
@@ -71,14 +76,23 @@ pub fn emit(lexer: &mut Lexer) {
       Any::Bracket(bracket)
       | Any::Comment(Comment { bracket, .. })
       | Any::Quoted(Quoted { bracket, .. }) => {
-        if let BracketKind::CxxLike { ident_rule, open, close } = &bracket.kind
-        {
-          let [_, range, _] = if !c.is_close {
-            range.split_around(open.0.len(), open.1.len())
-          } else {
-            range.split_around(close.0.len(), close.1.len())
-          };
+        let ident = match &bracket.kind {
+          BracketKind::CxxLike { ident_rule, open, close } => {
+            let [_, range, _] = if !c.is_close {
+              range.split_around(open.0.len(), open.1.len())
+            } else {
+              range.split_around(close.0.len(), close.1.len())
+            };
+            Some((ident_rule, range))
+          }
+          BracketKind::Heredoc { introducer, ident_rule, .. } => {
+            let [_, range, _] = range.split_around(introducer.len(), 0);
+            Some((ident_rule, range))
+          }
+          _ => None,
+        };
 
+        if let Some((ident_rule, range)) = ident {
           let [_, name, _] = find_affixes(range, &ident_rule.affixes);
           if name.text().chars().count() < ident_rule.min_len {
             continue 'verify;
@@ -156,7 +170,7 @@ pub fn emit(lexer: &mut Lexer) {
             continue;
           }
 
-          if c.is_digit(digits.radix as u32) {
+          if digits.digit_value(c).is_some() {
             text = &text[c.len_utf8()..];
             last_was_sep = false;
             digits_in_block += 1;
@@ -205,14 +219,14 @@ pub fn emit(lexer: &mut Lexer) {
         let (remove, replace) =
           if !best.is_close { (open, close) } else { (close, open) };
 
-        let [_, mid, _] = range.split_around(remove.0.len(), remove.1.len());
+        let [_, mid, _] = split_delims(range, bracket.case, remove);
         Some(yarn!("{}{}{}", replace.0, mid.text(), replace.1))
       }
       BracketKind::CxxLike { ident_rule, open, close, .. } => {
         let (remove, replace) =
           if !best.is_close { (open, close) } else { (close, open) };
 
-        let [_, mid, _] = range.split_around(remove.0.len(), remove.1.len());
+        let [_, mid, _] = split_delims(range, bracket.case, remove);
         let [_, name, _] = find_affixes(mid, &ident_rule.affixes);
 
         let text = name.text();
@@ -235,6 +249,31 @@ pub fn emit(lexer: &mut Lexer) {
 
         Some(yarn!("{}{}{}", replace.0, mid.text(), replace.1))
       }
+      BracketKind::Heredoc { introducer, ident_rule, .. } => {
+        let [_, name, _] = range.split_around(introducer.len(), 0);
+
+        let text = name.text();
+        let count = text.chars().count();
+        if count < ident_rule.min_len {
+          lexer
+            .builtins()
+            .ident_too_small(ident_rule.min_len, count, name);
+        }
+
+        if ident_rule.ascii_only {
+          for c in text.chars() {
+            if !c.is_ascii()
+              && !ident_rule.extra_continues.contains(c)
+              && !ident_rule.extra_starts.contains(c)
+            {
+              lexer.builtins().non_ascii_in_ident(best.lexeme, name);
+              break;
+            }
+          }
+        }
+
+        Some(name.text().into())
+      }
     },
     _ => None,
   };
@@ -259,6 +298,10 @@ pub fn emit(lexer: &mut Lexer) {
     // of token we're going to create.
 
     let rule = lexer.spec().rule(best.lexeme);
+    if !matches!(rule, Any::Comment(..) | Any::LineEnd(..)) {
+      lexer.emit_indentation();
+    }
+
     if !matches!(rule, Any::Comment(..)) {
       // Diagnose a \ that is not followed by only spaces and comments.
       if let Some(cancel) = lexer.line_end_cancel.take() {
@@ -275,10 +318,13 @@ pub fn emit(lexer: &mut Lexer) {
     }
 
     match rule {
-      Any::Keyword(..) => lexer.add_token(best.lexeme, range.len(), None),
+      Any::Keyword(..) | Any::Regex(..) => {
+        lexer.add_token(best.lexeme, range.len(), None)
+      }
 
       Any::LineEnd(..) if text == "\n" => {
-        lexer.add_token(best.lexeme, range.len(), None)
+        lexer.add_token(best.lexeme, range.len(), None);
+        lexer.line_start = Some(lexer.cursor());
       }
       Any::LineEnd(..) => {
         // The cancel is always inserted as whitespace.
@@ -286,6 +332,10 @@ pub fn emit(lexer: &mut Lexer) {
         lexer.line_end_cancel = Some(range.span2())
       }
 
+      Any::Indent(..) | Any::Dedent(..) => {
+        bug!("matched an indentation rule with the DFA")
+      }
+
       Any::Bracket(..) => {
         // Construct the closer.
         lexer.push_closer(
@@ -310,12 +360,18 @@ pub fn emit(lexer: &mut Lexer) {
         let mut depth = 1;
         let close = mirrored.clone().unwrap().immortalize();
         while let Some(c) = lexer.text(cursor..).chars().next() {
-          if rule.can_nest && lexer.text(cursor..).starts_with(text) {
+          let rest = lexer.text(cursor..);
+          let case = rule.bracket.case;
+          if let Some(nested) =
+            case.strip_prefix(rest, text).filter(|_| rule.can_nest)
+          {
             depth += 1;
-            cursor += text.len();
-          } else if lexer.text(cursor..).starts_with(close.as_str()) {
+            cursor += rest.len() - nested.len();
+          } else if let Some(len) =
+            rule.bracket.close_len(&close, lexer.text(..), cursor)
+          {
             depth -= 1;
-            cursor += close.len();
+            cursor += len;
             if depth == 0 {
               break;
             }
@@ -357,8 +413,13 @@ pub fn emit(lexer: &mut Lexer) {
           }
         }
 
+        #[cfg(feature = "confusables")]
+        if rule.confusables {
+          diagnose_confusables(lexer, best.lexeme, range);
+        }
+
         lexer.add_token(rt::PREFIX, prefix.len(), None);
-        lexer.add_token(best.lexeme, range.len(), None);
+        lexer.add_token(best.lexeme, range.len(), normalize(rule, text));
         lexer.add_token(rt::SUFFIX, suffix.len(), None);
       }
 
@@ -415,8 +476,7 @@ pub fn emit(lexer: &mut Lexer) {
                 };
 
                 if !ok {
-                  lexer.builtins().unexpected(
-                    Expected::Name(yarn!("digit separator")),
+                  lexer.builtins().misplaced_separator(
                     best.lexeme,
                     range.subspan(offset..offset + sep.len()),
                   );
@@ -432,8 +492,7 @@ pub fn emit(lexer: &mut Lexer) {
 
           if let Some(rest) = text.strip_prefix(rule.point.as_str()) {
             if last_was_sep && !rule.corner_cases.around_point {
-              lexer.builtins().unexpected(
-                Expected::Name(yarn!("digit separator")),
+              lexer.builtins().misplaced_separator(
                 best.lexeme,
                 range.subspan(offset..offset + sep.len()),
               );
@@ -452,8 +511,7 @@ pub fn emit(lexer: &mut Lexer) {
           for (i, (pre, exp)) in rule.exps.iter().enumerate() {
             if let Some(rest) = text.strip_prefix(pre.as_str()) {
               if last_was_sep && !rule.corner_cases.around_exp {
-                lexer.builtins().unexpected(
-                  Expected::Name(yarn!("digit separator")),
+                lexer.builtins().misplaced_separator(
                   best.lexeme,
                   range.subspan(offset..offset + sep.len()),
                 );
@@ -502,8 +560,7 @@ pub fn emit(lexer: &mut Lexer) {
         }
 
         if last_was_sep && !rule.corner_cases.suffix {
-          lexer.builtins().unexpected(
-            Expected::Name(yarn!("digit separator")),
+          lexer.builtins().misplaced_separator(
             best.lexeme,
             range.subspan(offset - sep.len()..),
           );
@@ -557,6 +614,7 @@ pub fn emit(lexer: &mut Lexer) {
                 rule.point,
                 plural(digits.min_chunks - 1)
               ))
+              .code("E0013")
               .at(chunk_span);
           }
 
@@ -589,19 +647,27 @@ pub fn emit(lexer: &mut Lexer) {
               }
 
               text = &text[c.len_utf8()..];
-              if !c.is_digit(digits.radix as u32) {
-                lexer.builtins().unexpected(
-                  Expected::Literal(c.into()),
-                  lexer.stream().last_token(),
-                  lexer.span(cursor..cursor + c.len_utf8()),
-                )
-                .remark(
-                  chunk_span,
-                  f!(
-                    "because this value is {} (base {}), digits should be within '0'..='{:x}'",
-                    digits.radix_name(), digits.radix, digits.radix - 1,
+              if digits.digit_value(c).is_none() {
+                let base = match digits.alphabet {
+                  Some(_) => format!("base {}", digits.radix),
+                  None => {
+                    format!("{} (base {})", digits.radix_name(), digits.radix)
+                  }
+                };
+                let allowed = digits.allowed_digits();
+                lexer
+                  .builtins()
+                  .unexpected(
+                    Expected::Literal(c.into()),
+                    lexer.stream().last_token(),
+                    lexer.span(cursor..cursor + c.len_utf8()),
+                  )
+                  .remark(
+                    chunk_span,
+                    f!(
+                    "because this value is {base}, digits should be {allowed}"
                   ),
-                );
+                  );
               }
             }
           }
@@ -610,14 +676,18 @@ pub fn emit(lexer: &mut Lexer) {
 
       Any::Quoted(rule) => {
         let close = mirrored.clone().unwrap().immortalize();
+        let bracket = &rule.bracket;
 
+        let single_line = rule.single_line;
         let mut chunk_start = end;
         let mut cursor = end;
         let mut marks = vec![chunk_start as u32];
+        let mut holes = Vec::new();
+        let mut expansions = Vec::new();
         let uq_end = loop {
-          if lexer.text(cursor..).starts_with(close.as_str()) {
+          if let Some(len) = bracket.close_len(&close, lexer.text(..), cursor) {
             let end = cursor;
-            cursor += close.len();
+            cursor += len;
             if end > chunk_start {
               marks.push(end as u32);
             }
@@ -626,6 +696,44 @@ pub fn emit(lexer: &mut Lexer) {
           }
 
           let rest = lexer.text(cursor..);
+          if single_line && rest.starts_with('\n') {
+            // Stop here without consuming the newline, so that lexing can
+            // resume on the next line.
+            break None;
+          }
+
+          if let Some((open, hole_close)) = &rule.interpolation {
+            if rest.starts_with(open.as_str()) {
+              // Push unconditionally, just like for escapes.
+              marks.push(cursor as u32);
+
+              let hole_start = cursor;
+              let data_start = cursor + open.len();
+              let (hole, data_end) =
+                rt::lex_hole(lexer, data_start, hole_close);
+
+              cursor = data_end;
+              if lexer.text(cursor..).starts_with(hole_close.as_str()) {
+                cursor += hole_close.len();
+              } else {
+                lexer.builtins().unclosed(
+                  lexer.span(hole_start..data_start),
+                  hole_close,
+                  Lexeme::eof(),
+                  lexer.eof(),
+                );
+              }
+
+              holes.push((hole_start as u32, lexer.stream().holes.len()));
+              lexer.stream_mut().holes.push(hole);
+              marks.extend(
+                [data_start, data_start, data_end, cursor].map(|x| x as u32),
+              );
+              chunk_start = cursor;
+              continue;
+            }
+          }
+
           let (esc, rule) = match rule.escapes.longest_prefix(rest) {
             Some(e) => e,
             None => match rest.chars().next() {
@@ -663,7 +771,9 @@ pub fn emit(lexer: &mut Lexer) {
                 // characters, we would lex `"\x" ` as being `\x` with arg `" `.
                 // So, we want to check for a closer on *every* loop iteration, and
                 // break out if we *see* it: we should not consume it.
-                if lexer.text(cursor..).starts_with(close.as_str()) {
+                if bracket.close_len(&close, lexer.text(..), cursor).is_some()
+                  || (single_line && lexer.text(cursor..).starts_with('\n'))
+                {
                   break;
                 }
 
@@ -699,7 +809,7 @@ pub fn emit(lexer: &mut Lexer) {
               }
 
               let arg_start = cursor;
-              let Some(len) = lexer.text(..cursor).find(close.as_str()) else {
+              let Some(len) = lexer.text(cursor..).find(close.as_str()) else {
                 lexer.builtins().invalid_escape(
                   lexer.span(esc_start..cursor),
                   f!("expected a `{close}`"),
@@ -709,6 +819,110 @@ pub fn emit(lexer: &mut Lexer) {
               cursor += len + close.len();
               [arg_start, arg_start + len, cursor]
             }
+
+            rule::Escape::Expand(esc_close, expander) => 'expand: {
+              let arg_start = cursor;
+              if !esc_close.is_empty() {
+                // Like for fixed-length escapes, we must not run past the end
+                // of the string looking for the closer.
+                loop {
+                  let rest = lexer.text(cursor..);
+                  if rest.starts_with(esc_close.as_str()) {
+                    break;
+                  }
+
+                  let at_end =
+                    bracket.close_len(&close, lexer.text(..), cursor).is_some()
+                      || (single_line && rest.starts_with('\n'));
+                  match rest.chars().next() {
+                    Some(c) if !at_end => cursor += c.len_utf8(),
+                    _ => {
+                      lexer.builtins().invalid_escape(
+                        lexer.span(esc_start..cursor),
+                        f!("expected a `{esc_close}`"),
+                      );
+                      break 'expand [arg_start, cursor, cursor];
+                    }
+                  }
+                }
+              }
+
+              let arg_end = cursor;
+              cursor += esc_close.len();
+              match (expander.parse)(lexer.text(arg_start..arg_end)) {
+                Some(value) => {
+                  expansions.push((esc_start as u32, value.as_str().into()))
+                }
+                None => {
+                  lexer.builtins().invalid_escape(
+                    lexer.span(esc_start..cursor),
+                    expander.error,
+                  );
+                }
+              }
+
+              [arg_start, arg_end, cursor]
+            }
+
+            rule::Escape::Utf16 => 'utf16: {
+              // Returns the number of hex digits at the start of `text` (up to
+              // four), and the code unit they spell, if there are four.
+              fn hex4(text: &str) -> (usize, Option<u32>) {
+                let len = text
+                  .bytes()
+                  .take(4)
+                  .take_while(u8::is_ascii_hexdigit)
+                  .count();
+                let unit = (len == 4)
+                  .then(|| u32::from_str_radix(&text[..4], 16).unwrap());
+                (len, unit)
+              }
+
+              let arg_start = cursor;
+              let (len, unit) = hex4(lexer.text(cursor..));
+              cursor += len;
+              let arg_end = cursor;
+              let Some(unit) = unit else {
+                lexer.builtins().invalid_escape(
+                  lexer.span(esc_start..cursor),
+                  "expected exactly 4 hex digits here",
+                );
+                break 'utf16 [arg_start, arg_end, cursor];
+              };
+
+              let (c, what) = match unit {
+                // A high surrogate must be immediately followed by another
+                // escape containing a low surrogate; together, they encode a
+                // single character.
+                0xd800..0xdc00 => {
+                  let low = lexer.text(cursor..).strip_prefix(esc).map(hex4);
+                  match low {
+                    Some((_, Some(low @ 0xdc00..0xe000))) => {
+                      cursor += esc.len() + 4;
+                      let c =
+                        0x10000 + (((unit - 0xd800) << 10) | (low - 0xdc00));
+                      (char::from_u32(c), "")
+                    }
+                    _ => (None, "high"),
+                  }
+                }
+                _ => (char::from_u32(unit), "low"),
+              };
+
+              match c {
+                Some(c) => {
+                  expansions.push((esc_start as u32, c.to_string().into()))
+                }
+                None => {
+                  lexer.builtins().invalid_escape(
+                    lexer.span(esc_start..cursor),
+                    f!("unpaired {what} surrogate"),
+                  );
+                }
+              }
+
+              [arg_start, arg_end, cursor]
+            }
           };
 
           marks.push(esc_end as u32);
@@ -717,9 +931,19 @@ pub fn emit(lexer: &mut Lexer) {
         };
 
         if uq_end.is_none() {
-          lexer
-            .builtins()
-            .unclosed(span, &close, Lexeme::eof(), lexer.eof());
+          if cursor < lexer.file().len() {
+            // We stopped at the end of a single-line string.
+            lexer.builtins().unclosed(
+              span,
+              &close,
+              Expected::Name(yarn!("end of line")),
+              lexer.span(cursor..cursor),
+            );
+          } else {
+            lexer
+              .builtins()
+              .unclosed(span, &close, Lexeme::eof(), lexer.eof());
+          }
         }
 
         // We have to parse the suffix ourselves explicitly!
@@ -727,8 +951,10 @@ pub fn emit(lexer: &mut Lexer) {
           .affixes
           .suffixes()
           .iter()
-          .filter(|y| lexer.text(cursor..).starts_with(y.as_str()))
-          .map(|y| y.len())
+          .filter_map(|y| {
+            let rest = lexer.text(cursor..);
+            Some(rest.len() - rule.affixes.case.strip_prefix(rest, y)?.len())
+          })
           .max()
           .unwrap_or_else(|| {
             let found = match lexer.text(cursor..).chars().next() {
@@ -753,7 +979,7 @@ pub fn emit(lexer: &mut Lexer) {
         lexer.add_token(
           best.lexeme,
           cursor - lexer.cursor(),
-          Some(rt::Kind::Quoted(rt::Quoted { marks })),
+          Some(rt::Kind::Quoted(rt::Quoted { marks, holes, expansions })),
         );
         lexer.add_token(rt::SUFFIX, suf, None);
       }
@@ -787,8 +1013,9 @@ pub fn emit(lexer: &mut Lexer) {
 
   let rest = lexer.text(lexer.cursor()..);
   let prev = lexer.text(..lexer.cursor()).chars().next_back();
-  if prev.is_some_and(is_xid) {
-    let xids = rest.find(|c| !is_xid(c)).unwrap_or(rest.len());
+  let spec = lexer.spec();
+  if prev.is_some_and(|c| spec.is_xid(c)) {
+    let xids = rest.find(|c| !spec.is_xid(c)).unwrap_or(rest.len());
     if xids > 0 {
       let start = lexer.cursor();
       lexer.add_token(rt::UNEXPECTED, xids, None);
@@ -812,6 +1039,96 @@ pub fn emit(lexer: &mut Lexer) {
   }
 }
 
+/// Normalizes the text of an identifier according to its rule, returning the
+/// metadata to record for it if the normalized text differs.
+#[cfg(feature = "normalize")]
+fn normalize(rule: &rule::Ident, text: &str) -> Option<rt::Kind> {
+  use unicode_normalization::is_nfc_quick;
+  use unicode_normalization::IsNormalized;
+  use unicode_normalization::UnicodeNormalization;
+
+  let normalized = match rule.normalization? {
+    rule::Normalization::Nfc => {
+      if is_nfc_quick(text.chars()) == IsNormalized::Yes {
+        return None;
+      }
+      text.nfc().collect::<String>()
+    }
+  };
+
+  (normalized != text).then(|| rt::Kind::Ident(normalized.into()))
+}
+
+#[cfg(not(feature = "normalize"))]
+fn normalize(_: &rule::Ident, _: &str) -> Option<rt::Kind> {
+  None
+}
+
+/// Diagnoses an identifier that mixes scripts, or that consists entirely of
+/// characters that resemble ASCII characters.
+#[cfg(feature = "confusables")]
+fn diagnose_confusables(
+  lexer: &mut Lexer,
+  lexeme: Lexeme<rule::Any>,
+  name: Span,
+) {
+  use unicode_security::mixed_script::AugmentedScriptSet;
+
+  let text = name.text();
+  if text.is_ascii() {
+    return;
+  }
+
+  // Find the first character that does not share a script with the ones
+  // before it, and blame the run of such characters that starts there.
+  let mut scripts = AugmentedScriptSet::default();
+  let shares_script = |scripts: AugmentedScriptSet, c: char| {
+    let mut scripts = scripts;
+    scripts.intersect_with(c.into());
+    !scripts.is_empty()
+  };
+  for (i, c) in text.char_indices() {
+    if shares_script(scripts, c) {
+      scripts.intersect_with(c.into());
+      continue;
+    }
+
+    let end = text[i..]
+      .char_indices()
+      .find(|&(_, c)| shares_script(scripts, c))
+      .map_or(text.len(), |(j, _)| i + j);
+    lexer.builtins().mixed_script_ident(
+      lexeme,
+      name.subspan(i..end),
+      AugmentedScriptSet::from(&text[i..end]),
+      scripts,
+    );
+    return;
+  }
+
+  let lookalike = unicode_security::skeleton(text).collect::<String>();
+  if lookalike.is_ascii() {
+    lexer.builtins().confusable_ident(lexeme, name, &lookalike);
+  }
+}
+
+/// Splits the delimiters `start` and `end` off of `range`, which must begin and
+/// end with them.
+fn split_delims<'a>(
+  range: Span<'a>,
+  case: Case,
+  (start, end): &(Yarn, Yarn),
+) -> [Span<'a>; 3] {
+  let text = range.text();
+  let start = case
+    .strip_prefix(text, start)
+    .map_or(0, |r| text.len() - r.len());
+  let end = case
+    .strip_suffix(text, end)
+    .map_or(0, |r| text.len() - r.len());
+  range.split_around(start, end)
+}
+
 /// Extracts the affixes from `text`.
 fn find_affixes_partial<'a>(
   range: Span<'a>,
@@ -863,8 +1180,9 @@ fn find_prefix<'a>(range: Span<'a>, affixes: &Affixes) -> (Span<'a>, Span<'a>) {
   let prefix = affixes
     .prefixes()
     .iter()
-    .filter(|y| text.starts_with(y.as_str()))
-    .map(|y| y.len())
+    .filter_map(|y| {
+      Some(text.len() - affixes.case.strip_prefix(text, y)?.len())
+    })
     .max()
     .unwrap_or_else(|| bug!("could not find matching prefix post-DFA"));
   range.split_at(prefix)
@@ -875,8 +1193,9 @@ fn find_suffix<'a>(range: Span<'a>, affixes: &Affixes) -> (Span<'a>, Span<'a>) {
   let suffix = affixes
     .suffixes()
     .iter()
-    .filter(|y| text.ends_with(y.as_str()))
-    .map(|y| y.len())
+    .filter_map(|y| {
+      Some(text.len() - affixes.case.strip_suffix(text, y)?.len())
+    })
     .max()
     .unwrap_or_else(|| bug!("could not find matching suffix post-DFA"));
   range.split_at(text.len() - suffix)
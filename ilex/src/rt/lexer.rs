@@ -4,6 +4,7 @@ use std::ops::Index;
 use std::ops::RangeBounds;
 
 use bitvec::vec::BitVec;
+use byteyarn::yarn;
 use byteyarn::Yarn;
 use regex_automata::hybrid::dfa::Cache;
 
@@ -12,6 +13,7 @@ use crate::file::File;
 use crate::file::Span;
 use crate::file::Span2;
 use crate::report::Builtins;
+use crate::report::Expected;
 use crate::report::Report;
 use crate::rt;
 use crate::rule;
@@ -22,8 +24,6 @@ use crate::spec::Spec;
 use crate::token;
 use crate::token::Stream;
 
-use super::unicode::is_xid;
-
 /// The lexer state struct, that tracks everything going on during a lexing
 /// operation.
 pub struct Lexer<'a, 'ctx> {
@@ -35,9 +35,25 @@ pub struct Lexer<'a, 'ctx> {
   comments: Vec<token::Id>,
   pub line_end_cancel: Option<Span2>,
 
+  // The start of the current line, if no token that would trigger indentation
+  // has been lexed on it yet.
+  pub line_start: Option<usize>,
+  indents: Vec<Indentation>,
+
   cache: Cache,
 }
 
+/// An indentation level pushed by an indent token.
+struct Indentation {
+  // The indentation's width, with tabs expanded to the rule's tab width.
+  width: u32,
+  // The indentation's width, with tabs counted as one column.
+  narrow: u32,
+  // The first character of the line that introduced this level, for
+  // diagnostics.
+  span: Span2,
+}
+
 /// Yet-unclosed brackets.
 pub struct Closer {
   lexeme: Lexeme<rule::Bracket>,
@@ -59,6 +75,7 @@ impl<'a, 'ctx> Lexer<'a, 'ctx> {
         meta_idx: Vec::new(),
         meta: Vec::new(),
         silent: BitVec::new(),
+        holes: Vec::new(),
       },
 
       cursor: 0,
@@ -66,12 +83,31 @@ impl<'a, 'ctx> Lexer<'a, 'ctx> {
       comments: Vec::new(),
       line_end_cancel: None,
 
+      line_start: Some(0),
+      indents: Vec::new(),
+
       cache: Cache::new(&spec.dfa().engine),
     }
   }
 
+  /// Creates a new lexer for an interpolation hole, which starts lexing at
+  /// `start` rather than at the start of the file.
+  pub fn new_at(
+    file: File<'ctx>,
+    report: &'a Report,
+    spec: &'ctx Spec,
+    start: usize,
+  ) -> Self {
+    let mut lexer = Self::new(file, report, spec);
+    lexer.line_start = None;
+    // Everything before the hole is treated as whitespace, so that token
+    // spans in the hole's stream come out right.
+    lexer.add_token(rt::WHITESPACE, start, None);
+    lexer
+  }
+
   /// Returns the report for diagnostics.
-  pub fn report(&self) -> &Report {
+  pub fn report(&self) -> &'a Report {
     self.report
   }
 
@@ -149,12 +185,25 @@ impl<'a, 'ctx> Lexer<'a, 'ctx> {
 
   /// Pops a closer, if it is time for it.
   pub fn pop_closer(&mut self) {
-    let idx = self.closers.iter().rposition(|close| {
-      self.text(self.cursor()..).starts_with(close.close.as_str())
-    });
-    let Some(idx) = idx else { return };
+    let (idx, close_len) = self
+      .closers
+      .iter()
+      .enumerate()
+      .rev()
+      .find_map(|(i, close)| {
+        let bracket = self.spec().rule(close.lexeme);
+        Some((
+          i,
+          bracket.close_len(&close.close, self.text(..), self.cursor())?,
+        ))
+      })
+      .unzip();
+    let (Some(idx), Some(close_len)) = (idx, close_len) else { return };
     let len = self.closers.len();
 
+    // The rest of this line continues the bracket's line.
+    self.line_start = None;
+
     // Pull out our to-be-closed. Swap it with the outermost one so that when
     // we close "mixed delimiters", we still generate all the right tokens.
     self.closers.swap(idx, len - 1);
@@ -164,7 +213,7 @@ impl<'a, 'ctx> Lexer<'a, 'ctx> {
     }
 
     let start = self.cursor();
-    let mut end = start + close.close.len();
+    let mut end = start + close_len;
 
     let close_idx = self.stream.toks.len();
     let meta_idx = self.stream.meta.len();
@@ -183,8 +232,9 @@ impl<'a, 'ctx> Lexer<'a, 'ctx> {
 
     let rest = self.text(end..);
     let prev = rest.chars().next_back();
-    if prev.is_some_and(is_xid) {
-      let xids = rest.find(|c| !is_xid(c)).unwrap_or(rest.len());
+    let spec = self.spec();
+    if prev.is_some_and(|c| spec.is_xid(c)) {
+      let xids = rest.find(|c| !spec.is_xid(c)).unwrap_or(rest.len());
       if xids > 0 {
         let start = end;
         end += xids;
@@ -219,6 +269,69 @@ impl<'a, 'ctx> Lexer<'a, 'ctx> {
     );
   }
 
+  /// Emits indent and dedent tokens, if this is the first token of its line
+  /// and the spec is indentation-sensitive.
+  pub fn emit_indentation(&mut self) {
+    let Some(line_start) = self.line_start.take() else { return };
+    let Some((indent, dedent)) = self.spec().builder.indent else { return };
+    if !self.closers.is_empty() {
+      // Indentation is not significant within brackets.
+      return;
+    }
+
+    let rule = self.spec().rule(indent);
+    let (mut width, mut narrow) = (0u32, 0u32);
+    for c in self.text(line_start..self.cursor()).chars() {
+      if !c.is_whitespace() {
+        break;
+      }
+
+      if c == '\t' {
+        width += rule.tab_width - width % rule.tab_width;
+      } else {
+        width += 1;
+      }
+      narrow += 1;
+    }
+
+    // Diagnostics point at the first character of the line, since the
+    // indentation itself is invisible.
+    let first = self.text(self.cursor()..).chars().next();
+    let span =
+      self.span(self.cursor()..self.cursor() + first.map_or(0, char::len_utf8));
+
+    let is_consistent = |level: Option<&Indentation>| {
+      let (w, n) = level.map(|l| (l.width, l.narrow)).unwrap_or_default();
+      rule.mixed_tabs || width.cmp(&w) == narrow.cmp(&n)
+    };
+
+    let top = self.indents.last().map(|l| l.width).unwrap_or(0);
+    if width > top {
+      if !is_consistent(self.indents.last()) {
+        self.builtins().mixed_tabs(span);
+      }
+
+      self
+        .indents
+        .push(Indentation { width, narrow, span: span.span2() });
+      self.add_token(indent.any(), 0, None);
+      return;
+    }
+
+    while self.indents.last().is_some_and(|l| l.width > width) {
+      self.indents.pop();
+      self.add_token(dedent.any(), 0, None);
+    }
+
+    let outer = self.indents.last();
+    if outer.map(|l| l.width).unwrap_or(0) != width {
+      let outer = outer.map(|l| l.span.get(self.file()));
+      self.builtins().inconsistent_dedent(span, outer);
+    } else if !is_consistent(outer) {
+      self.builtins().mixed_tabs(span);
+    }
+  }
+
   /// Adds a new token.
   pub fn add_token(
     &mut self,
@@ -296,31 +409,44 @@ impl<'a, 'ctx> Lexer<'a, 'ctx> {
 
   pub fn skip_whitespace(&mut self) -> bool {
     let have_line_end = self.spec().builder.line_end.is_some();
-    let len = self
-      .text(self.cursor()..)
-      .chars()
-      .take_while(|&c| {
-        if c == '\n' && have_line_end {
-          return self.line_end_cancel.take().is_some();
+    let mut len = 0;
+    for c in self.text(self.cursor()..).chars() {
+      if c == '\n' && have_line_end {
+        // A cancelled newline continues the current line.
+        if self.line_end_cancel.take().is_none() {
+          break;
         }
+      } else if !c.is_whitespace() {
+        break;
+      } else if c == '\n' {
+        self.line_start = Some(self.cursor() + len + 1);
+      }
 
-        c.is_whitespace()
-      })
-      .map(char::len_utf8)
-      .sum();
+      len += c.len_utf8();
+    }
 
     self.add_token(rt::WHITESPACE, len, None);
     len > 0
   }
 
   pub fn finish(mut self) -> token::Stream<'ctx> {
+    if let Some((_, dedent)) = self.spec().builder.indent {
+      for _ in mem::take(&mut self.indents) {
+        self.add_token(dedent.any(), 0, None);
+      }
+    }
     self.add_token(Lexeme::eof().any(), 0, None);
 
+    // Interpolation holes stop before the end of the file.
+    let at = self.span(self.cursor()..self.cursor());
     for close in mem::take(&mut self.closers) {
       let open = self.lookup_span(close.original_open_idx);
-      self
-        .builtins()
-        .unclosed(open, &close.close, Lexeme::eof(), self.eof());
+      let found = if self.cursor() < self.file().len() {
+        Expected::Name(yarn!("end of interpolation"))
+      } else {
+        Lexeme::eof().into()
+      };
+      self.builtins().unclosed(open, &close.close, found, at);
     }
 
     self.stream
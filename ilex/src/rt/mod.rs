@@ -15,7 +15,7 @@ use crate::token;
 
 mod emit2;
 pub mod lexer;
-mod unicode;
+pub mod unicode;
 
 mod dfa;
 pub use dfa::compile;
@@ -27,10 +27,36 @@ pub fn lex<'ctx>(
   spec: &'ctx Spec,
 ) -> Result<token::Stream<'ctx>, Fatal> {
   let mut lexer = lexer::Lexer::new(file, report, spec);
+  lex_until(&mut lexer, None);
+  report.fatal_or(lexer.finish())
+}
+
+/// Lexes the contents of an interpolation hole that starts at `start`, up to
+/// the first `close` that does not close a bracket opened within the hole.
+///
+/// Returns the hole's stream and the offset at which lexing stopped, which is
+/// either the start of `close` or the end of the file.
+pub fn lex_hole<'ctx>(
+  outer: &lexer::Lexer<'_, 'ctx>,
+  start: usize,
+  close: &str,
+) -> (token::Stream<'ctx>, usize) {
+  let mut lexer =
+    lexer::Lexer::new_at(outer.file(), outer.report(), outer.spec(), start);
+  lex_until(&mut lexer, Some(close));
+  let end = lexer.cursor();
+  (lexer.finish(), end)
+}
+
+fn lex_until(lexer: &mut lexer::Lexer, stop: Option<&str>) {
+  let (file, report, spec) = (lexer.file(), lexer.report(), lexer.spec());
 
   let unexpected = Cell::new(None);
   let diagnose_unexpected = |end: usize| {
     let Some(start) = unexpected.take() else { return };
+    if report.too_many_errors() {
+      return;
+    }
     report
       .builtins(spec)
       .unexpected_token(file.span(start..end));
@@ -51,7 +77,12 @@ pub fn lex<'ctx>(
       continue;
     }
 
-    emit2::emit(&mut lexer);
+    if stop.is_some_and(|stop| lexer.text(start..).starts_with(stop)) {
+      diagnose_unexpected(start);
+      break;
+    }
+
+    emit2::emit(lexer);
     if lexer.cursor() > start {
       diagnose_unexpected(start);
       continue;
@@ -62,30 +93,38 @@ pub fn lex<'ctx>(
       unexpected.set(Some(start))
     }
   }
-
-  report.fatal_or(lexer.finish())
 }
 
 /// The internal representation of a token inside of a token stream.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
   pub lexeme: Lexeme<rule::Any>,
   pub end: u32,
 }
 #[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Metadata {
   pub kind: Option<Kind>,
   pub comments: Vec<token::Id>,
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
   Quoted(Quoted),
   Digital(Digital),
-  Offset { cursor: i32, meta: i32 },
+  Offset {
+    cursor: i32,
+    meta: i32,
+  },
+  // The normalized text of an identifier, if it differs from the original.
+  #[cfg(feature = "normalize")]
+  Ident(Box<str>),
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Quoted {
   // Offsets for the components of the string. First mark is the end of the
   // open quote; following are alternating marks for textual and escape content.
@@ -96,16 +135,31 @@ pub struct Quoted {
   // end of the whole escape. This means that when we encounter \xNN, the
   // positions of the marks are \x||NN||. When we encounter \u{NN}, the positions
   // are \u|{|NN|}|. For \n, the positions are \n||||.
+  //
+  // Interpolation holes are recorded as escapes whose extra data is the
+  // contents of the hole, e.g. ${|NN|}|.
   pub marks: Vec<u32>,
+  // The starts of the escapes that are actually interpolation holes, along
+  // with the index of each hole's stream in its parent stream.
+  pub holes: Vec<(u32, usize)>,
+  // The starts of the escapes that were expanded by the lexer, along with the
+  // strings they expanded to.
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Vec::is_empty")
+  )]
+  pub expansions: Vec<(u32, Box<str>)>,
 }
 
 #[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Digital {
   pub digits: DigitBlocks,
   pub exponents: Vec<DigitBlocks>,
 }
 
 #[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DigitBlocks {
   pub prefix: Span2,
   pub sign: Option<(Sign, Span2)>,
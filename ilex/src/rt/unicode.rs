@@ -7,11 +7,9 @@
 
 use std::cmp::Ordering::{Equal, Greater, Less};
 
-pub fn is_xid(c: char) -> bool {
-  search_ranges(c, XID_CONTINUE)
-}
+pub const UNICODE_VERSION: (u64, u64, u64) = (15, 0, 0);
 
-fn search_ranges(c: char, r: &[(char, char)]) -> bool {
+pub fn search_ranges(c: char, r: &[(char, char)]) -> bool {
   r.binary_search_by(|&(lo, hi)| {
     if lo > c {
       return Greater;
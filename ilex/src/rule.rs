@@ -1,10 +1,12 @@
 //! Lexer rules.
 
 use core::fmt;
+use std::collections::HashMap;
 use std::ops::Range;
 use std::ops::RangeBounds;
 
 use byteyarn::Yarn;
+use regex_syntax::hir::Hir;
 use twie::Trie;
 
 use crate::token;
@@ -29,15 +31,18 @@ pub use crate::token::Sign;
 
 /// Any of the possible rule types in a [`Spec`][crate::Spec].
 #[derive(Debug)]
-#[allow(missing_docs)]
+#[allow(missing_docs, clippy::large_enum_variant)]
 pub enum Any {
   Keyword(Keyword),
   LineEnd(LineEnd),
+  Indent(Indent),
+  Dedent(Dedent),
   Bracket(Bracket),
   Ident(Ident),
   Quoted(Quoted),
   Comment(Comment),
   Digital(Digital),
+  Regex(Regex),
 }
 
 impl Any {
@@ -46,15 +51,51 @@ impl Any {
     match self {
       Any::Keyword(_) => "Keyword",
       Any::LineEnd(_) => "LineEnd",
+      Any::Indent(_) => "Indent",
+      Any::Dedent(_) => "Dedent",
       Any::Bracket(_) => "Bracket",
       Any::Ident(_) => "Ident",
       Any::Digital(_) => "Digital",
       Any::Quoted(_) => "Quoted",
       Any::Comment(_) => "Comment",
+      Any::Regex(_) => "Regex",
+    }
+  }
+
+  /// The name given to this rule with `named()`, or the empty string if it
+  /// has none.
+  pub(crate) fn name(&self) -> &Yarn {
+    match self {
+      Any::Keyword(rule) => &rule.name,
+      Any::LineEnd(rule) => &rule.name,
+      Any::Indent(rule) => &rule.name,
+      Any::Dedent(rule) => &rule.name,
+      Any::Bracket(rule) => &rule.name,
+      Any::Ident(rule) => &rule.name,
+      Any::Digital(rule) => &rule.name,
+      Any::Quoted(rule) => &rule.name,
+      Any::Comment(rule) => &rule.name,
+      Any::Regex(rule) => &rule.name,
     }
   }
 }
 
+macro_rules! named {
+  () => {
+    /// Gives this rule a human-readable name, such as `"string literal"`.
+    ///
+    /// Diagnostics that mention this rule, such as "expected ..." and
+    /// "unexpected ...", will use this name instead of one generated from the
+    /// rule's delimiters. A name passed to
+    /// [`SpecBuilder::named_rule()`][crate::SpecBuilder::named_rule] takes
+    /// precedence over this one.
+    pub fn named(mut self, name: impl Into<Yarn>) -> Self {
+      self.name = name.into();
+      self
+    }
+  };
+}
+
 impl Rule for Any {
   type Token<'lex> = token::Any<'lex>;
 
@@ -100,12 +141,42 @@ impl TryFrom<Any> for Eof {
 #[derive(Debug)]
 pub struct Keyword {
   pub(crate) value: Yarn,
+  pub(crate) case: Case,
+  pub(crate) name: Yarn,
 }
 
 impl Keyword {
   /// Constructs a new keyword rule with the exact string it matches.
   pub fn new(value: impl Into<Yarn>) -> Self {
-    Self { value: value.into() }
+    Self {
+      value: value.into(),
+      case: Case::Sensitive,
+      name: "".into(),
+    }
+  }
+
+  named!();
+
+  /// Makes this keyword match regardless of ASCII case, so that
+  /// `Keyword::new("select")` also matches `SELECT` and `Select`.
+  ///
+  /// The resulting token's text is the text as it appears in the source.
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Keyword::new("select")
+  ///   .case_insensitive();
+  /// ```
+  pub fn case_insensitive(mut self) -> Self {
+    self.case = Case::Ascii;
+    self
+  }
+
+  /// Like [`Keyword::case_insensitive()`], but uses Unicode simple case
+  /// folding, so that non-ASCII letters also match regardless of case.
+  pub fn unicode_case_insensitive(mut self) -> Self {
+    self.case = Case::Unicode;
+    self
   }
 }
 
@@ -162,6 +233,7 @@ impl TryFrom<Any> for Keyword {
 #[derive(Default, Debug)]
 pub struct LineEnd {
   pub(crate) cancel: Yarn,
+  pub(crate) name: Yarn,
 }
 
 impl LineEnd {
@@ -172,8 +244,10 @@ impl LineEnd {
 
   /// COnstructs a new line end rule with the given cancel prefix.
   pub fn cancellable(cancel: impl Into<Yarn>) -> Self {
-    Self { cancel: cancel.into() }
+    Self { cancel: cancel.into(), ..Self::default() }
   }
+
+  named!();
 }
 
 impl Rule for LineEnd {
@@ -204,6 +278,222 @@ impl TryFrom<Any> for LineEnd {
   }
 }
 
+/// A token described by a regular expression, for tokens that are awkward to
+/// express with the other rules, such as dates or IP addresses.
+///
+/// The expression participates in the lexer's longest-match selection just
+/// like every other rule: the longest match wins, and ties are broken in favor
+/// of the rule that was added first. Matches produce [`token::Keyword`]s.
+///
+/// The expression uses the syntax of the [`regex`](https://docs.rs/regex)
+/// crate, and is always anchored at the start of the token.
+#[derive(Debug)]
+pub struct Regex {
+  pub(crate) source: Yarn,
+  pub(crate) hir: Hir,
+  pub(crate) name: Yarn,
+}
+
+impl Regex {
+  /// Constructs a new regex rule.
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Regex::new(r"[0-9]{4}-[0-9]{2}-[0-9]{2}");
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if `pattern` is not a valid regular expression, or if it can
+  /// match the empty string.
+  #[track_caller]
+  pub fn new(pattern: impl Into<Yarn>) -> Self {
+    let source = pattern.into();
+    let hir = match regex_syntax::parse(&source) {
+      Ok(hir) => hir,
+      Err(e) => panic!("invalid pattern for rule::Regex: {e}"),
+    };
+    assert!(
+      hir.properties().minimum_len() != Some(0),
+      "rule::Regex pattern `{source}` cannot match the empty string"
+    );
+
+    Self { source, hir, name: "".into() }
+  }
+
+  named!();
+}
+
+impl Rule for Regex {
+  type Token<'lex> = token::Keyword<'lex>;
+
+  fn try_from_ref(value: &Any) -> Result<&Self, WrongKind> {
+    match value {
+      Any::Regex(rule) => Ok(rule),
+      _ => Err(WrongKind { want: "Regex", got: value.debug_name() }),
+    }
+  }
+}
+
+impl From<Regex> for Any {
+  fn from(value: Regex) -> Self {
+    Any::Regex(value)
+  }
+}
+
+impl TryFrom<Any> for Regex {
+  type Error = WrongKind;
+
+  fn try_from(value: Any) -> Result<Self, Self::Error> {
+    match value {
+      Any::Regex(rule) => Ok(rule),
+      _ => Err(WrongKind { want: "Regex", got: value.debug_name() }),
+    }
+  }
+}
+
+/// An increase in indentation, for languages with significant whitespace,
+/// such as Python.
+///
+/// When a spec contains an `Indent` rule, the lexer keeps a stack of
+/// indentation levels, and measures the indentation of the first token on
+/// each line. If it is deeper than the top of the stack, a zero-length
+/// `Indent` token is emitted before that token, and the new level is pushed.
+/// If it is shallower, one zero-length [`Dedent`] token is emitted for each
+/// level that is popped; dedenting to a level that is not on the stack is
+/// diagnosed. Any remaining levels are popped before the end of the file.
+///
+/// Lines that contain only whitespace and comments do not affect indentation,
+/// nor do lines inside of a [`Bracket`] or lines that follow a cancelled
+/// [`LineEnd`].
+///
+/// A spec that contains an `Indent` rule must also contain a [`Dedent`] rule,
+/// and vice-versa. Both produce [`token::Keyword`]s.
+#[derive(Debug)]
+pub struct Indent {
+  pub(crate) tab_width: u32,
+  pub(crate) mixed_tabs: bool,
+  pub(crate) name: Yarn,
+}
+
+impl Default for Indent {
+  fn default() -> Self {
+    Self {
+      tab_width: 8,
+      mixed_tabs: false,
+      name: "".into(),
+    }
+  }
+}
+
+impl Indent {
+  /// Constructs a new indentation rule.
+  ///
+  /// By default, tabs advance to the next multiple of eight columns, and
+  /// indentation that is ambiguous due to mixing tabs and spaces is an error.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets the number of columns that a tab advances to a multiple of.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `width` is zero.
+  pub fn tab_width(mut self, width: u32) -> Self {
+    assert!(width > 0, "the tab width of an Indent rule cannot be zero");
+    self.tab_width = width;
+    self
+  }
+
+  /// Allows tabs and spaces to be mixed freely.
+  ///
+  /// By default, the lexer measures indentation both with the configured tab
+  /// width and with a tab width of one; if the two measurements disagree on
+  /// whether a line is indented relative to the previous one, the
+  /// indentation depends on the reader's tab width, and is diagnosed. This
+  /// is the same rule that Python uses.
+  pub fn allow_mixed_tabs(mut self) -> Self {
+    self.mixed_tabs = true;
+    self
+  }
+
+  named!();
+}
+
+impl Rule for Indent {
+  type Token<'lex> = token::Keyword<'lex>;
+
+  fn try_from_ref(value: &Any) -> Result<&Self, WrongKind> {
+    match value {
+      Any::Indent(rule) => Ok(rule),
+      _ => Err(WrongKind { want: "Indent", got: value.debug_name() }),
+    }
+  }
+}
+
+impl From<Indent> for Any {
+  fn from(value: Indent) -> Self {
+    Any::Indent(value)
+  }
+}
+
+impl TryFrom<Any> for Indent {
+  type Error = WrongKind;
+
+  fn try_from(value: Any) -> Result<Self, Self::Error> {
+    match value {
+      Any::Indent(rule) => Ok(rule),
+      _ => Err(WrongKind { want: "Indent", got: value.debug_name() }),
+    }
+  }
+}
+
+/// A decrease in indentation.
+///
+/// See [`Indent`].
+#[derive(Default, Debug)]
+pub struct Dedent {
+  pub(crate) name: Yarn,
+}
+
+impl Dedent {
+  /// Constructs a new dedentation rule.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  named!();
+}
+
+impl Rule for Dedent {
+  type Token<'lex> = token::Keyword<'lex>;
+
+  fn try_from_ref(value: &Any) -> Result<&Self, WrongKind> {
+    match value {
+      Any::Dedent(rule) => Ok(rule),
+      _ => Err(WrongKind { want: "Dedent", got: value.debug_name() }),
+    }
+  }
+}
+
+impl From<Dedent> for Any {
+  fn from(value: Dedent) -> Self {
+    Any::Dedent(value)
+  }
+}
+
+impl TryFrom<Any> for Dedent {
+  type Error = WrongKind;
+
+  fn try_from(value: Any) -> Result<Self, Self::Error> {
+    match value {
+      Any::Dedent(rule) => Ok(rule),
+      _ => Err(WrongKind { want: "Dedent", got: value.debug_name() }),
+    }
+  }
+}
+
 /// A paired bracket, such as `(..)`.
 ///
 /// Brackets are pairs of delimiters with tokens between them. They are used as
@@ -211,6 +501,8 @@ impl TryFrom<Any> for LineEnd {
 #[derive(Debug)]
 pub struct Bracket {
   pub(crate) kind: BracketKind,
+  pub(crate) case: Case,
+  pub(crate) name: Yarn,
 }
 
 impl Bracket {
@@ -228,7 +520,11 @@ impl Bracket {
       "both arguments to Bracket::paired() must be non-empty"
     );
 
-    Self { kind: BracketKind::Paired(open, close) }
+    Self {
+      kind: BracketKind::Paired(open, close),
+      case: Case::Sensitive,
+      name: "".into(),
+    }
   }
 
   /// A Rust raw string-like bracket. This corresponds to `##"foo"##` raw
@@ -279,6 +575,8 @@ impl Bracket {
 
     Self {
       kind: BracketKind::RustLike { repeating, open, close },
+      case: Case::Sensitive,
+      name: "".into(),
     }
   }
 
@@ -322,8 +620,130 @@ impl Bracket {
 
     Self {
       kind: BracketKind::CxxLike { ident_rule: ident, open, close },
+      case: Case::Sensitive,
+      name: "".into(),
     }
   }
+
+  /// A shell heredoc-like bracket. This corresponds to `<<EOF ... EOF`
+  /// heredocs in POSIX shells.
+  ///
+  /// The bracket is opened by `introducer` followed by an identifier, and is
+  /// closed by the same identifier, alone on its own line. Unlike in a shell,
+  /// the contents begin immediately after the opening identifier, rather than
+  /// on the following line.
+  ///
+  /// To specify the exact syntax from POSIX shells, you would write
+  /// `Bracket::heredoc("<<", Ident::new())`.
+  ///
+  /// Heredoc delimiters cannot be empty, so `ident` is given a minimum length
+  /// of one if it has none.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `ident` has any affixes or if `introducer` is empty.
+  #[track_caller]
+  pub fn heredoc(introducer: impl Into<Yarn>, ident: Ident) -> Self {
+    Self::heredoc_impl(introducer.into(), ident, false)
+  }
+
+  /// Like [`Bracket::heredoc()`], but the closing identifier may be preceded
+  /// by tabs. This corresponds to `<<-EOF ... EOF` heredocs in POSIX shells.
+  ///
+  /// Only the tabs before the closing identifier are ignored; the contents of
+  /// the heredoc are not altered.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `ident` has any affixes or if `introducer` is empty.
+  #[track_caller]
+  pub fn indented_heredoc(introducer: impl Into<Yarn>, ident: Ident) -> Self {
+    Self::heredoc_impl(introducer.into(), ident, true)
+  }
+
+  #[track_caller]
+  fn heredoc_impl(
+    introducer: Yarn,
+    mut ident: Ident,
+    strip_tabs: bool,
+  ) -> Self {
+    assert!(
+      ident.affixes.prefixes.is_empty() && ident.affixes.suffixes.is_empty(),
+      "Bracket::heredoc() requires an identifier with no affixes"
+    );
+    assert!(
+      !introducer.is_empty(),
+      "the introducer of Bracket::heredoc() cannot be empty"
+    );
+
+    ident.min_len = ident.min_len.max(1);
+    Self {
+      kind: BracketKind::Heredoc {
+        introducer,
+        ident_rule: ident,
+        strip_tabs,
+      },
+      case: Case::Sensitive,
+      name: "".into(),
+    }
+  }
+
+  /// Makes this bracket's delimiters match regardless of ASCII case, so that
+  /// `Bracket::paired("begin", "end")` also matches `BEGIN ... End`.
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Bracket::paired("begin", "end")
+  ///   .case_insensitive();
+  /// ```
+  pub fn case_insensitive(mut self) -> Self {
+    self.case = Case::Ascii;
+    self
+  }
+
+  /// Like [`Bracket::case_insensitive()`], but uses Unicode simple case
+  /// folding, so that non-ASCII letters also match regardless of case.
+  pub fn unicode_case_insensitive(mut self) -> Self {
+    self.case = Case::Unicode;
+    self
+  }
+
+  named!();
+
+  /// Returns the length of the closing delimiter `close` if it occurs at
+  /// `cursor` in `text`.
+  pub(crate) fn close_len(
+    &self,
+    close: &str,
+    text: &str,
+    cursor: usize,
+  ) -> Option<usize> {
+    let &BracketKind::Heredoc { strip_tabs, .. } = &self.kind else {
+      let rest = &text[cursor..];
+      return Some(rest.len() - self.case.strip_prefix(rest, close)?.len());
+    };
+
+    // A heredoc's delimiter must be alone on its own line.
+    let mut before = &text[..cursor];
+    let mut rest = &text[cursor..];
+    if strip_tabs {
+      before = before.trim_end_matches('\t');
+      rest = rest.trim_start_matches('\t');
+    }
+    if !before.ends_with('\n') {
+      return None;
+    }
+
+    let after = self.case.strip_prefix(rest, close)?;
+    if !after.is_empty()
+      && !after.starts_with('\n')
+      && !after.starts_with("\r\n")
+    {
+      return None;
+    }
+
+    Some(text.len() - cursor - after.len())
+  }
 }
 
 #[derive(Debug)]
@@ -339,6 +759,11 @@ pub(crate) enum BracketKind {
     open: (Yarn, Yarn),
     close: (Yarn, Yarn),
   },
+  Heredoc {
+    introducer: Yarn,
+    ident_rule: Ident,
+    strip_tabs: bool,
+  },
 }
 
 impl Rule for Bracket {
@@ -375,10 +800,74 @@ impl TryFrom<Any> for Bracket {
   }
 }
 
+/// How a rule compares its fixed strings against the input.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Case {
+  #[default]
+  Sensitive,
+  Ascii,
+  Unicode,
+}
+
+impl Case {
+  /// If `text` starts with `prefix` under this comparison, returns the rest of
+  /// `text`.
+  pub(crate) fn strip_prefix<'a>(
+    self,
+    text: &'a str,
+    prefix: &str,
+  ) -> Option<&'a str> {
+    if self == Case::Sensitive {
+      return text.strip_prefix(prefix);
+    }
+
+    let mut chars = text.chars();
+    for p in prefix.chars() {
+      if !self.eq(chars.next()?, p) {
+        return None;
+      }
+    }
+    Some(chars.as_str())
+  }
+
+  /// If `text` ends with `suffix` under this comparison, returns the rest of
+  /// `text`.
+  pub(crate) fn strip_suffix<'a>(
+    self,
+    text: &'a str,
+    suffix: &str,
+  ) -> Option<&'a str> {
+    if self == Case::Sensitive {
+      return text.strip_suffix(suffix);
+    }
+
+    let mut chars = text.chars();
+    for s in suffix.chars().rev() {
+      if !self.eq(chars.next_back()?, s) {
+        return None;
+      }
+    }
+    Some(chars.as_str())
+  }
+
+  fn eq(self, a: char, b: char) -> bool {
+    match self {
+      Case::Sensitive => a == b,
+      Case::Ascii => a.eq_ignore_ascii_case(&b),
+      Case::Unicode => {
+        a == b
+          || a.to_lowercase().eq(b.to_lowercase())
+          || a.to_uppercase().eq(b.to_uppercase())
+      }
+    }
+  }
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct Affixes {
   prefixes: Vec<Yarn>,
   suffixes: Vec<Yarn>,
+  pub(crate) case: Case,
 }
 
 impl Affixes {
@@ -479,6 +968,20 @@ macro_rules! affixes {
         .extend(suffixes.into_iter().map(Y::into));
       self
     }
+
+    /// Makes this rule's prefixes and suffixes match regardless of ASCII
+    /// case.
+    pub fn case_insensitive_affixes(mut self) -> Self {
+      self.affixes.case = Case::Ascii;
+      self
+    }
+
+    /// Like `case_insensitive_affixes()`, but uses Unicode simple case
+    /// folding, so that non-ASCII letters also match regardless of case.
+    pub fn unicode_case_insensitive_affixes(mut self) -> Self {
+      self.affixes.case = Case::Unicode;
+      self
+    }
   };
 }
 
@@ -487,11 +990,16 @@ macro_rules! affixes {
 /// Identifiers are self-delimiting "words" like `foo` and `黒猫`.
 #[derive(Default, Debug)]
 pub struct Ident {
+  pub(crate) name: Yarn,
   pub(crate) ascii_only: bool,
   pub(crate) extra_starts: String,
   pub(crate) extra_continues: String,
   pub(crate) affixes: Affixes,
   pub(crate) min_len: usize,
+  #[cfg(feature = "normalize")]
+  pub(crate) normalization: Option<Normalization>,
+  #[cfg(feature = "confusables")]
+  pub(crate) confusables: bool,
 }
 
 impl Ident {
@@ -557,7 +1065,57 @@ impl Ident {
     self
   }
 
+  /// Makes this rule normalize the text of the identifiers it matches.
+  ///
+  /// The normalized text is available through
+  /// [`token::Ident::normalized()`]; the token's span still refers to the
+  /// text exactly as it appears in the source. This makes it possible to
+  /// treat, for example, a composed `é` and an `e` followed by a combining
+  /// acute accent as the same identifier.
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Ident::new()
+  ///   .normalize(Normalization::Nfc);
+  /// ```
+  #[cfg(feature = "normalize")]
+  pub fn normalize(mut self, form: Normalization) -> Self {
+    self.normalization = Some(form);
+    self
+  }
+
+  /// Makes this rule diagnose identifiers that could be mistaken for other
+  /// identifiers, as described in [UTS #39](https://unicode.org/reports/tr39/).
+  ///
+  /// Two kinds of identifiers are diagnosed: those that mix scripts, such as
+  /// `pаypal` with a Cyrillic `а`, and those written entirely in non-ASCII
+  /// characters that look like ASCII ones, such as the all-Cyrillic `раура`.
+  /// Both are lints that default to warnings; see
+  /// [`Builtins`][crate::report::Builtins] for their codes.
+  ///
+  /// This complements [`Ident::ascii_only()`], for languages that wish to
+  /// allow non-ASCII identifiers.
+  #[cfg(feature = "confusables")]
+  pub fn detect_confusables(mut self) -> Self {
+    self.confusables = true;
+    self
+  }
+
   affixes!();
+  named!();
+}
+
+/// A Unicode normalization form, for use with [`Ident::normalize()`].
+///
+/// See [UAX #15](https://unicode.org/reports/tr15/) for the precise meaning of
+/// each form.
+#[cfg(feature = "normalize")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Normalization {
+  /// Normalization Form C, i.e., canonical decomposition followed by
+  /// canonical composition.
+  Nfc,
 }
 
 impl Rule for Ident {
@@ -591,7 +1149,8 @@ impl TryFrom<Any> for Ident {
 /// A quoted string rule.
 ///
 /// Quoted strings consist of one or more [`Bracket`] which capture the
-/// Unicode scalars between them. No lexing occurs between these brackets.
+/// Unicode scalars between them. No lexing occurs between these brackets,
+/// except within interpolation holes (see [`Quoted::with_interpolation()`]).
 ///
 /// Escape sequences are processed, which generate `u32` codes (which can be
 /// used to represent values not representable as `char`, particularly for
@@ -600,7 +1159,11 @@ impl TryFrom<Any> for Ident {
 pub struct Quoted {
   pub(crate) bracket: Bracket,
   pub(crate) escapes: Trie<str, Escape>,
+  pub(crate) decoders: HashMap<Yarn, Decoder>,
   pub(crate) affixes: Affixes,
+  pub(crate) single_line: bool,
+  pub(crate) interpolation: Option<(Yarn, Yarn)>,
+  pub(crate) name: Yarn,
 }
 
 impl Quoted {
@@ -618,10 +1181,62 @@ impl Quoted {
     Self {
       bracket,
       escapes: Trie::new(),
+      decoders: HashMap::new(),
       affixes: Affixes::default(),
+      single_line: false,
+      interpolation: None,
+      name: "".into(),
     }
   }
 
+  /// Makes this rule single-line.
+  ///
+  /// If a line ends before the closing quote is found, the string is diagnosed
+  /// as unclosed and lexing resumes on the next line, instead of consuming the
+  /// rest of the file. This keeps the common "forgot a closing quote" mistake
+  /// from swallowing every token that follows it.
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Quoted::new('"')
+  ///   .single_line();
+  /// ```
+  pub fn single_line(mut self) -> Self {
+    self.single_line = true;
+    self
+  }
+
+  /// Adds interpolation holes to this rule, such as the `${...}` in
+  /// JavaScript template strings.
+  ///
+  /// When `open` appears in the string, the lexer lexes ordinary tokens,
+  /// using the whole spec, until it finds a `close` that does not close a
+  /// bracket opened within the hole. The tokens in each hole can be
+  /// retrieved with [`token::Quoted::holes()`].
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Quoted::new('`')
+  ///   .with_interpolation("${", "}");
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if either delimiter is empty.
+  pub fn with_interpolation(
+    mut self,
+    open: impl Into<Yarn>,
+    close: impl Into<Yarn>,
+  ) -> Self {
+    let (open, close) = (open.into(), close.into());
+    assert!(
+      !open.is_empty() && !close.is_empty(),
+      "cannot create interpolation holes with empty delimiters"
+    );
+    self.interpolation = Some((open, close));
+    self
+  }
+
   /// Adds a basic escape rule to this rule.
   ///
   /// A basic escape is one that just appears literally in the string,
@@ -741,16 +1356,243 @@ impl Quoted {
     self
   }
 
-  /// Adds the Rust escaping rules to this rule.
+  /// Adds an expanding escape rule to this rule.
+  ///
+  /// Other kinds of escapes are decoded by the user, and usually stand for a
+  /// single character. An expanding escape is instead decoded by ilex itself,
+  /// into a string of any length.
+  ///
+  /// The escape consists of `key`, followed by any text up to and including
+  /// the next `close` (which may not cross the string's closing quote).
+  /// `parse` is called with the text between them, and returns the string
+  /// the escape stands for, or `None` if the escape is invalid. If `close` is
+  /// empty, the escape is just `key`, and `parse` is called with the empty
+  /// string.
+  ///
+  /// The resulting strings are available from [`token::Quoted::expansions()`],
+  /// and are used automatically by [`token::Quoted::to_utf8()`].
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Quoted::new('"')
+  ///   .expanding_escape(r"\&", ";", |name| match name {
+  ///     "copy" => Some("©".into()),
+  ///     "hellip" => Some("...".into()),
+  ///     _ => None,
+  ///   })
+  ///   // Normalize Windows newlines in multi-line strings.
+  ///   .expanding_escape("\r\n", "", |_| Some("\n".into()));
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the key is empty.
+  pub fn expanding_escape(
+    mut self,
+    key: impl Into<Yarn>,
+    close: impl Into<Yarn>,
+    parse: impl Fn(&str) -> Option<Yarn> + Send + Sync + 'static,
+  ) -> Self {
+    let key = key.into();
+    assert!(!key.is_empty());
+    self.escapes.insert(
+      &key,
+      Escape::Expand(
+        close.into(),
+        Expander {
+          parse: Box::new(parse),
+          error: "unrecognized escape sequence",
+        },
+      ),
+    );
+    self
+  }
+
+  /// Adds a UTF-16 escape rule to this rule.
+  ///
+  /// The escape consists of `key` followed by exactly four hex digits, which
+  /// specify a UTF-16 code unit, such as JavaScript's `\uNNNN`. A character
+  /// outside of the Basic Multilingual Plane is written as two such escapes
+  /// that form a surrogate pair, such as `\ud83d\ude00`; ilex combines these
+  /// into a single escape, and diagnoses surrogates that are not paired.
+  ///
+  /// Like any other expanding escape, the decoded character is available from
+  /// [`token::Quoted::expansions()`].
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Quoted::new('"')
+  ///   .utf16_escape(r"\u");
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the key is empty.
+  pub fn utf16_escape(mut self, key: impl Into<Yarn>) -> Self {
+    let key = key.into();
+    assert!(!key.is_empty());
+    self.escapes.insert(&key, Escape::Utf16);
+    self
+  }
+
+  /// Adds a Unicode character name escape rule to this rule.
+  ///
+  /// The escape consists of `key` followed by the name of a character in
+  /// braces, such as Python's `\N{LATIN SMALL LETTER A}`. Names are matched
+  /// case-insensitively, and may also be aliases or named sequences.
+  ///
+  /// Like any other expanding escape, the character is available from
+  /// [`token::Quoted::expansions()`].
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Quoted::new('"')
+  ///   .unicode_name_escape(r"\N");
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the key is empty.
+  #[cfg(feature = "unicode-names")]
+  pub fn unicode_name_escape(mut self, key: impl Into<Yarn>) -> Self {
+    let key = key.into();
+    assert!(!key.is_empty());
+    self.escapes.insert(
+      format!("{key}{{").as_str(),
+      Escape::Expand(
+        "}".into(),
+        Expander {
+          parse: Box::new(|name| {
+            unicode_names2::character(name).map(|c| Yarn::from(c.to_string()))
+          }),
+          error: "unknown character name",
+        },
+      ),
+    );
+    self
+  }
+
+  /// Sets how the escape with the given key is decoded by
+  /// [`token::Quoted::decode_codes()`] and [`token::Quoted::decode_utf8()`].
+  ///
+  /// `parse` is called with the escape's data (such as the `NN` in `\xNN`, or
+  /// the empty string for a basic escape), and returns the character code the
+  /// escape stands for, or `None` if the data is invalid. Expanding escapes
+  /// are decoded to their expansions, and do not need a decoder.
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Quoted::new('"')
+  ///   .escape(r"\n")
+  ///   .escape_decoder(r"\n", |_| Some('\n'.into()))
+  ///   .fixed_length_escape(r"\x", 2)
+  ///   .escape_decoder(r"\x", |hex| u32::from_str_radix(hex, 16).ok());
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the key is empty.
+  pub fn escape_decoder(
+    mut self,
+    key: impl Into<Yarn>,
+    parse: impl Fn(&str) -> Option<u32> + Send + Sync + 'static,
+  ) -> Self {
+    let key = key.into();
+    assert!(!key.is_empty());
+    self
+      .decoders
+      .insert(key, Decoder { parse: Box::new(parse) });
+    self
+  }
+
+  /// Adds basic escapes that each stand for the given character.
+  fn decoded_escapes(mut self, escapes: &[(&'static str, char)]) -> Self {
+    for &(key, c) in escapes {
+      self = self
+        .escape(key)
+        .escape_decoder(key, move |_| Some(c.into()));
+    }
+    self
+  }
+
+  /// Adds the Rust escaping rules to this rule, along with their decoders.
   pub fn add_rust_escapes(self) -> Self {
     self
       .invalid_escape(r"\")
-      .escapes([r"\0", r"\n", r"\r", r"\t", r"\\", "\\\"", r"\'"])
+      .decoded_escapes(&[
+        (r"\0", '\0'),
+        (r"\n", '\n'),
+        (r"\r", '\r'),
+        (r"\t", '\t'),
+        (r"\\", '\\'),
+        ("\\\"", '"'),
+        (r"\'", '\''),
+      ])
       .fixed_length_escape(r"\x", 2)
+      .escape_decoder(r"\x", parse_hex)
       .bracketed_escape(r"\u", '{', '}')
+      .escape_decoder(r"\u", parse_hex)
+  }
+
+  /// Adds the C escaping rules to this rule.
+  ///
+  /// This includes octal escapes of one to three digits, such as `\0` and
+  /// `\177`, each of which is a separate escape whose text contains the
+  /// digits. `\x` always takes exactly two hex digits.
+  pub fn add_c_escapes(mut self) -> Self {
+    let octal = (0..0o10)
+      .map(|n| (n, format!(r"\{n:o}")))
+      .chain((0..0o100).map(|n| (n, format!(r"\{n:02o}"))))
+      .chain((0..0o1000).map(|n| (n, format!(r"\{n:03o}"))));
+    for (n, key) in octal {
+      let key = Yarn::from(key);
+      self = self
+        .escape(key.clone())
+        .escape_decoder(key, move |_| Some(n));
+    }
+
+    self
+      .invalid_escape(r"\")
+      .decoded_escapes(&[
+        (r"\a", '\x07'),
+        (r"\b", '\x08'),
+        (r"\f", '\x0c'),
+        (r"\n", '\n'),
+        (r"\r", '\r'),
+        (r"\t", '\t'),
+        (r"\v", '\x0b'),
+        (r"\\", '\\'),
+        ("\\\"", '"'),
+        (r"\'", '\''),
+        (r"\?", '?'),
+      ])
+      .fixed_length_escape(r"\x", 2)
+      .escape_decoder(r"\x", parse_hex)
+      .fixed_length_escape(r"\u", 4)
+      .escape_decoder(r"\u", parse_hex)
+      .fixed_length_escape(r"\U", 8)
+      .escape_decoder(r"\U", parse_hex)
+  }
+
+  /// Adds the JSON escaping rules to this rule, along with their decoders.
+  pub fn add_json_escapes(self) -> Self {
+    self
+      .invalid_escape(r"\")
+      .decoded_escapes(&[
+        (r"\b", '\x08'),
+        (r"\f", '\x0c'),
+        (r"\n", '\n'),
+        (r"\r", '\r'),
+        (r"\t", '\t'),
+        (r"\/", '/'),
+        (r"\\", '\\'),
+        ("\\\"", '"'),
+      ])
+      .utf16_escape(r"\u")
   }
 
   affixes!();
+  named!();
 }
 
 impl From<Bracket> for Quoted {
@@ -813,6 +1655,59 @@ pub(crate) enum Escape {
   /// This can be used to implement escapes like Rust's version of `\u`
   /// (aka `\u{NNNN}`).
   Bracketed(Yarn, Yarn),
+
+  /// The escape consumes the text up to the given closer (if it is nonempty)
+  /// and passes it to the [`Expander`], which converts it into the string the
+  /// escape stands for.
+  ///
+  /// This can be used to implement escapes that do not stand for a single
+  /// character, such as HTML-style named entities (aka `\&copy;`).
+  Expand(Yarn, Expander),
+
+  /// The escape consumes four hex digits, which specify a UTF-16 code unit.
+  /// If it is a high surrogate, it must be followed by the same escape
+  /// containing a low surrogate, and the two are decoded together.
+  ///
+  /// This can be used to implement escapes like JSON's `\u` (aka `\uNNNN`).
+  Utf16,
+}
+
+/// Parses the body of an [`Escape::Expand`] into its expansion.
+pub(crate) type ExpandFn = Box<dyn Fn(&str) -> Option<Yarn> + Send + Sync>;
+
+/// The function that decodes an [`Escape::Expand`].
+pub(crate) struct Expander {
+  pub(crate) parse: ExpandFn,
+  /// The diagnostic to emit when `parse` fails.
+  pub(crate) error: &'static str,
+}
+
+impl fmt::Debug for Expander {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("Expander(..)")
+  }
+}
+
+/// Parses the data of an escape into the character code it stands for.
+pub(crate) type DecodeFn = Box<dyn Fn(&str) -> Option<u32> + Send + Sync>;
+
+/// The function that decodes an escape; see [`Quoted::escape_decoder()`].
+pub(crate) struct Decoder {
+  pub(crate) parse: DecodeFn,
+}
+
+impl fmt::Debug for Decoder {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("Decoder(..)")
+  }
+}
+
+/// Parses a non-empty string of hex digits, such as the data of a `\x` escape.
+fn parse_hex(hex: &str) -> Option<u32> {
+  if hex.is_empty() || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+    return None;
+  }
+  u32::from_str_radix(hex, 16).ok()
 }
 
 /// A digital literal rule.
@@ -833,6 +1728,7 @@ pub struct Digital {
   pub(crate) point: Yarn,
 
   pub(crate) affixes: Affixes,
+  pub(crate) name: Yarn,
 }
 
 /// Places in which a separator in a [`Digital`] is allowed.
@@ -875,18 +1771,31 @@ impl Default for SeparatorCornerCases {
 }
 
 impl Digital {
-  /// Creates a new rule with the given radix (which must be between 2 and 16).
+  /// Creates a new rule with the given radix (which must be between 2 and 36).
+  ///
+  /// Digits with values 10 through 35 are written as the letters `a` through
+  /// `z`, in either case.
   ///
   /// For example, `Digital::new(16)` creates a rule for hexadecimal.
   pub fn new(radix: u8) -> Self {
     assert!(
-      (2..=16).contains(&radix),
-      "radix must be within 2..=16, got {radix}"
+      (2..=36).contains(&radix),
+      "radix must be within 2..=36, got {radix}"
     );
 
     Self::from_digits(Digits::new(radix))
   }
 
+  /// Creates a new rule whose digits are the characters of `alphabet`; see
+  /// [`Digits::with_alphabet()`].
+  ///
+  /// For example, `Digital::with_alphabet("01234567")` is equivalent to
+  /// `Digital::new(8)`.
+  #[track_caller]
+  pub fn with_alphabet(alphabet: impl Into<Yarn>) -> Self {
+    Self::from_digits(Digits::with_alphabet(alphabet))
+  }
+
   /// Creates a new rule from a [`Digits`].
   pub fn from_digits(digits: Digits) -> Self {
     Self {
@@ -897,6 +1806,7 @@ impl Digital {
       corner_cases: Default::default(),
       point: ".".into(),
       affixes: Affixes::default(),
+      name: "".into(),
     }
   }
 
@@ -994,6 +1904,7 @@ impl Digital {
   }
 
   affixes!();
+  named!();
 }
 
 /// A digit chunk within a [`Digital`].
@@ -1003,31 +1914,89 @@ impl Digital {
 #[derive(Debug, Clone)]
 pub struct Digits {
   pub(crate) radix: u8,
+  pub(crate) alphabet: Option<Yarn>,
   pub(crate) signs: Vec<(Yarn, Sign)>,
   pub(crate) min_chunks: u32,
   pub(crate) max_chunks: u32,
 }
 
 impl Digits {
-  /// Creates a new base, with the given radix (which must be between 2 and 16).
+  /// Creates a new base, with the given radix (which must be between 2 and 36).
+  ///
+  /// Digits with values 10 through 35 are written as the letters `a` through
+  /// `z`, in either case.
   ///
   /// For example, `Digital::new(16)` creates a base for hexadecimal.
   pub fn new(radix: u8) -> Self {
     assert!(
-      (2..=16).contains(&radix),
-      "radix must be within 2..=16, got {radix}"
+      (2..=36).contains(&radix),
+      "radix must be within 2..=36, got {radix}"
     );
 
     Self {
       radix,
+      alphabet: None,
       signs: Vec::new(),
       min_chunks: 1,
       max_chunks: 1,
     }
   }
 
+  /// Creates a new base whose digits are the characters of `alphabet`, in
+  /// order of increasing value; the radix is the number of characters.
+  ///
+  /// Unlike [`Digits::new()`], digits are case-sensitive, and may be any
+  /// characters at all, which allows for non-contiguous digit sets such as
+  /// base58.
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// Digits::with_alphabet(
+  ///   "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz",
+  /// );
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if `alphabet` has fewer than 2 or more than 255 characters, or if
+  /// any character occurs more than once.
+  #[track_caller]
+  pub fn with_alphabet(alphabet: impl Into<Yarn>) -> Self {
+    let alphabet = alphabet.into();
+    let radix = alphabet.chars().count();
+    assert!(
+      (2..=255).contains(&radix),
+      "alphabet must have between 2 and 255 digits, got {radix}"
+    );
+    for (i, c) in alphabet.char_indices() {
+      assert!(
+        !alphabet[..i].contains(c),
+        "alphabet contains the digit {c:?} more than once"
+      );
+    }
+
+    Self {
+      radix: radix as u8,
+      alphabet: Some(alphabet),
+      signs: Vec::new(),
+      min_chunks: 1,
+      max_chunks: 1,
+    }
+  }
+
+  /// Returns the value of the digit `c`, if it is a digit in this base.
+  pub(crate) fn digit_value(&self, c: char) -> Option<u32> {
+    match &self.alphabet {
+      Some(alphabet) => alphabet.chars().position(|d| d == c).map(|i| i as u32),
+      None => c.to_digit(self.radix as u32),
+    }
+  }
+
   /// Returns the name of this rule's radix (e.g., "binary").
   /// Useful for diagnostics.
+  ///
+  /// Radixes above 36, which are only possible with a custom alphabet, are
+  /// named `"custom"`.
   pub fn radix_name(&self) -> &'static str {
     match self.radix {
       2 => "binary",
@@ -1044,8 +2013,44 @@ impl Digits {
       13 => "tridecimal",
       14 => "tetradecimal",
       15 => "pentadecimal",
-      16 => "hexadecmial",
-      _ => unreachable!(),
+      16 => "hexadecimal",
+      17 => "heptadecimal",
+      18 => "octodecimal",
+      19 => "enneadecimal",
+      20 => "vigesimal",
+      21 => "unvigesimal",
+      22 => "duovigesimal",
+      23 => "trivigesimal",
+      24 => "tetravigesimal",
+      25 => "pentavigesimal",
+      26 => "hexavigesimal",
+      27 => "septemvigesimal",
+      28 => "octovigesimal",
+      29 => "novemvigesimal",
+      30 => "trigesimal",
+      31 => "untrigesimal",
+      32 => "duotrigesimal",
+      33 => "tritrigesimal",
+      34 => "tetratrigesimal",
+      35 => "pentatrigesimal",
+      36 => "hexatrigesimal",
+      _ => "custom",
+    }
+  }
+
+  /// Returns a description of the valid digits in this rule's radix, such as
+  /// `within '0'..='7'` or `one of `01`. Useful for diagnostics.
+  pub(crate) fn allowed_digits(&self) -> String {
+    if let Some(alphabet) = &self.alphabet {
+      return format!("one of `{alphabet}`");
+    }
+
+    let radix = self.radix as u32;
+    let last = char::from_digit(radix - 1, radix).unwrap();
+    if radix <= 10 {
+      format!("within '0'..='{last}'")
+    } else {
+      format!("within '0'..='9' or 'a'..='{last}'")
     }
   }
 
@@ -1128,6 +2133,7 @@ impl TryFrom<Any> for Digital {
 pub struct Comment {
   pub(crate) bracket: Bracket,
   pub(crate) can_nest: bool,
+  pub(crate) name: Yarn,
 }
 
 impl Comment {
@@ -1146,14 +2152,20 @@ impl Comment {
   /// Creates a new comment that can nest. For example, Rust block comments
   /// can nest: `/* /* */ */`
   pub fn nesting(bracket: Bracket) -> Self {
-    Self { bracket, can_nest: true }
+    Self { bracket, can_nest: true, name: "".into() }
   }
 
   /// Creates a new comment that can't nest. For example, a line comment is a
   /// non-nesting comment where a newline '\n' is the closing delimiter.
   pub fn non_nesting(bracket: Bracket) -> Self {
-    Self { bracket, can_nest: false }
+    Self {
+      bracket,
+      can_nest: false,
+      name: "".into(),
+    }
   }
+
+  named!();
 }
 
 impl From<&'static str> for Comment {
@@ -5,6 +5,7 @@ use std::fmt;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::ops::RangeInclusive;
 
 use byteyarn::yarn;
 use byteyarn::Yarn;
@@ -15,6 +16,8 @@ use crate::report::Expected;
 use crate::rt;
 use crate::rule;
 use crate::rule::Comment;
+use crate::rule::Dedent;
+use crate::rule::Indent;
 use crate::rule::LineEnd;
 use crate::rule::Rule;
 
@@ -56,7 +59,10 @@ impl<R> Lexeme<R> {
   pub(crate) fn can_have_comments(self, spec: &Spec) -> bool {
     !self.is_aux()
       && (self.is_eof()
-        || !matches!(spec.rule(self.any()), rule::Any::Comment(_)))
+        || !matches!(
+          spec.rule(self.any()),
+          rule::Any::Comment(_) | rule::Any::Indent(_) | rule::Any::Dedent(_)
+        ))
   }
 
   /// Converts this lexeme into an index.
@@ -91,6 +97,13 @@ pub struct Spec {
 }
 
 impl Spec {
+  /// The version of Unicode that ilex's built-in identifier tables were
+  /// generated for, as a `(major, minor, update)` triple.
+  ///
+  /// These tables are part of ilex itself, so they only change when ilex does.
+  /// To be independent of them entirely, see [`SpecBuilder::xid_tables()`].
+  pub const UNICODE_VERSION: (u64, u64, u64) = rt::unicode::UNICODE_VERSION;
+
   /// Returns a new [`SpecBuilder`].
   pub fn builder() -> SpecBuilder {
     SpecBuilder::default()
@@ -129,6 +142,16 @@ impl Spec {
       .unwrap_or(Expected::Literal(or.to_string().into()))
   }
 
+  /// Returns whether `c` can continue an identifier.
+  pub(crate) fn is_xid(&self, c: char) -> bool {
+    rt::unicode::search_ranges(c, self.builder.xid().cont)
+  }
+
+  /// Returns the priority of the rule corresponding to a particular lexeme.
+  pub(crate) fn priority(&self, lexeme: Lexeme<rule::Any>) -> i32 {
+    self.builder.priorities[lexeme.index()]
+  }
+
   /// Returns the underlying DFAs for this spec.
   pub(crate) fn dfa(&self) -> &rt::Dfa {
     &self.dfa
@@ -140,7 +163,19 @@ impl Spec {
 pub struct SpecBuilder {
   pub(crate) rules: Vec<rule::Any>,
   pub(crate) names: Vec<Yarn>,
+  pub(crate) priorities: Vec<i32>,
   pub(crate) line_end: Option<Lexeme<LineEnd>>,
+  pub(crate) indent: Option<(Lexeme<Indent>, Lexeme<Dedent>)>,
+  indent_parts: (Option<Lexeme<Indent>>, Option<Lexeme<Dedent>>),
+  xid: Option<XidRanges<Vec<(char, char)>>>,
+}
+
+/// The ranges of characters that can start and continue an identifier, sorted
+/// and disjoint.
+#[derive(Copy, Clone)]
+pub(crate) struct XidRanges<R> {
+  pub start: R,
+  pub cont: R,
 }
 
 impl SpecBuilder {
@@ -154,15 +189,22 @@ impl SpecBuilder {
   ///
   /// Panics if any of the invariants of a [`Spec`] are violated, or if any rule
   /// combinations are ambiguous (e.g., they have the same prefix).
-  pub fn compile(self) -> Spec {
-    let dfa = rt::compile(&self.rules);
+  pub fn compile(mut self) -> Spec {
+    self.indent = match self.indent_parts {
+      (None, None) => None,
+      (Some(indent), Some(dedent)) => Some((indent, dedent)),
+      _ => panic!("ilex: a spec with an Indent rule must also have a Dedent rule, and vice-versa"),
+    };
+
+    let dfa = rt::compile(&self.rules, self.xid());
     Spec { builder: self, dfa }
   }
 
   /// Adds a new rule to the [`Spec`] being built.
   ///
   /// When parsing the next token, the `ilex` lexer will select the longest
-  /// matching token, giving priority to tokens *added first*.
+  /// matching token, giving priority to tokens *added first*. This can be
+  /// overridden with [`SpecBuilder::set_priority()`].
   ///
   /// ```
   /// # use ilex::*;
@@ -198,25 +240,131 @@ impl SpecBuilder {
       )
     }
 
-    self.names.push(name.into());
-    self.rules.push(rule.into());
+    let rule = rule.into();
+    let mut name = name.into();
+    if name.is_empty() {
+      name = rule.name().clone();
+    }
+
+    self.names.push(name);
+    self.priorities.push(0);
+    self.rules.push(rule);
     let lex = Lexeme::new(self.rules.len() as i32 - 1);
-    if let rule::Any::LineEnd(_) = self.rules.last().unwrap() {
-      self.line_end = Some(lex.cast());
+    match self.rules.last().unwrap() {
+      rule::Any::LineEnd(_) => self.line_end = Some(lex.cast()),
+      rule::Any::Indent(_) => self.indent_parts.0 = Some(lex.cast()),
+      rule::Any::Dedent(_) => self.indent_parts.1 = Some(lex.cast()),
+      _ => {}
     }
     lex
   }
 
+  /// Sets the priority of a rule that was already added to this builder.
+  ///
+  /// Priority is only used to break ties between rules that match the *same*
+  /// number of bytes; the longest match always wins, regardless of priority.
+  /// Among equally long matches, the rule with the highest priority wins, and
+  /// rules with the same priority are ordered by which was added first. All
+  /// rules start out with priority zero.
+  ///
+  /// ```
+  /// # use ilex::*;
+  /// use ilex::rule;
+  /// let mut builder = Spec::builder();
+  /// let ident = builder.rule(rule::Ident::new());
+  ///
+  /// // Without this, `null` would always lex as an identifier.
+  /// let null = builder.rule(rule::Keyword::new("null"));
+  /// builder.set_priority(null, 1);
+  /// let spec = builder.compile();
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// May panic if the given lexeme came from another builder.
+  pub fn set_priority<R>(&mut self, lexeme: Lexeme<R>, priority: i32) {
+    self.priorities[lexeme.index()] = priority;
+  }
+
+  /// Overrides which characters can start and continue an identifier.
+  ///
+  /// By default, these are given by the Unicode `XID_Start` and
+  /// `XID_Continue` properties, using tables built into ilex (see
+  /// [`Spec::UNICODE_VERSION`]). This replaces them with the given ranges,
+  /// which affects which characters [`rule::Ident`]s match, and which
+  /// characters are diagnosed as extraneous after another token. This makes it
+  /// possible to freeze a language's identifier rules regardless of the
+  /// version of ilex in use.
+  ///
+  /// ```
+  /// # use ilex::*;
+  /// let mut builder = Spec::builder();
+  /// builder.xid_tables(
+  ///   ['A'..='Z', '_'..='_', 'a'..='z'],
+  ///   ['0'..='9', 'A'..='Z', '_'..='_', 'a'..='z'],
+  /// );
+  /// let ident = builder.rule(rule::Ident::new());
+  /// let spec = builder.compile();
+  /// ```
+  pub fn xid_tables(
+    &mut self,
+    start: impl IntoIterator<Item = RangeInclusive<char>>,
+    cont: impl IntoIterator<Item = RangeInclusive<char>>,
+  ) {
+    // Ranges are binary-searched, so they need to be sorted and disjoint.
+    fn normalize(
+      ranges: impl IntoIterator<Item = RangeInclusive<char>>,
+    ) -> Vec<(char, char)> {
+      let mut ranges = ranges
+        .into_iter()
+        .filter(|r| !r.is_empty())
+        .map(|r| r.into_inner())
+        .collect::<Vec<_>>();
+      ranges.sort();
+
+      let mut merged = Vec::<(char, char)>::with_capacity(ranges.len());
+      for (lo, hi) in ranges {
+        match merged.last_mut() {
+          Some((_, prev)) if lo as u32 <= *prev as u32 + 1 => {
+            *prev = (*prev).max(hi)
+          }
+          _ => merged.push((lo, hi)),
+        }
+      }
+      merged
+    }
+
+    self.xid = Some(XidRanges {
+      start: normalize(start),
+      cont: normalize(cont),
+    });
+  }
+
+  /// Returns the ranges of characters that can start and continue an
+  /// identifier, respectively.
+  pub(crate) fn xid(&self) -> XidRanges<&[(char, char)]> {
+    match &self.xid {
+      Some(XidRanges { start, cont }) => XidRanges { start, cont },
+      None => XidRanges {
+        start: rt::unicode::XID_START,
+        cont: rt::unicode::XID_CONTINUE,
+      },
+    }
+  }
+
   #[doc(hidden)]
   pub fn __macro_rule<R: Rule>(
     &mut self,
     name: Option<&'static str>,
+    priority: i32,
     rule: impl Into<R>,
   ) -> Lexeme<R> {
-    match name {
+    let lexeme = match name {
       Some(name) => self.named_rule(name, rule.into()),
       None => self.rule(rule.into()),
-    }
+    };
+    self.set_priority(lexeme, priority);
+    lexeme
   }
 }
 
@@ -245,6 +393,20 @@ impl<R> Ord for Lexeme<R> {
   }
 }
 
+#[cfg(feature = "serde")]
+impl<R> serde::Serialize for Lexeme<R> {
+  fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    self.id.serialize(s)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, R> serde::Deserialize<'de> for Lexeme<R> {
+  fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    i32::deserialize(d).map(Lexeme::new)
+  }
+}
+
 impl<R> Hash for Lexeme<R> {
   fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
     i32::hash(&self.id, state)
@@ -265,6 +427,8 @@ impl Lexeme<rule::Any> {
     match spec.rule(self) {
       rule::Any::Keyword(rule) => yarn!("`{}`", rule.value),
       rule::Any::LineEnd(_) => "line ending".into(),
+      rule::Any::Indent(_) => "indentation".into(),
+      rule::Any::Dedent(_) => "dedentation".into(),
       rule::Any::Bracket(d)
       | rule::Any::Comment(Comment { bracket: d, .. }) => match &d.kind {
         rule::BracketKind::Paired(open, close) => {
@@ -285,6 +449,9 @@ impl Lexeme<rule::Any> {
         rule::BracketKind::CxxLike {
           open: (o1, o2), close: (c1, c2), ..
         } => yarn!("`{o1}<ident>{o2} ... {c1}<ident>{c2}`"),
+        rule::BracketKind::Heredoc { introducer, .. } => {
+          yarn!("`{introducer}<ident> ... <ident>`")
+        }
       },
 
       rule::Any::Ident(tok) => {
@@ -296,6 +463,7 @@ impl Lexeme<rule::Any> {
       rule::Any::Digital(tok) => {
         yarn!("{}number", tok.affixes)
       }
+      rule::Any::Regex(rule) => yarn!("match for `{}`", rule.source),
     }
   }
 }
@@ -334,7 +502,7 @@ macro_rules! __spec__ {
             $($rule: $crate::__spec__!(
               @impl "call-meta"
               $(#[$($fmeta)*])*;
-              spec, $ty, $rule, name: None, rule: stringify!($rule)
+              spec, $ty, $rule, name: None, priority: 0, rule: stringify!($rule)
             ),)*
             __spec: spec.compile(),
           }
@@ -351,51 +519,69 @@ macro_rules! __spec__ {
   (@impl "call-meta"
     #[named($arg:literal)]
     $(#[$($rest:tt)+])*;
-    $spec:ident, $ty:ty, $ident:ident, name: $name:expr, rule: $rule:expr
+    $spec:ident, $ty:ty, $ident:ident,
+    name: $name:expr, priority: $priority:expr, rule: $rule:expr
   ) => {
     $crate::__spec__!(@impl "call-meta"
       $(#[$($rest)+])*;
-      $spec, $ty, $ident, name: Some($arg), rule: $rule
+      $spec, $ty, $ident, name: Some($arg), priority: $priority, rule: $rule
     )
   };
 
   (@impl "call-meta"
     #[named]
     $(#[$($rest:tt)+])*;
-    $spec:ident, $ty:ty, $ident:ident, name: $name:expr, rule: $rule:expr
+    $spec:ident, $ty:ty, $ident:ident,
+    name: $name:expr, priority: $priority:expr, rule: $rule:expr
+  ) => {
+    $crate::__spec__!(@impl "call-meta"
+      $(#[$($rest)+])*;
+      $spec, $ty, $ident,
+      name: Some(stringify!($ident)), priority: $priority, rule: $rule
+    )
+  };
+
+  (@impl "call-meta"
+    #[priority($arg:expr)]
+    $(#[$($rest:tt)+])*;
+    $spec:ident, $ty:ty, $ident:ident,
+    name: $name:expr, priority: $priority:expr, rule: $rule:expr
   ) => {
     $crate::__spec__!(@impl "call-meta"
       $(#[$($rest)+])*;
-      $spec, $ty, $ident, name: Some(stringify!($ident)), rule: $rule
+      $spec, $ty, $ident, name: $name, priority: $arg, rule: $rule
     )
   };
 
   (@impl "call-meta"
     #[rule($($arg:tt)*)]
     $(#[$($rest:tt)+])*;
-    $spec:ident, $ty:ty, $ident:ident, name: $name:expr, rule: $rule:expr
+    $spec:ident, $ty:ty, $ident:ident,
+    name: $name:expr, priority: $priority:expr, rule: $rule:expr
   ) => {
     $crate::__spec__!(@impl "call-meta"
       $(#[$($rest)+])*;
-      $spec, $ty, $ident, name: $name, rule: ($($arg)*)
+      $spec, $ty, $ident, name: $name, priority: $priority, rule: ($($arg)*)
     )
   };
 
   (@impl "call-meta"
     #[$($meta:tt)+]
     $(#[$($rest:tt)+])*;
-    $spec:ident, $ty:ty, $ident:ident, name: $name:expr, rule: $rule:expr
+    $spec:ident, $ty:ty, $ident:ident,
+    name: $name:expr, priority: $priority:expr, rule: $rule:expr
   ) => {
     $crate::__spec__!(@impl "call-meta"
       $(#[$($rest)+])*;
-      $spec, $ty, $ident, name: $name, rule: $rule
+      $spec, $ty, $ident, name: $name, priority: $priority, rule: $rule
     )
   };
 
   (@impl "call-meta"
     ;
-    $spec:ident, $ty:ty, $ident:ident, name: $name:expr, rule: $rule:expr
+    $spec:ident, $ty:ty, $ident:ident,
+    name: $name:expr, priority: $priority:expr, rule: $rule:expr
   ) => {
-    $spec.__macro_rule($name, $rule)
+    $spec.__macro_rule($name, $priority, $rule)
   };
 }
@@ -17,7 +17,10 @@ use std::ops::RangeBounds;
 use std::panic::Location;
 
 use byteyarn::yarn;
+use byteyarn::Yarn;
 use byteyarn::YarnBox;
+#[cfg(feature = "normalize")]
+use byteyarn::YarnRef;
 use num_traits::Bounded;
 
 use crate::f;
@@ -34,6 +37,8 @@ use crate::spec::Spec;
 use crate::Never;
 use crate::WrongKind;
 
+#[cfg(feature = "serde")]
+mod serialize;
 mod stream;
 pub mod summary;
 
@@ -48,6 +53,7 @@ pub use stream::Stream;
 /// An [`Id`] is a lightweight handle to some token, which can be converted
 /// back into that token using the corresponding [`Stream`].
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Id(pub(crate) NonZeroU32);
 
 impl Id {
@@ -506,6 +512,23 @@ impl<'lex> Ident<'lex> {
   pub fn has_suffix(&self, expected: &str) -> bool {
     self.suffix().is_some_and(|s| s.text() == expected)
   }
+
+  /// Returns this token's name, normalized as specified by
+  /// [`rule::Ident::normalize()`].
+  ///
+  /// If the rule does not normalize identifiers, or the name was already
+  /// normalized, this is just the text of [`Ident::name()`].
+  #[cfg(feature = "normalize")]
+  pub fn normalized(self) -> YarnRef<'lex, str> {
+    match self
+      .stream
+      .lookup_meta(self.id)
+      .and_then(|m| m.kind.as_ref())
+    {
+      Some(rt::Kind::Ident(text)) => YarnRef::new(text),
+      _ => YarnRef::new(self.name().text()),
+    }
+  }
 }
 
 impl<'lex> Token<'lex> for Ident<'lex> {
@@ -700,24 +723,13 @@ impl<'lex> Digital<'lex> {
   where
     N: Bounded + PartialOrd + FromRadix + fmt::Display,
   {
-    let rule = self.rule().unwrap();
     let radix = self.radix();
     let here = Location::caller();
 
     self
       .digit_blocks()
       .map(|span| {
-        let text = span.text();
-        let buf;
-        let text =
-          if !rule.separator.is_empty() && text.contains(&*rule.separator) {
-            buf = text.replace(&*rule.separator, "");
-            buf.as_str()
-          } else {
-            text
-          };
-
-        let mut value = N::from_radix(text, radix, &rule.separator);
+        let mut value = N::from_digits(self.digit_values(span), radix.into());
         if self.is_negative() {
           value = value.and_then(N::checked_neg);
         }
@@ -759,7 +771,7 @@ impl<'lex> Digital<'lex> {
     range: impl RangeBounds<Fp>,
     report: &Report,
   ) -> Result<Fp, fp::Exotic> {
-    let fp: Fp = self.parse_fp(report, false)?;
+    let fp: Fp = self.parse_fp(Some(report), false)?;
 
     if !fp.__is_finite() || !range.contains(&fp) {
       report.builtins(self.spec()).literal_out_of_range(
@@ -785,7 +797,7 @@ impl<'lex> Digital<'lex> {
     range: impl RangeBounds<Fp>,
     report: &Report,
   ) -> Result<Fp, fp::Exotic> {
-    let fp: Fp = self.parse_fp(report, true)?;
+    let fp: Fp = self.parse_fp(Some(report), true)?;
 
     if !fp.__is_finite() || !range.contains(&fp) {
       report.builtins(self.spec()).literal_out_of_range(
@@ -800,7 +812,99 @@ impl<'lex> Digital<'lex> {
     Ok(fp)
   }
 
-  fn digit_rule(self) -> &'lex rule::Digits {
+  /// Parses this token as a `u64`, without generating diagnostics.
+  ///
+  /// Returns [`IntError::NotAnInteger`] if there is more than one digit block
+  /// or any exponents, and [`IntError::Overflow`] if the value does not fit in
+  /// a `u64` (including if it is negative).
+  pub fn to_u64(self) -> Result<u64, IntError> {
+    match self.magnitude::<u64>()? {
+      0 => Ok(0),
+      _ if self.is_negative() => Err(IntError::Overflow),
+      mag => Ok(mag),
+    }
+  }
+
+  /// Parses this token as an `i128`, without generating diagnostics.
+  ///
+  /// Returns [`IntError::NotAnInteger`] if there is more than one digit block
+  /// or any exponents, and [`IntError::Overflow`] if the value does not fit in
+  /// an `i128`.
+  pub fn to_i128(self) -> Result<i128, IntError> {
+    let mag = self.magnitude::<u128>()?;
+    let value = if self.is_negative() {
+      0i128.checked_sub_unsigned(mag)
+    } else {
+      i128::try_from(mag).ok()
+    };
+    value.ok_or(IntError::Overflow)
+  }
+
+  /// Parses this token as an `f64`, without generating diagnostics.
+  ///
+  /// This accepts the same literals as [`Digital::to_float()`]: up to two
+  /// digit blocks, followed by at most one single-block exponent. Anything
+  /// else produces [`FloatError::NotAFloat`]. Values too large to be finite
+  /// produce [`FloatError::Overflow`].
+  pub fn to_f64(self) -> Result<f64, FloatError> {
+    if self.digit_blocks().count() > 2
+      || self.exponents().count() > 1
+      || self
+        .exponents()
+        .next()
+        .is_some_and(|exp| exp.digit_blocks().count() > 1)
+    {
+      return Err(FloatError::NotAFloat);
+    }
+
+    let fp = self
+      .parse_fp::<fp::Fp64>(None, false)
+      .map_err(FloatError::Exotic)?;
+    if !fp.is_finite() {
+      return Err(FloatError::Overflow);
+    }
+    Ok(fp.to_hard())
+  }
+
+  /// Parses the absolute value of this token as an integer.
+  fn magnitude<N: FromRadix>(self) -> Result<N, IntError> {
+    let mut blocks = self.digit_blocks();
+    let (Some(block), None, None) =
+      (blocks.next(), blocks.next(), self.exponents().next())
+    else {
+      return Err(IntError::NotAnInteger);
+    };
+
+    N::from_digits(self.digit_values(block), self.radix().into())
+      .ok_or(IntError::Overflow)
+  }
+
+  /// Returns the values of the digits in `block`, skipping separators.
+  pub(crate) fn digit_values(
+    self,
+    block: Span<'lex>,
+  ) -> impl Iterator<Item = u32> + 'lex {
+    let sep = self.rule().unwrap().separator.as_str();
+    let digits = self.digit_rule();
+    let mut text = block.text();
+    iter::from_fn(move || loop {
+      if !sep.is_empty() {
+        if let Some(rest) = text.strip_prefix(sep) {
+          text = rest;
+          continue;
+        }
+      }
+
+      let next = text.chars().next()?;
+      text = &text[next.len_utf8()..];
+
+      return Some(digits.digit_value(next).unwrap_or_else(|| {
+        bug!("an invalid digit slipped past the lexer: {:?}", next)
+      }));
+    })
+  }
+
+  pub(crate) fn digit_rule(self) -> &'lex rule::Digits {
     let rule = self.rule().unwrap();
     if self.idx == 0 {
       &rule.mant
@@ -819,6 +923,7 @@ impl<'lex> Digital<'lex> {
 
 /// A sign for a [`Digital`] literal.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Sign {
   /// Positive.
   Pos,
@@ -832,6 +937,45 @@ impl Default for Sign {
   }
 }
 
+/// An error from [`Digital::to_u64()`] and friends.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IntError {
+  /// The literal has more than one digit block, or has an exponent.
+  NotAnInteger,
+  /// The literal's value is out of range for the requested type.
+  Overflow,
+}
+
+impl fmt::Display for IntError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::NotAnInteger => f.write_str("literal is not an integer"),
+      Self::Overflow => f.write_str("integer literal out of range"),
+    }
+  }
+}
+
+/// An error from [`Digital::to_f64()`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FloatError {
+  /// The literal has too many digit blocks or exponents.
+  NotAFloat,
+  /// The literal's value is too large to be finite.
+  Overflow,
+  /// The literal's radix is not supported; see [`Digital::to_float()`].
+  Exotic(fp::Exotic),
+}
+
+impl fmt::Display for FloatError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::NotAFloat => f.write_str("literal is not a float"),
+      Self::Overflow => f.write_str("float literal out of range"),
+      Self::Exotic(e) => write!(f, "{e:?}"),
+    }
+  }
+}
+
 /// A base 2 integer type of portable size that can be parsed from any radix.
 pub trait FromRadix: Sized {
   /// Parses a value from` data`, given it's in a particular radix.
@@ -839,12 +983,22 @@ pub trait FromRadix: Sized {
   /// The result must be exact: truncation or rounding are not permitted.
   /// Occurrences thereof must be signaled by returning `None`.
   ///
-  /// The implementation may assume that `radix` is in `2..=16`, and that the
-  /// the only bytes that occur in `data` are `0..=9`, `a..=f`, and `A..=F`, as
+  /// The implementation may assume that `radix` is in `2..=36`, and that the
+  /// the only bytes that occur in `data` are `0..=9`, `a..=z`, and `A..=Z`, as
   /// would be implied by the value of `radix`; also, the byte sequence in
   /// `sep` may appear, which should be ignored.
   fn from_radix(data: &str, radix: u8, sep: &str) -> Option<Self>;
 
+  /// Builds a value out of a sequence of digit values, most significant
+  /// first. This is used for digits written in a custom alphabet; see
+  /// [`Digits::with_alphabet()`][crate::rule::Digits::with_alphabet].
+  ///
+  /// Each digit is less than `radix`. As with [`FromRadix::from_radix()`],
+  /// the result must be exact, and an empty sequence of digits must produce
+  /// `None`.
+  fn from_digits(digits: impl Iterator<Item = u32>, radix: u32)
+    -> Option<Self>;
+
   /// Equivalent to `std`'s [`checked_neg()`][i32::checked_neg()].
   fn checked_neg(self) -> Option<Self>;
 }
@@ -857,9 +1011,7 @@ macro_rules! impl_radix {
         radix: u8,
         sep: &str,
       ) -> Option<Self> {
-        let mut total: Self = 0;
-        let mut count = 0;
-        while !data.is_empty() {
+        let digits = iter::from_fn(|| loop {
           if !sep.is_empty() {
             if let Some(rest) = data.strip_prefix(sep) {
               data = rest;
@@ -867,16 +1019,29 @@ macro_rules! impl_radix {
             }
           }
 
-          let next = data.chars().next().unwrap();
+          let next = data.chars().next()?;
           data = &data[next.len_utf8()..];
 
-          let digit = next.to_digit(radix as _).unwrap_or_else(|| {
+          return Some(next.to_digit(radix as _).unwrap_or_else(|| {
             bug!("an invalid digit slipped past the lexer: {:?}", next)
-          });
+          }));
+        });
 
-          total = total
-            .checked_mul(radix as Self)
-            .and_then(|i| i.checked_add(digit as Self))?;
+        Self::from_digits(digits, radix as u32)
+      }
+
+      fn from_digits(
+        digits: impl Iterator<Item = u32>,
+        radix: u32,
+      ) -> Option<Self> {
+        let radix = Self::try_from(radix).ok();
+        let mut total: Self = 0;
+        let mut count = 0;
+        for digit in digits {
+          if total != 0 {
+            total = total.checked_mul(radix?)?;
+          }
+          total = total.checked_add(digit.try_into().ok()?)?;
 
           count += 1;
         }
@@ -1002,16 +1167,21 @@ impl<'lex> Quoted<'lex> {
   ///
   /// There are two kinds of content: either a literal span of Unicode scalars
   /// (represented as a [`Span`] pointing to those characters) or a single
-  /// escape, potentially with some side data.
+  /// escape, potentially with some side data. If the rule has interpolation
+  /// holes, there is a third kind, for the contents of each hole, and if it
+  /// has expanding escapes, a fourth, for escapes that ilex has decoded.
   ///
   /// It is up to the user of the library to decode these two content types into
   /// strings. [`Quoted::to_utf8()`] helps with the common case of doing this for
-  /// UTF-8 strings.
+  /// UTF-8 strings, and [`Quoted::decode_utf8()`] and
+  /// [`Quoted::decode_codes()`] do it with the rule's escape decoders.
   pub fn raw_content(self) -> impl Iterator<Item = Content<Span<'lex>>> + 'lex {
     let file = self.stream.file();
     let mut next = self.meta.marks[0];
     let mut is_escape = false;
     let mut marks = &self.meta.marks[1..];
+    let mut holes = &self.meta.holes[..];
+    let mut expansions = &self.meta.expansions[..];
 
     iter::from_fn(move || loop {
       return match is_escape {
@@ -1043,6 +1213,18 @@ impl<'lex> Quoted<'lex> {
           marks = rest;
           is_escape = false;
 
+          if holes.first().is_some_and(|&(hole, _)| hole == start) {
+            holes = &holes[1..];
+            let data = file.span(data_start as usize..data_end as usize);
+            return Some(Content::Hole(data));
+          }
+
+          if expansions.first().is_some_and(|(esc, _)| *esc == start) {
+            expansions = &expansions[1..];
+            let span = file.span(start as usize..end as usize);
+            return Some(Content::Expanded(span));
+          }
+
           let span = file.span(start as usize..esc_end as usize);
           let data = (data_start != data_end)
             .then(|| file.span(data_start as usize..data_end as usize));
@@ -1052,6 +1234,29 @@ impl<'lex> Quoted<'lex> {
     })
   }
 
+  /// Returns cursors over the tokens in each of this token's interpolation
+  /// holes, in order.
+  ///
+  /// Each one corresponds to a [`Content::Hole`] in
+  /// [`Quoted::raw_content()`].
+  pub fn holes(self) -> impl Iterator<Item = Cursor<'lex>> + 'lex {
+    let stream = self.stream;
+    self
+      .meta
+      .holes
+      .iter()
+      .map(|&(_, idx)| stream.holes[idx].cursor())
+  }
+
+  /// Returns the strings that this token's expanding escapes expanded to, in
+  /// order.
+  ///
+  /// Each one corresponds to a [`Content::Expanded`] in
+  /// [`Quoted::raw_content()`]. See [`rule::Quoted::expanding_escape()`].
+  pub fn expansions(self) -> impl Iterator<Item = &'lex str> + 'lex {
+    self.meta.expansions.iter().map(|(_, s)| &**s)
+  }
+
   /// Returns the unique single literal content of this token, if it is unique.
   pub fn literal(self) -> Option<Span<'lex>> {
     if self.meta.marks.len() > 2 {
@@ -1064,6 +1269,10 @@ impl<'lex> Quoted<'lex> {
 
   /// Constructs a UTF-8 string in the "obvious way", using this token and a
   /// mapping function for escapes.
+  ///
+  /// Interpolation holes are passed to the mapping function as escapes with
+  /// no data, whose span is the contents of the hole. Expanding escapes are
+  /// replaced with their expansions, without calling the mapping function.
   pub fn to_utf8(
     self,
     mut decode_esc: impl FnMut(Span, Option<Span<'lex>>, &mut String),
@@ -1072,20 +1281,133 @@ impl<'lex> Quoted<'lex> {
       .raw_content()
       .map(|c| match c {
         Content::Lit(sp) => sp.text().len(),
-        Content::Esc(..) => 1,
+        Content::Esc(..) | Content::Hole(..) | Content::Expanded(..) => 1,
       })
       .sum();
 
     let mut buf = String::with_capacity(total);
+    let mut expansions = self.expansions();
     for chunk in self.raw_content() {
       match chunk {
         Content::Lit(sp) => buf.push_str(sp.text()),
         Content::Esc(sp, data) => decode_esc(sp, data, &mut buf),
+        Content::Hole(sp) => decode_esc(sp, None, &mut buf),
+        Content::Expanded(_) => buf.push_str(expansions.next().unwrap()),
       }
     }
     buf
   }
 
+  /// Decodes this token into the character codes it stands for, using the
+  /// escape decoders of its rule.
+  ///
+  /// Literal chunks become their Unicode scalar values, each escape becomes
+  /// the code that its [decoder][rule::Quoted::escape_decoder()] returns for
+  /// it, and expanding escapes become their expansions. Any `u32` is allowed,
+  /// so this is suitable for byte strings and other non-Unicode encodings.
+  ///
+  /// Escapes without a decoder, escapes whose decoder fails, and interpolation
+  /// holes become diagnostics, and contribute no codes.
+  #[track_caller]
+  pub fn decode_codes(self, report: &Report) -> Vec<u32> {
+    let mut codes = Vec::new();
+    self.decode(report, Location::caller(), |code, _| codes.push(code));
+    codes
+  }
+
+  /// Decodes this token into the string it stands for, using the escape
+  /// decoders of its rule.
+  ///
+  /// This is like [`Quoted::decode_codes()`], except that an escape whose
+  /// code is not a Unicode scalar value, such as a surrogate, is diagnosed and
+  /// replaced with U+FFFD REPLACEMENT CHARACTER.
+  ///
+  /// ```
+  /// # use ilex::*;
+  /// let mut builder = Spec::builder();
+  /// let string = builder.rule(rule::Quoted::new('"').add_rust_escapes());
+  /// let spec = builder.compile();
+  ///
+  /// let ctx = Context::new();
+  /// let report = ctx.new_report();
+  /// let file = ctx.new_file("<i>", r#""caf\u{e9}\n\x41\\""#);
+  /// let stream = file.lex(&spec, &report).unwrap();
+  ///
+  /// let quoted = stream.cursor().next().unwrap().quoted().unwrap();
+  /// assert_eq!(quoted.decode_utf8(&report), "café\nA\\");
+  /// ```
+  #[track_caller]
+  pub fn decode_utf8(self, report: &Report) -> Yarn {
+    let here = Location::caller();
+    let mut buf = String::new();
+    self.decode(report, here, |code, span| {
+      buf.push(char::from_u32(code).unwrap_or_else(|| {
+        report
+          .builtins(self.spec())
+          .invalid_escape(span, f!("{code:#x} is not a Unicode scalar value"))
+          .reported_at(here);
+        char::REPLACEMENT_CHARACTER
+      }))
+    });
+    buf.into()
+  }
+
+  /// Walks the content of this token, calling `push` with each character code
+  /// it decodes to, along with the span it came from.
+  fn decode(
+    self,
+    report: &Report,
+    here: &'static Location<'static>,
+    mut push: impl FnMut(u32, Span<'lex>),
+  ) {
+    let rule = self.rule().unwrap();
+    let mut expansions = self.expansions();
+    // Each escape is a text end mark followed by four escape marks, the last
+    // of which is where the escape, including any closing delimiter, ends.
+    let mut esc_ends = self.meta.marks.iter().skip(5).step_by(5);
+    for chunk in self.raw_content() {
+      let esc_end = match chunk {
+        Content::Lit(_) => None,
+        _ => esc_ends.next(),
+      };
+      match chunk {
+        Content::Lit(sp) => sp.text().chars().for_each(|c| push(c.into(), sp)),
+        Content::Expanded(sp) => {
+          for c in expansions.next().unwrap().chars() {
+            push(c.into(), sp)
+          }
+        }
+        Content::Esc(key, data) => {
+          let end = *esc_end.unwrap() as usize;
+          let span = key.file().span(key.start()..end);
+          let Some(decoder) = rule.decoders.get(key.text()) else {
+            report
+              .builtins(self.spec())
+              .invalid_escape(span, "no decoder is registered for this escape")
+              .reported_at(here);
+            continue;
+          };
+
+          match (decoder.parse)(data.map_or("", |d| d.text())) {
+            Some(code) => push(code, span),
+            None => {
+              report
+                .builtins(self.spec())
+                .invalid_escape(span, "could not decode this escape")
+                .reported_at(here);
+            }
+          }
+        }
+        Content::Hole(sp) => {
+          report
+            .error("cannot decode an interpolated string")
+            .saying(sp, "interpolation hole here")
+            .reported_at(here);
+        }
+      }
+    }
+  }
+
   /// Returns this token's prefix.
   pub fn prefix(self) -> Option<Span<'lex>> {
     self.stream.lookup_prefix(self.id)
@@ -1119,6 +1441,16 @@ pub enum Content<Span> {
   /// An escape sequence, which may have associated data (e.g. the `NN` from a
   /// `\xNN`).
   Esc(Span, Option<Span>),
+
+  /// The contents of an interpolation hole, not including its delimiters.
+  ///
+  /// The tokens within it can be obtained from [`Quoted::holes()`].
+  Hole(Span),
+
+  /// An expanding escape sequence, including its data and closer.
+  ///
+  /// The string it expanded to can be obtained from [`Quoted::expansions()`].
+  Expanded(Span),
 }
 
 impl<Span> Content<Span> {
@@ -1234,6 +1566,9 @@ impl<'lex> Any<'lex> {
 
     let (pre, suf, kind) = match self {
       Any::Eof(_) => return yarn!("<eof>"),
+      Any::Keyword(tok) if tok.text().is_empty() => {
+        return self.lexeme().to_yarn(spec);
+      }
       Any::Keyword(tok) => return yarn!("`{}`", tok.text()),
       Any::Bracket(d) => {
         return yarn!("`{} ... {}`", d.open().text(), d.close().text());
@@ -0,0 +1,163 @@
+//! `serde` support for [`Stream`]s.
+
+use std::borrow::Cow;
+
+use bitvec::vec::BitVec;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::file::File;
+use crate::rt;
+use crate::spec::Lexeme;
+use crate::spec::Spec;
+use crate::token::Id;
+use crate::token::Stream;
+
+/// The serialized form of a stream. Everything that refers to the file's text
+/// does so by byte offset, so the file itself is not included.
+#[derive(Serialize, Deserialize)]
+struct Wire<'a> {
+  version: u32,
+  /// The length of the file the stream was lexed from, used for sanity
+  /// checking when rebinding.
+  len: usize,
+  /// The number of rules in the spec the stream was lexed with.
+  rules: usize,
+
+  toks: Cow<'a, [rt::Token]>,
+  meta_idx: Cow<'a, [Id]>,
+  meta: Cow<'a, [rt::Metadata]>,
+  silent: Vec<usize>,
+  holes: Vec<Wire<'a>>,
+}
+
+const VERSION: u32 = 1;
+
+impl<'a> Wire<'a> {
+  fn new(stream: &'a Stream) -> Self {
+    Wire {
+      version: VERSION,
+      len: stream.file.len(),
+      rules: stream.spec.builder.rules.len(),
+      toks: Cow::Borrowed(&stream.toks),
+      meta_idx: Cow::Borrowed(&stream.meta_idx),
+      meta: Cow::Borrowed(&stream.meta),
+      silent: stream.silent.iter_ones().collect(),
+      holes: stream.holes.iter().map(Wire::new).collect(),
+    }
+  }
+
+  fn bind<'ctx, E: serde::de::Error>(
+    self,
+    file: File<'ctx>,
+    spec: &'ctx Spec,
+  ) -> Result<Stream<'ctx>, E> {
+    if self.version != VERSION {
+      return Err(E::custom(format_args!(
+        "unsupported token stream version {}",
+        self.version
+      )));
+    }
+    if self.len != file.len() {
+      return Err(E::custom(format_args!(
+        "token stream was lexed from a file of length {}, but `{}` has length {}",
+        self.len,
+        file.path(),
+        file.len(),
+      )));
+    }
+    if self.rules != spec.builder.rules.len() {
+      return Err(E::custom(format_args!(
+        "token stream was lexed with a spec with {} rules, but this spec has {}",
+        self.rules,
+        spec.builder.rules.len(),
+      )));
+    }
+
+    let mut prev = 0;
+    for tok in self.toks.iter() {
+      let lexeme = tok.lexeme;
+      let known = lexeme.is_aux()
+        || lexeme == Lexeme::eof().any()
+        || (0..self.rules).contains(&lexeme.index());
+      if !known {
+        return Err(E::custom(format_args!("unknown lexeme {lexeme:?}")));
+      }
+      if tok.end < prev || tok.end as usize > self.len {
+        return Err(E::custom("token stream has out-of-order token offsets"));
+      }
+      prev = tok.end;
+    }
+    if self.meta_idx.len() != self.meta.len()
+      || self.meta_idx.iter().any(|id| id.idx() >= self.toks.len())
+    {
+      return Err(E::custom("token stream has inconsistent metadata"));
+    }
+
+    let mut silent = BitVec::new();
+    for idx in self.silent {
+      if silent.len() <= idx {
+        silent.resize(idx + 1, false);
+      }
+      silent.set(idx, true);
+    }
+
+    Ok(Stream {
+      file,
+      spec,
+      toks: self.toks.into_owned(),
+      meta_idx: self.meta_idx.into_owned(),
+      meta: self.meta.into_owned(),
+      silent,
+      holes: self
+        .holes
+        .into_iter()
+        .map(|hole| hole.bind(file, spec))
+        .collect::<Result<_, _>>()?,
+    })
+  }
+}
+
+impl Serialize for Stream<'_> {
+  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    Wire::new(self).serialize(s)
+  }
+}
+
+impl<'ctx> Stream<'ctx> {
+  /// Deserializes a stream that was serialized with this stream's
+  /// [`Serialize`] implementation, rebinding it to the file and spec it was
+  /// originally lexed from.
+  ///
+  /// Tokens are stored as byte offsets into `file`, so the resulting stream
+  /// yields the same spans and text as the original. This returns an error if
+  /// `file` or `spec` is obviously not the one the stream was lexed from, but
+  /// not every mismatch can be detected.
+  ///
+  /// ```
+  /// # use ilex::rule::*;
+  /// # use ilex::token::Stream;
+  /// let mut spec = ilex::Spec::builder();
+  /// spec.rule(Ident::new());
+  /// let spec = spec.compile();
+  ///
+  /// let ctx = ilex::Context::new();
+  /// let report = ctx.new_report();
+  /// let file = ctx.new_file("x.txt", "hello world");
+  /// let stream = file.lex(&spec, &report).unwrap();
+  ///
+  /// let json = serde_json::to_string(&stream).unwrap();
+  /// let mut de = serde_json::Deserializer::from_str(&json);
+  /// let reloaded = Stream::deserialize(file, &spec, &mut de).unwrap();
+  /// assert_eq!(reloaded.summary(), stream.summary());
+  /// ```
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    file: File<'ctx>,
+    spec: &'ctx Spec,
+    d: D,
+  ) -> Result<Self, D::Error> {
+    Wire::deserialize(d)?.bind(file, spec)
+  }
+}
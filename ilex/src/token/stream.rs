@@ -32,6 +32,9 @@ pub struct Stream<'ctx> {
   pub(crate) meta: Vec<rt::Metadata>,
 
   pub(crate) silent: BitVec, // Set of lexemes that have been silenced.
+
+  // Streams for the interpolation holes of quoted tokens in this stream.
+  pub(crate) holes: Vec<Stream<'ctx>>,
 }
 
 impl<'ctx> Stream<'ctx> {
@@ -131,9 +134,11 @@ impl<'ctx> Stream<'ctx> {
 
     Some(match self.spec().rule(tok.lexeme) {
       rule::Any::Comment(..) => return None,
-      rule::Any::Keyword(..) | rule::Any::LineEnd(..) => {
-        token::Keyword { stream: self, id }.into()
-      }
+      rule::Any::Keyword(..)
+      | rule::Any::LineEnd(..)
+      | rule::Any::Indent(..)
+      | rule::Any::Dedent(..)
+      | rule::Any::Regex(..) => token::Keyword { stream: self, id }.into(),
       rule::Any::Ident(..) => token::Ident { stream: self, id }.into(),
 
       rule::Any::Bracket(..) => {
@@ -37,13 +37,22 @@ impl<'a> Cursor<'a> {
             .entry("contents", tok.contents().summary()),
         ),
 
-        Any::Ident(tok) => Doc::single(
-          "ident",
-          doc
+        Any::Ident(tok) => {
+          let doc = doc
             .entry("prefix", tok.prefix().map(span2doc))
             .entry("suffix", tok.suffix().map(span2doc))
-            .entry("name", span2doc(tok.name())),
-        ),
+            .entry("name", span2doc(tok.name()));
+
+          #[cfg(feature = "normalize")]
+          let doc = doc.entry(
+            "normalized",
+            Some(tok.normalized())
+              .filter(|n| n != tok.name().text())
+              .map(|n| n.to_box()),
+          );
+
+          Doc::single("ident", doc)
+        }
 
         Any::Digital(tok) => Doc::single(
           "ident",
@@ -66,8 +75,9 @@ impl<'a> Cursor<'a> {
             ),
         ),
 
-        Any::Quoted(tok) => Doc::single(
-          "quoted",
+        Any::Quoted(tok) => Doc::single("quoted", {
+          let mut holes = tok.holes();
+          let mut expansions = tok.expansions();
           doc
             .entry("prefix", tok.prefix().map(span2doc))
             .entry("suffix", tok.suffix().map(span2doc))
@@ -79,9 +89,15 @@ impl<'a> Cursor<'a> {
                 Content::Esc(esc, data) => Doc::new()
                   .entry("esc", span2doc(esc))
                   .entry("data", data.map(span2doc)),
+                Content::Hole(hole) => Doc::new()
+                  .entry("hole", span2doc(hole))
+                  .entry("contents", holes.next().map(|c| c.summary())),
+                Content::Expanded(esc) => Doc::new()
+                  .entry("esc", span2doc(esc))
+                  .entry("expanded", expansions.next()),
               }),
-            ),
-        ),
+            )
+        }),
       }
     }))
   }
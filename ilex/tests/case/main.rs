@@ -0,0 +1,39 @@
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/case/*.txt")]
+fn case(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Sql {
+    #[rule(Keyword::new("select").case_insensitive())]
+    select: Lexeme<Keyword>,
+
+    #[rule(Keyword::new("from").case_insensitive())]
+    from: Lexeme<Keyword>,
+
+    #[rule(Keyword::new("café").unicode_case_insensitive())]
+    cafe: Lexeme<Keyword>,
+
+    #[rule(Bracket::paired("begin", "end").case_insensitive())]
+    block: Lexeme<Bracket>,
+
+    #[rule(Quoted::new('\'').prefixes(["", "x"]).case_insensitive_affixes())]
+    string: Lexeme<Quoted>,
+
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report = ctx.new_report();
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Sql::get().spec(), &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "confusables")]
+
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/confusables/*.txt")]
+fn confusables(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Ident::new().detect_confusables())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => {
+      tokens(stream.summary());
+      if let Err(fatal) = report.fatal::<()>() {
+        stderr(fatal.to_string());
+      }
+    }
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
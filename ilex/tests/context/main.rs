@@ -0,0 +1,45 @@
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::token;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/context/*.txt")]
+fn context(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule("//")]
+    comment: Lexeme<Comment>,
+
+    #[rule("(", ")")]
+    parens: Lexeme<Bracket>,
+
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report = ctx.new_report_with(Options {
+    color: false,
+    context_lines: 2,
+    ..Default::default()
+  });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  // Files that lex successfully get a diagnostic for every bracket, so that
+  // multi-line spans are exercised too.
+  let [stderr] = test.outputs(["stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => {
+      for token in &stream {
+        if let token::Any::Bracket(bracket) = token {
+          report.error("found a bracket").saying(bracket, "this one");
+        }
+      }
+      stderr(report.fatal::<()>().unwrap_err().to_string())
+    }
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
@@ -0,0 +1,151 @@
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::token;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/escapes/expand/*.txt")]
+fn expand(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Quoted::new('"')
+      .invalid_escape(r"\")
+      .escapes([r"\n", r"\\", r#"\""#])
+      .expanding_escape(r"\&", ";", |name| match name {
+        "amp" => Some("&".into()),
+        "copy" => Some("©".into()),
+        "hellip" => Some("...".into()),
+        _ => None,
+      })
+      .expanding_escape("\r\n", "", |_| Some("\n".into())))]
+    string: Lexeme<Quoted>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, strings, stderr] =
+    test.outputs(["tokens.yaml", "strings", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => {
+      tokens(stream.summary());
+
+      let mut out = String::new();
+      for token in &mut stream.cursor() {
+        let Ok(quoted) = token::Quoted::try_from(token) else { continue };
+        let string = quoted.to_utf8(|esc, _, buf| {
+          buf.push(match esc.text() {
+            r"\n" => '\n',
+            r"\\" => '\\',
+            r#"\""# => '"',
+            _ => unreachable!(),
+          })
+        });
+        out.push_str(&format!("{string:?}\n"));
+      }
+      strings(out);
+    }
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
+
+#[gilded::test("tests/escapes/c/*.txt")]
+fn c(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Quoted::new('"').add_c_escapes())]
+    string: Lexeme<Quoted>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, bytes, stderr] =
+    test.outputs(["tokens.yaml", "bytes", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => {
+      tokens(stream.summary());
+
+      let mut out = String::new();
+      for token in &mut stream.cursor() {
+        let Ok(quoted) = token::Quoted::try_from(token) else { continue };
+        let codes = quoted.decode_codes(&report);
+        out.push_str(&format!("{codes:x?}\n"));
+      }
+      bytes(out);
+    }
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
+
+#[gilded::test("tests/escapes/rust/*.txt")]
+fn rust(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Quoted::new('"').add_rust_escapes())]
+    string: Lexeme<Quoted>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, strings, stderr] =
+    test.outputs(["tokens.yaml", "strings", "stderr"]);
+  let stream = match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => stream,
+    Err(fatal) => {
+      stderr(fatal.to_string());
+      return;
+    }
+  };
+  tokens(stream.summary());
+
+  let mut out = String::new();
+  for token in &mut stream.cursor() {
+    let Ok(quoted) = token::Quoted::try_from(token) else { continue };
+    // Decode the codes against a separate report, so that each diagnostic is
+    // only recorded once.
+    let codes = quoted.decode_codes(&ctx.new_report());
+    let string = quoted.decode_utf8(&report);
+    out.push_str(&format!("{string:?} {codes:x?}\n"));
+  }
+  strings(out);
+
+  if let Err(fatal) = report.fatal_or(()) {
+    stderr(fatal.to_string());
+  }
+}
+
+#[gilded::test("tests/escapes/json/*.txt")]
+fn json(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Quoted::new('"').add_json_escapes())]
+    string: Lexeme<Quoted>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
@@ -35,6 +35,18 @@ fn greedy(test: &gilded::Test) {
 
     #[rule(Comment::line("//"))]
     comment: Lexeme<Comment>,
+
+    #[rule(Quoted::with(Bracket::heredoc("<<", Ident::new())))]
+    heredoc: Lexeme<Quoted>,
+
+    #[rule(Quoted::with(Bracket::indented_heredoc("<<-", Ident::new())))]
+    indented_heredoc: Lexeme<Quoted>,
+
+    #[rule(Regex::new(r"[0-9]{1,3}(\.[0-9]{1,3}){3}"))]
+    ipv4: Lexeme<Regex>,
+
+    #[rule(Regex::new(r"[0-9]+(\.[0-9]+)*"))]
+    version: Lexeme<Regex>,
   }
 
   let ctx = Context::new();
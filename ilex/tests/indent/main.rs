@@ -0,0 +1,44 @@
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/indent/*.txt")]
+fn indent(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Indentation {
+    #[rule(Indent::new().tab_width(4))]
+    indent: Lexeme<Indent>,
+
+    #[rule(Dedent::new())]
+    dedent: Lexeme<Dedent>,
+
+    #[rule(LineEnd::cancellable("\\"))]
+    nl: Lexeme<LineEnd>,
+
+    #[rule(":")]
+    colon: Lexeme<Keyword>,
+
+    #[rule("(", ")")]
+    parens: Lexeme<Bracket>,
+
+    #[rule(Comment::line("#"))]
+    comment: Lexeme<Comment>,
+
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Indentation::get().spec(), &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
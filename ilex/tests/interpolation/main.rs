@@ -0,0 +1,40 @@
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/interpolation/*.txt")]
+fn interpolation(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Interpolation {
+    #[rule(Quoted::new('`')
+      .with_interpolation("${", "}")
+      .escape("\\$"))]
+    template: Lexeme<Quoted>,
+
+    #[rule("{", "}")]
+    braces: Lexeme<Bracket>,
+
+    #[rule("(", ")")]
+    parens: Lexeme<Bracket>,
+
+    #[rule("+")]
+    plus: Lexeme<Keyword>,
+
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Interpolation::get().spec(), &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
@@ -0,0 +1,61 @@
+use std::fmt::Write;
+
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+use ilex::Spanned;
+
+#[gilded::test("tests/latin1/*.txt")]
+fn latin1(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule("//")]
+    comment: Lexeme<Comment>,
+
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+
+    #[rule(Digital::new(10))]
+    number: Lexeme<Digital>,
+
+    #[rule(Quoted::new('"'))]
+    string: Lexeme<Quoted>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx.new_file_from_latin1(test.path(), test.text());
+
+  // Map every token's span back onto the undecoded input, and check that it
+  // covers the same bytes.
+  let to_input = |n| file.text(..n).chars().count();
+
+  let [tokens, offsets, stderr] =
+    test.outputs(["tokens.yaml", "offsets", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => {
+      let mut out = String::new();
+      for token in &stream {
+        let span = token.span();
+        let range = to_input(span.start())..to_input(span.end());
+        let bytes = span.text().chars().map(|c| c as u8).collect::<Vec<_>>();
+        assert_eq!(bytes, test.text()[range.clone()]);
+
+        writeln!(
+          out,
+          "{:?} -> {:?}: b\"{}\"",
+          span.start()..span.end(),
+          range,
+          bytes.escape_ascii(),
+        )
+        .unwrap();
+      }
+
+      tokens(stream.summary());
+      offsets(out);
+    }
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
@@ -0,0 +1,57 @@
+use ilex::report::Level;
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/lints/*.txt")]
+fn lints(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Ident::new().prefix("$").ascii_only())]
+    var: Lexeme<Ident>,
+
+    #[rule(Digital::new(10)
+      .prefix("0d")
+      .separator_with("_",
+        SeparatorCornerCases {
+          prefix: false,
+          suffix: false,
+          around_point: false,
+          around_exp: false,
+        }))]
+    quiet: Lexeme<Digital>,
+
+    #[rule(Digital::new(10)
+      .separator_with("_",
+        SeparatorCornerCases {
+          prefix: false,
+          suffix: false,
+          around_point: false,
+          around_exp: false,
+        }))]
+    loud: Lexeme<Digital>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  report.set_level("E0007", Level::Warn);
+  report.set_level(Spec::get().quiet, Level::Allow);
+  if test.path().as_str().contains("deny_warnings") {
+    report.deny_warnings();
+  }
+
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => {
+      tokens(stream.summary());
+      stderr(report.fatal::<()>().unwrap_err().to_string());
+    }
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
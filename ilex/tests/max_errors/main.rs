@@ -0,0 +1,36 @@
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/max_errors/*.txt")]
+fn max_errors(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report = ctx.new_report_with(Options {
+    color: false,
+    deduplicate: true,
+    ..Default::default()
+  });
+  report.max_errors(3);
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  // Report every token three times; the repeats are merged, so they should not
+  // use up the limit.
+  let [stderr] = test.outputs(["stderr"]);
+  let stream = file.lex(Spec::get().spec(), &report).unwrap();
+  for token in &stream {
+    for _ in 0..3 {
+      report.error("found a token").at(token);
+    }
+  }
+
+  stderr(report.fatal::<()>().unwrap_err().to_string());
+}
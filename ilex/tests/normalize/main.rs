@@ -0,0 +1,28 @@
+#![cfg(feature = "normalize")]
+
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/normalize/*.txt")]
+fn normalize(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Ident::new().prefix("$"))]
+    raw: Lexeme<Ident>,
+    #[rule(Ident::new().normalize(Normalization::Nfc))]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report = ctx.new_report();
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
@@ -4,6 +4,7 @@ use ilex::rule::*;
 use ilex::token;
 use ilex::Context;
 use ilex::Lexeme;
+use ilex::Spanned;
 
 #[ilex::spec]
 struct Numbers {
@@ -64,12 +65,13 @@ fn numbers(test: &gilded::Test) {
     .new_file_from_bytes(test.path(), test.text(), &report)
     .unwrap();
 
-  let [tokens, fp64, stderr] =
-    test.outputs(["tokens.yaml", "fp64.txt", "stderr"]);
+  let [tokens, fp64, values, stderr] =
+    test.outputs(["tokens.yaml", "fp64.txt", "values.txt", "stderr"]);
 
   match file.lex(Numbers::get().spec(), &report) {
     Ok(stream) => {
       tokens(stream.summary());
+      values(convert(stream.cursor()));
       match parse(Numbers::get(), stream.cursor(), &report) {
         Ok(v) => fp64(format!("{v:#?}")),
         Err(fatal) => stderr(fatal.to_string()),
@@ -80,6 +82,22 @@ fn numbers(test: &gilded::Test) {
   }
 }
 
+/// Converts every number with the diagnostic-free conversion functions.
+fn convert(cursor: ilex::token::Cursor) -> String {
+  let mut out = String::new();
+  for tok in cursor {
+    let Ok(num) = tok.digital() else { continue };
+    out += &format!(
+      "{}: {:?}, {:?}, {:?}\n",
+      num.text(),
+      num.to_u64(),
+      num.to_i128(),
+      num.to_f64(),
+    );
+  }
+  out
+}
+
 fn parse(
   lex: &Numbers,
   mut cursor: ilex::token::Cursor,
@@ -0,0 +1,37 @@
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/ordering/*.txt")]
+fn ordering(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report = ctx.new_report_with(Options {
+    color: false,
+    deduplicate: true,
+    sort_by_position: true,
+    ..Default::default()
+  });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  // Report every token twice, back to front, so that the output is only in
+  // order if the report sorts and deduplicates it.
+  let [stderr] = test.outputs(["stderr"]);
+  let stream = file.lex(Spec::get().spec(), &report).unwrap();
+  let tokens = stream.into_iter().collect::<Vec<_>>();
+  for token in tokens.iter().rev() {
+    report.warn("found a token").at(token);
+    report.warn("found a token").at(token);
+  }
+  report.note("no span here");
+
+  stderr(report.fatal::<()>().unwrap_err().to_string());
+}
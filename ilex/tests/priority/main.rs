@@ -0,0 +1,31 @@
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/priority/*.txt")]
+fn priority(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Priority {
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+
+    // Declared after `ident`, so this would never match without a priority.
+    #[priority(1)]
+    null: Lexeme<Keyword>,
+
+    // Declared after `ident` and not prioritized, so this never matches.
+    nil: Lexeme<Keyword>,
+  }
+
+  let ctx = Context::new();
+  let report = ctx.new_report();
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Priority::get().spec(), &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "serde")]
+
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/report_json/*.txt")]
+fn report_json(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Quoted::new('"')
+      .invalid_escape(r"\")
+      .escapes([r"\n", r"\\", r#"\""#]))]
+    string: Lexeme<Quoted>,
+
+    #[rule("(", ")")]
+    parens: Lexeme<Bracket>,
+
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report = ctx.new_report_with(Options {
+    color: false,
+    show_report_locations: false,
+    ..Default::default()
+  });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [json, sarif] = test.outputs(["json", "sarif.json"]);
+  let _ = file.lex(Spec::get().spec(), &report);
+  json(serde_json::to_string_pretty(&report).unwrap());
+
+  let log: serde_json::Value =
+    serde_json::from_str(&report.to_sarif()).unwrap();
+  sarif(serde_json::to_string_pretty(&log).unwrap());
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "serde")]
+
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::token::Stream;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/roundtrip/*.txt")]
+fn roundtrip(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Quoted::new('`')
+      .with_interpolation("${", "}")
+      .escape("\\$"))]
+    template: Lexeme<Quoted>,
+
+    #[rule("{", "}")]
+    braces: Lexeme<Bracket>,
+
+    #[rule("+")]
+    plus: Lexeme<Keyword>,
+
+    #[rule(Comment::line("//"))]
+    comment: Lexeme<Comment>,
+
+    #[rule(Digital::new(10).point_limit(0..2).exponents(["e"], Digits::new(10).plus()))]
+    number: Lexeme<Digital>,
+
+    #[rule(Ident::new())]
+    ident: Lexeme<Ident>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [json, tokens, stderr] = test.outputs(["json", "tokens.yaml", "stderr"]);
+  let spec = Spec::get().spec();
+  match file.lex(spec, &report) {
+    Ok(stream) => {
+      let text = serde_json::to_string(&stream).unwrap();
+      let mut de = serde_json::Deserializer::from_str(&text);
+      let reloaded = Stream::deserialize(file, spec, &mut de).unwrap();
+      assert_eq!(reloaded.summary(), stream.summary());
+
+      json(text);
+      tokens(reloaded.summary());
+    }
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
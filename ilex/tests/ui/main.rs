@@ -147,6 +147,19 @@ fn digital(test: &gilded::Test) {
             around_exp: false,
           }))]
     n4: Lexeme<Digital>,
+    #[priority(1)]
+    #[rule(Digital::new(32).prefix("0v"))]
+    m3: Lexeme<Digital>,
+    #[rule(Digital::new(36).prefix("0z"))]
+    m4: Lexeme<Digital>,
+    #[rule(Digital::with_alphabet(
+      "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz",
+    ).prefix("b58:"))]
+    m5: Lexeme<Digital>,
+    #[rule(Digital::with_alphabet(
+      "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+    ).prefix("b64:"))]
+    m6: Lexeme<Digital>,
   }
 
   let ctx = Context::new();
@@ -178,6 +191,38 @@ fn eof(test: &gilded::Test) {
 
     #[rule(Quoted::new("'"))]
     q1: Lexeme<Quoted>,
+
+    #[rule(Quoted::new('"').single_line())]
+    q2: Lexeme<Quoted>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
+
+#[gilded::test("tests/ui/named/*.txt")]
+fn named(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Ident::new().named("identifier"))]
+    id: Lexeme<Ident>,
+    #[rule(Digital::new(10).named("number"))]
+    nm: Lexeme<Digital>,
+    #[rule(Quoted::new('"').suffixes(["", "s"]).named("string literal"))]
+    st: Lexeme<Quoted>,
+    #[named("char literal")]
+    #[rule(Quoted::new("'").suffixes(["", "c"]).named("ignored"))]
+    ch: Lexeme<Quoted>,
   }
 
   let ctx = Context::new();
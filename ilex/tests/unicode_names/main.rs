@@ -0,0 +1,28 @@
+#![cfg(feature = "unicode-names")]
+
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Lexeme;
+
+#[gilded::test("tests/unicode_names/*.txt")]
+fn unicode_names(test: &gilded::Test) {
+  #[ilex::spec]
+  struct Spec {
+    #[rule(Quoted::new('"').invalid_escape(r"\").unicode_name_escape(r"\N"))]
+    string: Lexeme<Quoted>,
+  }
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(Spec::get().spec(), &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}
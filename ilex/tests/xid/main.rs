@@ -0,0 +1,35 @@
+use std::sync::OnceLock;
+
+use ilex::report::Options;
+use ilex::rule::*;
+use ilex::Context;
+use ilex::Spec;
+
+#[gilded::test("tests/xid/*.txt")]
+fn xid(test: &gilded::Test) {
+  static SPEC: OnceLock<Spec> = OnceLock::new();
+  let spec = SPEC.get_or_init(|| {
+    let mut builder = Spec::builder();
+    builder.xid_tables(
+      ['a'..='z', 'A'..='Z', '_'..='_', 'α'..='ω'],
+      ['a'..='z', 'A'..='Z', '0'..='9', '_'..='_', 'α'..='ω'],
+    );
+    builder.rule(Ident::new());
+    builder.rule(Digital::new(10));
+    builder.rule(Quoted::new('"'));
+    builder.compile()
+  });
+
+  let ctx = Context::new();
+  let report =
+    ctx.new_report_with(Options { color: false, ..Default::default() });
+  let file = ctx
+    .new_file_from_bytes(test.path(), test.text(), &report)
+    .unwrap();
+
+  let [tokens, stderr] = test.outputs(["tokens.yaml", "stderr"]);
+  match file.lex(spec, &report) {
+    Ok(stream) => tokens(stream.summary()),
+    Err(fatal) => stderr(fatal.to_string()),
+  }
+}